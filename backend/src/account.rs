@@ -0,0 +1,122 @@
+use models::account::{Credentials, Session};
+use pbkdf2::{pbkdf2_check, pbkdf2_simple};
+use rand::Rng;
+use rocket::http::Status;
+use rocket::request::{self, FromRequest, Request};
+use rocket::response::status;
+use rocket::{Outcome, State};
+use rocket_contrib::json;
+
+use super::store::{GameStore, Store, User};
+
+// PBKDF2 iterations for each password hash; the salt is chosen per password
+const HASH_ROUNDS: u32 = 10_000;
+const TOKEN_BYTES: usize = 32;
+
+type Refusal = status::Custom<json::JsonValue>;
+
+fn refuse(status: Status, message: &str) -> Refusal {
+    status::Custom(status, json!({ "error": message }))
+}
+
+/**
+ *  Request guard for routes that need someone logged in. The session token
+ *  comes from an `Authorization: Bearer <token>` header; requests without a
+ *  valid one are answered with 401.
+ */
+pub struct LoggedIn {
+    pub username: String,
+    token: String
+}
+
+impl<'a, 'r> FromRequest<'a, 'r> for LoggedIn {
+    type Error = ();
+
+    fn from_request(request: &'a Request<'r>) -> request::Outcome<Self, Self::Error> {
+        let token = request.headers().get_one("Authorization")
+            .and_then(|header| header.strip_prefix("Bearer "))
+            .map(str::trim);
+        let store = match request.guard::<State<Store>>().succeeded() {
+            Some(store) => store,
+            None => return Outcome::Failure((Status::InternalServerError, ())),
+        };
+        match token.and_then(|token| session_user(store.as_ref(), token).map(|username| (token, username))) {
+            Some((token, username)) => Outcome::Success(LoggedIn { username, token: token.to_string() }),
+            None => Outcome::Failure((Status::Unauthorized, ())),
+        }
+    }
+}
+
+/**
+ *  Create an account and log it in. The password is stored as a salted
+ *  PBKDF2 hash. Refused with 422 for unusable credentials and 409 when the
+ *  username is taken.
+ */
+#[post("/register", format = "application/json", data = "<credentials>")]
+pub fn register(credentials: json::Json<Credentials>, store: State<Store>) -> Result<json::Json<Session>, Refusal> {
+    let credentials = credentials.into_inner();
+    credentials.validate().map_err(|message| refuse(Status::UnprocessableEntity, &message))?;
+    if store.find_user(&credentials.username).map_err(store_error)?.is_some() {
+        return Err(refuse(Status::Conflict, "That username is taken"));
+    }
+    let password_hash = pbkdf2_simple(&credentials.password, HASH_ROUNDS)
+        .map_err(|e| refuse(Status::InternalServerError, &e.to_string()))?;
+    // Someone registering the same name at the same time may get there first
+    if !store.insert_user(User { username: credentials.username.clone(), password_hash }).map_err(store_error)? {
+        return Err(refuse(Status::Conflict, "That username is taken"));
+    }
+    start_session(store.as_ref(), credentials.username).map(json::Json)
+}
+
+/**
+ *  Log in to an existing account, answered with 401 if the username or
+ *  password is wrong
+ */
+#[post("/login", format = "application/json", data = "<credentials>")]
+pub fn login(credentials: json::Json<Credentials>, store: State<Store>) -> Result<json::Json<Session>, Refusal> {
+    let credentials = credentials.into_inner();
+    match store.find_user(&credentials.username).map_err(store_error)? {
+        Some(user) if pbkdf2_check(&credentials.password, &user.password_hash).is_ok() =>
+            start_session(store.as_ref(), credentials.username).map(json::Json),
+        _ => Err(refuse(Status::Unauthorized, "Wrong username or password")),
+    }
+}
+
+/**
+ *  End the session whose token the request carries
+ */
+#[post("/logout")]
+pub fn logout(user: LoggedIn, store: State<Store>) -> Result<status::NoContent, Refusal> {
+    store.delete_session(&user.token).map_err(store_error)?;
+    Ok(status::NoContent)
+}
+
+/**
+ *  Whether `name` belongs to an account, so only its owner may play as it.
+ *  A failed lookup is an error rather than a no, so nobody can take a
+ *  registered name while the store is down.
+ */
+pub fn is_registered(store: &dyn GameStore, name: &str) -> Result<bool, String> {
+    store.find_user(name).map(|user| user.is_some())
+}
+
+/**
+ *  Username logged in with `token`, if the session exists
+ */
+pub fn session_user(store: &dyn GameStore, token: &str) -> Option<String> {
+    store.session_user(token).ok()?
+}
+
+fn start_session(store: &dyn GameStore, username: String) -> Result<Session, Refusal> {
+    let bytes: [u8; TOKEN_BYTES] = rand::thread_rng().gen();
+    let token = bytes.iter().fold(String::new(), |mut token, byte| {
+        token.push_str(&format!("{:02x}", byte));
+        token
+    });
+    store.insert_session(&token, &username).map_err(store_error)?;
+    Ok(Session { username, token })
+}
+
+fn store_error(message: String) -> Refusal {
+    refuse(Status::InternalServerError, &message)
+}
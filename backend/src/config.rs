@@ -0,0 +1,113 @@
+use rocket::config::{Config, Value};
+
+/**
+ *  Where games, ratings and accounts are kept
+ */
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StoreKind {
+    Mongo,
+    Sqlite,
+    Memory
+}
+
+impl StoreKind {
+    pub fn from_name(name: &str) -> Option<StoreKind> {
+        match name {
+            "mongo" => Some(StoreKind::Mongo),
+            "sqlite" => Some(StoreKind::Sqlite),
+            "memory" => Some(StoreKind::Memory),
+            _ => None,
+        }
+    }
+}
+
+/**
+ *  Settings of the game server. They are read from the extras of Rocket's
+ *  config, so each can be set in Rocket.toml or overridden with a
+ *  `ROCKET_<NAME>` environment variable:
+ *
+ *  ```toml
+ *  store = "sqlite"
+ *  mongo_uri = "mongodb://db.example.com:27017/"
+ *  database_name = "TOOTandOTTO-Connect4-DB"
+ *  sqlite_path = "games.sqlite"
+ *  allowed_origins = ["https://connect4.example.com"]
+ *  rooms_address = "0.0.0.0:8001"
+ *  ```
+ *
+ *  The port and address the routes are served on are Rocket's own `port`
+ *  and `address`.
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct ServerConfig {
+    pub store: StoreKind,
+    pub mongo_uri: String,
+    pub database_name: String,
+    pub sqlite_path: String,
+    /// Origins the browser may call the routes from, which is where the frontend is served
+    pub allowed_origins: Vec<String>,
+    /// Address of the WebSocket server for online games
+    pub rooms_address: String,
+    pub port: u16
+}
+
+impl Default for ServerConfig {
+    fn default() -> ServerConfig {
+        ServerConfig {
+            store: StoreKind::Mongo,
+            mongo_uri: String::from("mongodb://localhost:27017/"),
+            database_name: String::from("TOOTandOTTO-Connect4-DB"),
+            // Relative to the directory the backend runs in
+            sqlite_path: String::from("games.sqlite"),
+            allowed_origins: vec![String::from("http://localhost:8080")],
+            rooms_address: String::from("localhost:8001"),
+            port: 8000
+        }
+    }
+}
+
+impl ServerConfig {
+    /**
+     *  Read the settings from Rocket's config, using the defaults for any not
+     *  given. A setting of the wrong type, or an unknown store, is an error
+     *  naming the setting.
+     */
+    pub fn from_rocket(config: &Config) -> Result<ServerConfig, String> {
+        let defaults = ServerConfig::default();
+        let store = match string(config, "store")? {
+            Some(name) => StoreKind::from_name(&name)
+                .ok_or_else(|| format!("Unknown store {}, use mongo, sqlite or memory", name))?,
+            None => defaults.store,
+        };
+        Ok(ServerConfig {
+            store,
+            mongo_uri: string(config, "mongo_uri")?.unwrap_or(defaults.mongo_uri),
+            database_name: string(config, "database_name")?.unwrap_or(defaults.database_name),
+            sqlite_path: string(config, "sqlite_path")?.unwrap_or(defaults.sqlite_path),
+            allowed_origins: strings(config, "allowed_origins")?.unwrap_or(defaults.allowed_origins),
+            rooms_address: string(config, "rooms_address")?.unwrap_or(defaults.rooms_address),
+            port: config.port
+        })
+    }
+}
+
+fn string(config: &Config, name: &str) -> Result<Option<String>, String> {
+    match config.extras.get(name) {
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(format!("{} must be a string", name)),
+        None => Ok(None),
+    }
+}
+
+// A list of strings, or one string for a list of one, as an environment variable is easier to give
+fn strings(config: &Config, name: &str) -> Result<Option<Vec<String>>, String> {
+    match config.extras.get(name) {
+        Some(Value::String(value)) => Ok(Some(vec![value.clone()])),
+        Some(Value::Array(values)) => values.iter()
+            .map(|value| value.as_str().map(String::from).ok_or_else(|| format!("{} must be a list of strings", name)))
+            .collect::<Result<Vec<String>, String>>()
+            .map(Some),
+        Some(_) => Err(format!("{} must be a list of strings", name)),
+        None => Ok(None),
+    }
+}
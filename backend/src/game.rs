@@ -1,110 +1,94 @@
-use bson::doc;
-// use bson::oid::ObjectId;
-use chrono::Utc;
+use log::error;
 use models::game::Game;
-use rocket::response::content;
+use models::listing::{GamePage, GameQuery, SortOrder};
+use models::replay::Replay;
+use rocket::http::Status;
+use rocket::response::status;
+use rocket::State;
 use rocket_contrib::json;
 
-use super::{MC, DB_NAME, GAMES_COLLECTION};
+use super::account::{self, LoggedIn};
+use super::store::{GameStore, Store};
 
 /** 
  *  Insert game into DB using JSON data obtained POST request body
  *  Read: https://rocket.rs/v0.4/guide/requests/#json
+ *
+ *  The moves are replayed and the winner is worked out from them, so the
+ *  winner_name sent by the client is ignored. Games with illegal moves, or
+ *  that are not over, are refused with 422 and an {"error": ...} body.
+ *
+ *  A player named after an account must be the account logged in with the
+ *  request's bearer token, otherwise the game is refused with 403. If the
+ *  accounts cannot be checked the game is refused with 500.
+ *
+ *  The game number sent by the client is ignored too. The stored game is
+ *  sent back with the number it was given.
  */
 #[post("/insert-game", format = "application/json", data = "<game>")]
-pub fn insert_game(game: json::Json<Game>) -> String {
-    unsafe {
-        match MC {
-            Some(ref client) => {
-                let db = client.database(DB_NAME);
-                let collection = db.collection(GAMES_COLLECTION);
-
-                let serialized_game = bson::to_bson(&game.0).unwrap();
-
-                if let bson::Bson::Document(document) = serialized_game {
-                    let _result = collection.insert_one(document, None);  // Insert into a MongoDB collection
-                } else {
-                    String::from("Error converting the BSON object into a MongoDB document");
-                }
-
-                String::from("Successfully inserted test game!")
-            },
-            None => String::from("Error"),
+pub fn insert_game(game: json::Json<Game>, user: Option<LoggedIn>, store: State<Store>) -> Result<json::Json<Game>, status::Custom<json::JsonValue>> {
+    let mut game = game.into_inner();
+    for name in [&game.player1_name, &game.player2_name].iter() {
+        let logged_in = user.as_ref().map_or(false, |user| &user.username == *name);
+        let registered = account::is_registered(store.as_ref(), name).map_err(|message| {
+            status::Custom(Status::InternalServerError, json!({ "error": message }))
+        })?;
+        if !logged_in && registered {
+            let error = format!("{} is a registered player, log in as {} to save games under that name", name, name);
+            return Err(status::Custom(Status::Forbidden, json!({ "error": error })));
         }
     }
+    game.winner_name = Replay::winner_name(&game).map_err(|e| {
+        status::Custom(Status::UnprocessableEntity, json!({ "error": e.to_string() }))
+    })?;
+    save_game(store.as_ref(), game).map(json::Json).map_err(|message| {
+        status::Custom(Status::InternalServerError, json!({ "error": message }))
+    })
 }
 
 /** 
- *  Returns list of games in DB as a JSON object array
+ *  Insert a game into the store, for the routes and the room server, and
+ *  update the players' ratings with its result. The game is numbered by the
+ *  store, and the stored record is returned.
  */
-#[get("/list-games")]
-pub fn list_games() -> content::Json<String> {
-    unsafe {
-        match MC {
-            Some(ref client) => {
-                let db = client.database(DB_NAME);
-                let collection = db.collection(GAMES_COLLECTION);
-
-                // Don't specify any filters to get all the games
-                let cursor = collection.find(None, None).unwrap();
-
-                // Create a string vector
-                let mut games_string_vec = Vec::<String>::new();
-
-                for result in cursor {
-                    match result {
-                        Ok(document) => {
-                            // Parse document into Game object
-                            if let Ok(game) = bson::from_bson::<Game>(bson::Bson::Document(document)) {
-                                // Push JSON string onto string vector
-                                games_string_vec.push(game.to_json_string());
-                            }
-                        },
-                        Err(_e) => (),
-                    }
-                }
-
-                // Send as JSON array
-                content::Json(format!("[{}]", games_string_vec.join(", ")))
-            },
-            None => content::Json(String::from("[]")),
-        }
+pub fn save_game(store: &dyn GameStore, game: Game) -> Result<Game, String> {
+    let game = store.insert_game(game)?;
+    // The game is stored either way, so a rating failure is only reported
+    if let Err(message) = store.update_ratings(&game) {
+        error!("Game {} was saved but not rated: {}", game.game_number, message);
     }
+    Ok(game)
 }
 
 /** 
- *  Simple GET request to insert default game data into DB
+ *  Returns one page of the games in DB, moves included, with the number of
+ *  games matching in total. The store does the filtering, sorting and
+ *  paging; the parameters are described on GameQuery. A page size of 0 or
+ *  less gets the default, so no request can ask for more than
+ *  MAX_PAGE_SIZE games. An unknown sort order is refused with 422.
  */
-#[get("/insert-default-test")]
-pub fn insert_default_test() -> String {
-    unsafe {
-        match MC {
-            Some(ref client) => {
-                let db = client.database(DB_NAME);
-                let collection = db.collection(GAMES_COLLECTION);
-
-                let now = Utc::now();
-                let default_game = Game {
-                    // id: ObjectId::new().unwrap(),
-                    game_number: 0,
-                    game_type: "Connect-4".to_string(),
-                    player1_name: "Player 1".to_string(),
-                    player2_name: "Player 2".to_string(),
-                    winner_name: "Player 1".to_string(),
-                    game_date: now.timestamp_millis()
-                };
-
-                let serialized_game = bson::to_bson(&default_game).unwrap();
-
-                if let bson::Bson::Document(document) = serialized_game {
-                    let _result = collection.insert_one(document, None);  // Insert into a MongoDB collection
-                } else {
-                    String::from("Error converting the BSON object into a MongoDB document");
-                }
-
-                String::from("Successfully inserted test game!")
-            },
-            None => String::from("Error"),
-        }
-    }
-}
\ No newline at end of file
+#[get("/list-games?<page>&<page_size>&<game_type>&<player>&<winner>&<from>&<to>&<sort>")]
+#[allow(clippy::too_many_arguments)]
+pub fn list_games(
+    page: Option<i64>,
+    page_size: Option<i64>,
+    game_type: Option<String>,
+    player: Option<String>,
+    winner: Option<String>,
+    from: Option<i64>,
+    to: Option<i64>,
+    sort: Option<String>,
+    store: State<Store>
+) -> Result<json::Json<GamePage>, status::Custom<json::JsonValue>> {
+    let sort = match sort {
+        Some(name) => Some(SortOrder::from_name(&name).ok_or_else(|| {
+            status::Custom(Status::UnprocessableEntity, json!({ "error": format!("Unknown sort order {}", name) }))
+        })?),
+        None => None,
+    };
+    let page_size = page_size.filter(|&size| size > 0);
+    let query = GameQuery { page, page_size, game_type, player, winner, from, to, sort };
+    store.list_games(&query).map(json::Json).map_err(|message| {
+        status::Custom(Status::InternalServerError, json!({ "error": message }))
+    })
+}
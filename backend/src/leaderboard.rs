@@ -0,0 +1,14 @@
+use models::rating::Rating;
+use rocket::State;
+use rocket_contrib::json;
+
+use super::store::Store;
+
+/**
+ *  Ratings of everyone who has played `game_type`, highest first. The
+ *  computer is listed at each difficulty it was played at.
+ */
+#[get("/leaderboard?<game_type>")]
+pub fn leaderboard(game_type: String, store: State<Store>) -> json::Json<Vec<Rating>> {
+    json::Json(store.ratings(&game_type).unwrap_or_default())
+}
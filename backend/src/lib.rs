@@ -0,0 +1,64 @@
+#![feature(decl_macro)]
+#![feature(proc_macro_hygiene)]
+
+#[macro_use] extern crate rocket;
+extern crate models;
+
+use rocket::http::Method;
+use rocket::Rocket;
+use rocket_cors::{AllowedHeaders, AllowedOrigins};
+
+pub mod account;
+pub mod config;
+pub mod game;
+pub mod leaderboard;
+pub mod players;
+pub mod rooms;
+pub mod store;
+
+use config::ServerConfig;
+use store::Store;
+
+#[get("/hello/<name>/<age>")]
+fn hello(name: String, age: u8) -> String {
+    format!("Hello, {} year old named {}!", age, name)
+}
+
+#[get("/hello/<name>")]
+fn hi(name: String) -> String {
+    name
+}
+
+/**
+ *  Mount every route on `rocket`, with `store` as the state they share and
+ *  requests allowed from the origins in `config`. The tests mount the routes
+ *  the same way, on stores that need no server. An origin that is not a
+ *  valid URL is an error, like any other bad setting.
+ */
+pub fn mount(rocket: Rocket, store: Store, config: &ServerConfig) -> Result<Rocket, String> {
+    let allowed_origins = AllowedOrigins::some_exact(&config.allowed_origins); // Set origin to that of app
+    let cors = rocket_cors::CorsOptions {
+        allowed_origins,
+        allowed_methods: vec![Method::Get, Method::Post].into_iter().map(From::from).collect(),
+        allowed_headers: AllowedHeaders::some(&["Authorization", "Accept", "Content-Type"]),
+        allow_credentials: true,
+        ..Default::default()
+    }
+    .to_cors()
+    .map_err(|e| format!("allowed_origins must be valid origins: {}", e))?;
+
+    Ok(rocket
+        .mount("/", routes![
+            hello,
+            hi,
+            game::insert_game,
+            game::list_games,
+            account::register,
+            account::login,
+            account::logout,
+            leaderboard::leaderboard,
+            players::player_stats
+        ])
+        .attach(cors)
+        .manage(store))
+}
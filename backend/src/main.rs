@@ -1,57 +1,17 @@
-#![feature(decl_macro)]
-#![feature(proc_macro_hygiene)]
-use rocket_cors;
+use backend::config::ServerConfig;
+use backend::{rooms, store};
+use std::thread;
 
-#[macro_use] extern crate rocket;
-#[macro_use] extern crate bson;
-extern crate models;
+fn main() -> Result<(), String> {
+    let rocket = rocket::ignite();
+    let config = ServerConfig::from_rocket(rocket.config())?;
+    let store = store::open(&config)?;
 
-use rocket::http::Method;
-use rocket_cors::{AllowedHeaders, AllowedOrigins, Error};
-use mongodb::{Client};
+    // Rocket 0.4 cannot upgrade to WebSockets, so online games are served on their own port
+    let rooms_store = store.clone();
+    let rooms_address = config.rooms_address.clone();
+    thread::spawn(move || rooms::listen(&rooms_address, rooms_store));
 
-mod game;
-
-static mut MC: Option<Client> = None;
-static DB_NAME: &'static str = "TOOTandOTTO-Connect4-DB";
-static GAMES_COLLECTION: &'static str = "games";
-
-#[get("/hello/<name>/<age>")]
-fn hello(name: String, age: u8) -> String {
-    format!("Hello, {} year old named {}!", age, name)
-}
-
-#[get("/hello/<name>")]
-fn hi(name: String) -> String {
-    name
-}
-
-fn main() -> Result<(), mongodb::error::Error> {
-    unsafe {
-        let client = Client::with_uri_str("mongodb://localhost:27017/")?;
-        MC = Some(client);
-    }
-
-    // TODO: Error handling for to_cors()
-    let allowed_origins = AllowedOrigins::some_exact(&["http://localhost:8080"]); // Set origin to that of app
-    let cors = rocket_cors::CorsOptions {
-        allowed_origins,
-        allowed_methods: vec![Method::Get, Method::Post].into_iter().map(From::from).collect(),
-        allowed_headers: AllowedHeaders::some(&["Authorization", "Accept", "Content-Type"]),
-        allow_credentials: true,
-        ..Default::default()
-    }
-    .to_cors().unwrap();
-
-    rocket::ignite()
-        .mount("/", routes![
-            hello,
-            hi,
-            game::insert_game,
-            game::list_games,
-            game::insert_default_test
-        ])
-        .attach(cors)
-        .launch();
+    backend::mount(rocket, store, &config)?.launch();
     Ok(())
-}
\ No newline at end of file
+}
@@ -0,0 +1,17 @@
+use models::listing::GameQuery;
+use models::stats::PlayerStats;
+use rocket::State;
+use rocket_contrib::json;
+
+use super::store::Store;
+
+/** 
+ *  Statistics of one player, gathered from every game they played in
+ */
+#[get("/players/<name>/stats")]
+pub fn player_stats(name: String, store: State<Store>) -> json::Json<PlayerStats> {
+    // A page size of 0 asks for every game
+    let query = GameQuery { player: Some(name.clone()), page_size: Some(0), ..GameQuery::default() };
+    let games = store.list_games(&query).map(|listing| listing.games).unwrap_or_default();
+    json::Json(PlayerStats::from_games(&name, &games))
+}
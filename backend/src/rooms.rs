@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::thread;
+use chrono::Utc;
+use log::error;
+use rand::Rng;
+use ws::{CloseCode, Handler, Message, Sender};
+use models::game::{Game, GameMove};
+use models::online::{ClientMessage, ServerMessage};
+use models::player::Player;
+use models::replay::Position;
+
+use super::{account, game};
+use super::store::{GameStore, Store};
+
+// Room codes leave out I and O, which are easily mistaken for 1 and 0
+const CODE_LETTERS: &[u8] = b"ABCDEFGHJKLMNPQRSTUVWXYZ";
+const CODE_LENGTH: usize = 4;
+
+/**
+ *  A game between two browsers. The server keeps its own copy of the board,
+ *  so only moves the rules allow are passed on.
+ */
+struct Room {
+    game: Game,
+    position: Position,
+    // Connections of player one and player two, who has not joined yet if None
+    players: [Option<Sender>; 2]
+}
+
+impl Room {
+    fn seat(&self, player: Player) -> &Option<Sender> {
+        match player {
+            Player::One => &self.players[0],
+            Player::Two => &self.players[1],
+        }
+    }
+
+    // Whether `out` is the connection sitting as `player`
+    fn holds(&self, player: Player, out: &Sender) -> bool {
+        self.seat(player).as_ref().map_or(false, |seated| seated.connection_id() == out.connection_id())
+    }
+
+    fn broadcast(&self, message: &ServerMessage) {
+        for out in self.players.iter().flatten() {
+            send(out, message);
+        }
+    }
+}
+
+type Rooms = Arc<Mutex<HashMap<String, Room>>>;
+
+fn lock(rooms: &Rooms) -> MutexGuard<HashMap<String, Room>> {
+    // A panic in one handler leaves no room half changed, so the others carry on
+    rooms.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/**
+ *  Run the game room server on `address`, blocking the calling thread.
+ *  Finished games are saved to `store`.
+ */
+pub fn listen(address: &str, store: Store) {
+    let rooms: Rooms = Arc::new(Mutex::new(HashMap::new()));
+    if let Err(e) = ws::listen(address, |out| Connection { out, rooms: rooms.clone(), store: store.clone(), seat: None }) {
+        error!("Game room server stopped: {}", e);
+    }
+}
+
+struct Connection {
+    out: Sender,
+    rooms: Rooms,
+    store: Store,
+    // Code of the room this browser was seated in and the side it plays.
+    // The room may have closed since, when the other player left.
+    seat: Option<(String, Player)>
+}
+
+impl Handler for Connection {
+    fn on_message(&mut self, message: Message) -> ws::Result<()> {
+        let request = message.as_text().ok().and_then(|text| serde_json::from_str(text).ok());
+        let result = match request {
+            Some(ClientMessage::Create { name, game_type, columns, rows, connect, token }) => {
+                let game = Game { game_type, player1_name: name, columns, rows, connect, ..Game::default() };
+                check_name(self.store.as_ref(), &game.player1_name, token).and_then(|()| self.create(game))
+            },
+            Some(ClientMessage::Join { code, name, token }) => {
+                check_name(self.store.as_ref(), &name, token).and_then(|()| self.join(&code.trim().to_uppercase(), name))
+            },
+            Some(ClientMessage::Move(mv)) => self.play(mv),
+            None => Err(String::from("Cannot read message")),
+        };
+        if let Err(reason) = result {
+            send(&self.out, &ServerMessage::Rejected { reason });
+        }
+        Ok(())
+    }
+
+    fn on_close(&mut self, _code: CloseCode, _reason: &str) {
+        // The room closes with either player, since the game cannot go on
+        let mut rooms = lock(&self.rooms);
+        if let Some((code, _)) = self.current_seat(&rooms) {
+            if let Some(room) = rooms.remove(&code) {
+                room.broadcast(&ServerMessage::OpponentLeft);
+            }
+        }
+        self.seat = None;
+    }
+}
+
+impl Connection {
+    // The seat this connection still has, if its room is open. The code of a
+    // closed room can be given to a new one, which this connection is not in.
+    fn current_seat(&self, rooms: &HashMap<String, Room>) -> Option<(String, Player)> {
+        let (code, player) = self.seat.clone()?;
+        rooms.get(&code).filter(|room| room.holds(player, &self.out)).map(|_| (code, player))
+    }
+
+    fn create(&mut self, game: Game) -> Result<(), String> {
+        let mut rooms = lock(&self.rooms);
+        if self.current_seat(&rooms).is_some() {
+            return Err(String::from("You are already in a room"));
+        }
+        let position = Position::new(&game).map_err(|e| e.to_string())?;
+        let mut code = new_code();
+        while rooms.contains_key(&code) {
+            code = new_code();
+        }
+        rooms.insert(code.clone(), Room { game, position, players: [Some(self.out.clone()), None] });
+        self.seat = Some((code.clone(), Player::One));
+        send(&self.out, &ServerMessage::Created { code });
+        Ok(())
+    }
+
+    fn join(&mut self, code: &str, name: String) -> Result<(), String> {
+        let mut rooms = lock(&self.rooms);
+        if self.current_seat(&rooms).is_some() {
+            return Err(String::from("You are already in a room"));
+        }
+        let room = rooms.get_mut(code).ok_or_else(|| format!("There is no room {}", code))?;
+        if room.players[1].is_some() {
+            return Err(format!("Room {} is full", code));
+        }
+        if name == room.game.player1_name {
+            return Err(String::from("Player names must be different!"));
+        }
+        room.game.player2_name = name;
+        room.players[1] = Some(self.out.clone());
+        self.seat = Some((code.to_string(), Player::Two));
+        for &player in [Player::One, Player::Two].iter() {
+            if let Some(out) = room.seat(player) {
+                send(out, &ServerMessage::Started { game: room.game.clone(), you: player });
+            }
+        }
+        Ok(())
+    }
+
+    fn play(&mut self, mv: GameMove) -> Result<(), String> {
+        if self.seat.is_none() {
+            return Err(String::from("You are not in a room"));
+        }
+        let mut rooms = lock(&self.rooms);
+        let (code, player) = self.current_seat(&rooms).ok_or_else(|| String::from("The room has closed"))?;
+        let room = rooms.get_mut(&code).ok_or_else(|| String::from("The room has closed"))?;
+        if room.players[1].is_none() {
+            return Err(String::from("Waiting for an opponent to join"));
+        }
+        if room.position.is_over() {
+            return Err(String::from("The game is already over"));
+        }
+        if room.position.current_player() != player {
+            return Err(String::from("It is not your turn"));
+        }
+        room.position.play(mv)?;
+        room.game.moves.push(mv);
+        room.broadcast(&ServerMessage::Moved(mv));
+
+        if room.position.is_over() {
+            room.game.winner_name = match room.position.winner() {
+                Some(Player::One) => room.game.player1_name.clone(),
+                Some(Player::Two) => room.game.player2_name.clone(),
+                None => String::from("Draw"),
+            };
+            room.game.game_date = Utc::now().timestamp_millis();
+            // Saving can be slow, and would hold up every room while the event loop waits
+            let finished = room.game.clone();
+            let store = self.store.clone();
+            thread::spawn(move || {
+                if let Err(message) = game::save_game(store.as_ref(), finished) {
+                    error!("Online game {} was not saved: {}", code, message);
+                }
+            });
+        }
+        Ok(())
+    }
+}
+
+// Names of accounts may only be used by the account's owner
+fn check_name(store: &dyn GameStore, name: &str, token: Option<String>) -> Result<(), String> {
+    let owner = token.and_then(|token| account::session_user(store, &token));
+    if owner.as_deref() != Some(name) && account::is_registered(store, name)? {
+        return Err(format!("{} is a registered player, log in as {} to play under that name", name, name));
+    }
+    Ok(())
+}
+
+fn new_code() -> String {
+    let mut rng = rand::thread_rng();
+    (0..CODE_LENGTH)
+        .map(|_| CODE_LETTERS[rng.gen_range(0, CODE_LETTERS.len())] as char)
+        .collect()
+}
+
+fn send(out: &Sender, message: &ServerMessage) {
+    // A message can only fail to send once the browser has gone, which on_close handles
+    if let Ok(text) = serde_json::to_string(message) {
+        let _ = out.send(text);
+    }
+}
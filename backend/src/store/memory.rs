@@ -0,0 +1,114 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use models::game::Game;
+use models::listing::{GamePage, GameQuery};
+use models::rating::{self, Rating};
+
+use super::{GameStore, User};
+
+/**
+ *  Store that keeps everything in memory, for running the backend and its
+ *  tests without a database. Nothing survives a restart.
+ */
+#[derive(Default)]
+pub struct MemoryStore {
+    data: Mutex<Data>
+}
+
+#[derive(Default)]
+struct Data {
+    games: Vec<Game>,
+    // Keyed by player and game type
+    ratings: HashMap<(String, String), Rating>,
+    users: HashMap<String, User>,
+    // Usernames keyed by session token
+    sessions: HashMap<String, String>
+}
+
+impl MemoryStore {
+    pub fn new() -> MemoryStore {
+        MemoryStore::default()
+    }
+
+    fn data(&self) -> MutexGuard<Data> {
+        // Nothing is left half changed by a panic, so a poisoned lock is still usable
+        self.data.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl GameStore for MemoryStore {
+    fn insert_game(&self, mut game: Game) -> Result<Game, String> {
+        let mut data = self.data();
+        game.game_number = data.games.iter().map(|game| game.game_number).max().unwrap_or(0) + 1;
+        data.games.push(game.clone());
+        Ok(game)
+    }
+
+    fn list_games(&self, query: &GameQuery) -> Result<GamePage, String> {
+        let data = self.data();
+        let mut games: Vec<Game> = data.games.iter().filter(|game| query.matches(game)).cloned().collect();
+        games.sort_by(|a, b| query.compare(a, b));
+        let total = games.len() as i64;
+        let mut games: Vec<Game> = games.into_iter().skip(query.skip() as usize).collect();
+        if query.page_size() > 0 {
+            games.truncate(query.page_size() as usize);
+        }
+        Ok(GamePage { games, total, page: query.page(), page_size: query.page_size() })
+    }
+
+    fn update_ratings(&self, game: &Game) -> Result<(), String> {
+        let (name1, name2) = match rating::rated_players(game) {
+            Some(names) => names,
+            None => return Ok(()),
+        };
+        // The lock is held from reading the ratings to storing them
+        let mut data = self.data();
+        let find = |player: &str| {
+            data.ratings.get(&(player.to_string(), game.game_type.clone())).cloned()
+                .unwrap_or_else(|| Rating::new(player, &game.game_type))
+        };
+        let mut player1 = find(&name1);
+        let mut player2 = find(&name2);
+        rating::rate_game(game, &mut player1, &mut player2);
+        for rating in [player1, player2].iter().cloned() {
+            data.ratings.insert((rating.player.clone(), rating.game_type.clone()), rating);
+        }
+        Ok(())
+    }
+
+    fn ratings(&self, game_type: &str) -> Result<Vec<Rating>, String> {
+        let mut ratings: Vec<Rating> = self.data().ratings.values()
+            .filter(|rating| rating.game_type == game_type)
+            .cloned()
+            .collect();
+        ratings.sort_by(|a, b| b.rating.partial_cmp(&a.rating).unwrap_or(std::cmp::Ordering::Equal));
+        Ok(ratings)
+    }
+
+    fn find_user(&self, username: &str) -> Result<Option<User>, String> {
+        Ok(self.data().users.get(username).cloned())
+    }
+
+    fn insert_user(&self, user: User) -> Result<bool, String> {
+        let mut data = self.data();
+        if data.users.contains_key(&user.username) {
+            return Ok(false);
+        }
+        data.users.insert(user.username.clone(), user);
+        Ok(true)
+    }
+
+    fn insert_session(&self, token: &str, username: &str) -> Result<(), String> {
+        self.data().sessions.insert(token.to_string(), username.to_string());
+        Ok(())
+    }
+
+    fn session_user(&self, token: &str) -> Result<Option<String>, String> {
+        Ok(self.data().sessions.get(token).cloned())
+    }
+
+    fn delete_session(&self, token: &str) -> Result<(), String> {
+        self.data().sessions.remove(token);
+        Ok(())
+    }
+}
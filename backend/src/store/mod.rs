@@ -0,0 +1,87 @@
+use std::sync::Arc;
+use models::game::Game;
+use models::listing::{GamePage, GameQuery};
+use models::rating::Rating;
+
+use super::config::{ServerConfig, StoreKind};
+
+mod memory;
+mod mongo;
+mod sqlite;
+
+pub use memory::MemoryStore;
+pub use mongo::MongoStore;
+pub use sqlite::SqliteStore;
+
+/**
+ *  An account as stored, with its salted password hash
+ */
+#[derive(Debug, Clone, PartialEq)]
+pub struct User {
+    pub username: String,
+    pub password_hash: String
+}
+
+/**
+ *  Everything the backend keeps: games, ratings, accounts and sessions.
+ *  Errors are messages that can be passed on to the client.
+ */
+pub trait GameStore: Send + Sync {
+    /**
+     *  Store a game under the next game number, returning it as stored. Games
+     *  saved at the same time must never share a number.
+     */
+    fn insert_game(&self, game: Game) -> Result<Game, String>;
+
+    /**
+     *  The page of games `query` asks for, with the number matching in total
+     */
+    fn list_games(&self, query: &GameQuery) -> Result<GamePage, String>;
+
+    /**
+     *  Update both players' ratings with the result of a saved game. Games
+     *  finishing together for the same player must all count, and games that
+     *  cannot be rated are skipped. The memory and SQLite stores write both
+     *  ratings as one step; see MongoStore for how it comes close.
+     */
+    fn update_ratings(&self, game: &Game) -> Result<(), String>;
+
+    /**
+     *  Every rating at `game_type`, highest first
+     */
+    fn ratings(&self, game_type: &str) -> Result<Vec<Rating>, String>;
+
+    fn find_user(&self, username: &str) -> Result<Option<User>, String>;
+
+    /**
+     *  Store a new account, returning false without storing it if the
+     *  username is already taken. Two accounts must never share a username,
+     *  even when registered at the same time.
+     */
+    fn insert_user(&self, user: User) -> Result<bool, String>;
+
+    fn insert_session(&self, token: &str, username: &str) -> Result<(), String>;
+
+    /**
+     *  Username logged in with `token`, if the session exists
+     */
+    fn session_user(&self, token: &str) -> Result<Option<String>, String>;
+
+    fn delete_session(&self, token: &str) -> Result<(), String>;
+}
+
+/**
+ *  The store the routes and the room server share, kept as Rocket state
+ */
+pub type Store = Arc<dyn GameStore>;
+
+/**
+ *  Open the store `config` chooses
+ */
+pub fn open(config: &ServerConfig) -> Result<Store, String> {
+    Ok(match config.store {
+        StoreKind::Mongo => Arc::new(MongoStore::connect(&config.mongo_uri, &config.database_name).map_err(|e| e.to_string())?),
+        StoreKind::Sqlite => Arc::new(SqliteStore::open(&config.sqlite_path)?),
+        StoreKind::Memory => Arc::new(MemoryStore::new()),
+    })
+}
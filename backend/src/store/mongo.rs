@@ -0,0 +1,302 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use bson::{doc, Bson, Document};
+use chrono::Utc;
+use mongodb::{Client, Collection, Database};
+use mongodb::error::{ErrorKind, WriteFailure};
+use mongodb::options::{FindOneAndUpdateOptions, FindOneOptions, FindOptions, ReturnDocument};
+use models::game::Game;
+use models::listing::{GamePage, GameQuery};
+use models::rating::{self, Rating};
+
+use super::{GameStore, User};
+
+static GAMES_COLLECTION: &str = "games";
+static USERS_COLLECTION: &str = "users";
+static SESSIONS_COLLECTION: &str = "sessions";
+static RATINGS_COLLECTION: &str = "ratings";
+static COUNTERS_COLLECTION: &str = "counters";
+
+// Counter document the game numbers are taken from
+const GAME_NUMBER_COUNTER: &str = "game_number";
+// Times a rating update is tried before giving up
+const RATING_ATTEMPTS: usize = 5;
+// Server error code for a write that breaks a unique index
+const DUPLICATE_KEY: i32 = 11000;
+
+/**
+ *  Store backed by a MongoDB database
+ */
+pub struct MongoStore {
+    db: Database,
+    ratings_indexed: AtomicBool,
+    users_indexed: AtomicBool
+}
+
+impl MongoStore {
+    /**
+     *  Connect to the database named `database_name` on the MongoDB server at
+     *  `uri`. The client connects lazily, so this succeeds even while the
+     *  server is down.
+     */
+    pub fn connect(uri: &str, database_name: &str) -> Result<MongoStore, mongodb::error::Error> {
+        let client = Client::with_uri_str(uri)?;
+        Ok(MongoStore {
+            db: client.database(database_name),
+            ratings_indexed: AtomicBool::new(false),
+            users_indexed: AtomicBool::new(false)
+        })
+    }
+
+    fn collection(&self, name: &str) -> Collection {
+        self.db.collection(name)
+    }
+
+    /**
+     *  Number for the next game saved. A counter document is incremented
+     *  atomically, so games saved at the same moment never share a number.
+     *  The counter starts from the highest number already stored.
+     */
+    fn next_game_number(&self) -> Result<i32, String> {
+        let counters = self.collection(COUNTERS_COLLECTION);
+        let filter = doc! { "_id": GAME_NUMBER_COUNTER };
+
+        if counters.find_one(Some(filter.clone()), None).map_err(|e| e.to_string())?.is_none() {
+            let highest = highest_game_number(&self.collection(GAMES_COLLECTION));
+            // Two servers may both try to create the counter, the second insert fails harmlessly
+            let _ = counters.insert_one(doc! { "_id": GAME_NUMBER_COUNTER, "value": highest }, None);
+        }
+
+        let options = FindOneAndUpdateOptions {
+            upsert: Some(true),
+            return_document: Some(ReturnDocument::After),
+            ..Default::default()
+        };
+        let counter = counters
+            .find_one_and_update(filter, doc! { "$inc": { "value": 1 } }, options)
+            .map_err(|e| e.to_string())?
+            .ok_or_else(|| String::from("The game counter is missing"))?;
+        counter.get_i32("value").map_err(|e| e.to_string())
+    }
+
+    fn find_rating(&self, player: &str, game_type: &str) -> Result<Option<Rating>, String> {
+        let found = self.collection(RATINGS_COLLECTION)
+            .find_one(Some(doc! { "player": player, "game_type": game_type }), None)
+            .map_err(|e| e.to_string())?;
+        match found {
+            Some(document) => bson::from_bson(Bson::Document(document)).map(Some).map_err(|e| e.to_string()),
+            None => Ok(None),
+        }
+    }
+
+    // Apply `rate` to the stored rating of `player`, retrying while other games change it first.
+    // Returns the rating from before, if there was one, and the rating written.
+    fn update_rating<F: Fn(&mut Rating)>(&self, player: &str, game_type: &str, rate: F) -> Result<(Option<Rating>, Rating), String> {
+        let collection = self.collection(RATINGS_COLLECTION);
+        let mut last_error = String::from("The rating kept changing");
+        for _ in 0..RATING_ATTEMPTS {
+            let stored = self.find_rating(player, game_type)?;
+            let mut rating = stored.clone().unwrap_or_else(|| Rating::new(player, game_type));
+            rate(&mut rating);
+            let document = to_document(&rating)?;
+            let written = match &stored {
+                // The counts go up with every game, so they show whether the rating changed
+                Some(old) => collection.replace_one(unchanged(old), document, None).map(|result| result.matched_count == 1),
+                // A second first game fails on the unique index, and is retried as an update
+                None => collection.insert_one(document, None).map(|_| true),
+            };
+            match written {
+                Ok(true) => return Ok((stored, rating)),
+                Ok(false) => (),
+                Err(e) => last_error = e.to_string(),
+            }
+        }
+        Err(last_error)
+    }
+
+    // Put back the rating `written` replaced, unless another game has changed it since
+    fn undo_rating(&self, before: Option<Rating>, written: &Rating) -> Result<(), String> {
+        let collection = self.collection(RATINGS_COLLECTION);
+        match before {
+            Some(before) => collection.replace_one(unchanged(written), to_document(&before)?, None).map(|_| ()),
+            None => collection.delete_one(unchanged(written), None).map(|_| ()),
+        }.map_err(|e| e.to_string())
+    }
+
+    // Give each player one rating per game type, which update_rating relies on
+    fn index_ratings(&self) {
+        self.unique_index(&self.ratings_indexed, RATINGS_COLLECTION, "player_game_type", doc! { "player": 1, "game_type": 1 });
+    }
+
+    // Give each account its own username, so two registering at once cannot both have it
+    fn index_users(&self) {
+        self.unique_index(&self.users_indexed, USERS_COLLECTION, "username", doc! { "username": 1 });
+    }
+
+    // Create a unique index on `key` once, marking `created` when it exists
+    fn unique_index(&self, created: &AtomicBool, collection: &str, name: &str, key: Document) {
+        if created.load(Ordering::Relaxed) {
+            return;
+        }
+        let command = doc! {
+            "createIndexes": collection,
+            "indexes": [{ "key": key, "name": name, "unique": true }]
+        };
+        // Tried again with the next write if the server is down
+        if self.db.run_command(command, None).is_ok() {
+            created.store(true, Ordering::Relaxed);
+        }
+    }
+}
+
+impl GameStore for MongoStore {
+    fn insert_game(&self, mut game: Game) -> Result<Game, String> {
+        game.game_number = self.next_game_number()?;
+        let document = to_document(&game)?;
+        self.collection(GAMES_COLLECTION).insert_one(document, None).map_err(|e| e.to_string())?;  // Insert into a MongoDB collection
+        Ok(game)
+    }
+
+    fn list_games(&self, query: &GameQuery) -> Result<GamePage, String> {
+        let collection = self.collection(GAMES_COLLECTION);
+        let filter = query.filter();
+        let total = collection.count_documents(filter.clone(), None).map_err(|e| e.to_string())?;
+
+        let options = FindOptions {
+            sort: Some(query.sort_document()),
+            skip: Some(query.skip()),
+            // A page size of 0 asks for every game
+            limit: Some(query.page_size()).filter(|&size| size > 0),
+            ..Default::default()
+        };
+        let cursor = collection.find(filter, options).map_err(|e| e.to_string())?;
+        Ok(GamePage {
+            // Older documents fill in defaults, and ones that cannot be read are skipped
+            games: from_documents(cursor),
+            total,
+            page: query.page(),
+            page_size: query.page_size()
+        })
+    }
+
+    /**
+     *  This version of the driver has no transactions, so each player's
+     *  rating is updated on its own. A rating is only replaced if it has not
+     *  changed since it was read, and is read again and retried otherwise,
+     *  so games finishing together for the same player all count. Both
+     *  players are rated against their opponent's rating from before the game.
+     *
+     *  If player two's rating cannot be written, player one's is put back as
+     *  it was. Only if that fails too, or another game has rated player one
+     *  in between, is the game left counted for player one alone.
+     */
+    fn update_ratings(&self, game: &Game) -> Result<(), String> {
+        let (name1, name2) = match rating::rated_players(game) {
+            Some(names) => names,
+            None => return Ok(()),
+        };
+        self.index_ratings();
+        let before1 = self.find_rating(&name1, &game.game_type)?.unwrap_or_else(|| Rating::new(&name1, &game.game_type));
+        let before2 = self.find_rating(&name2, &game.game_type)?.unwrap_or_else(|| Rating::new(&name2, &game.game_type));
+        let (stored1, written1) = self.update_rating(&name1, &game.game_type, |player1| rating::rate_game(game, player1, &mut before2.clone()))?;
+        match self.update_rating(&name2, &game.game_type, |player2| rating::rate_game(game, &mut before1.clone(), player2)) {
+            Ok(_) => Ok(()),
+            Err(message) => match self.undo_rating(stored1, &written1) {
+                Ok(()) => Err(message),
+                Err(undo) => Err(format!("{}, and {}'s rating could not be put back: {}", message, name1, undo)),
+            },
+        }
+    }
+
+    fn ratings(&self, game_type: &str) -> Result<Vec<Rating>, String> {
+        let options = FindOptions { sort: Some(doc! { "rating": -1 }), ..Default::default() };
+        let cursor = self.collection(RATINGS_COLLECTION)
+            .find(Some(doc! { "game_type": game_type }), options)
+            .map_err(|e| e.to_string())?;
+        Ok(from_documents(cursor))
+    }
+
+    fn find_user(&self, username: &str) -> Result<Option<User>, String> {
+        let found = self.collection(USERS_COLLECTION)
+            .find_one(Some(doc! { "username": username }), None)
+            .map_err(|e| e.to_string())?;
+        Ok(found.and_then(|user| {
+            let password_hash = user.get_str("password_hash").ok()?;
+            Some(User { username: username.to_string(), password_hash: password_hash.to_string() })
+        }))
+    }
+
+    fn insert_user(&self, user: User) -> Result<bool, String> {
+        self.index_users();
+        let inserted = self.collection(USERS_COLLECTION).insert_one(doc! {
+            "username": user.username,
+            "password_hash": user.password_hash,
+            "created": Utc::now().timestamp_millis()
+        }, None);
+        match inserted {
+            Ok(_) => Ok(true),
+            Err(e) if is_duplicate_key(&e) => Ok(false),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn insert_session(&self, token: &str, username: &str) -> Result<(), String> {
+        self.collection(SESSIONS_COLLECTION).insert_one(doc! {
+            "token": token,
+            "username": username,
+            "created": Utc::now().timestamp_millis()
+        }, None).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn session_user(&self, token: &str) -> Result<Option<String>, String> {
+        let session = self.collection(SESSIONS_COLLECTION)
+            .find_one(Some(doc! { "token": token }), None)
+            .map_err(|e| e.to_string())?;
+        Ok(session.and_then(|session| session.get_str("username").ok().map(String::from)))
+    }
+
+    fn delete_session(&self, token: &str) -> Result<(), String> {
+        self.collection(SESSIONS_COLLECTION)
+            .delete_one(doc! { "token": token }, None)
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+// Filter matching `rating` only while no other game has been counted in it
+fn unchanged(rating: &Rating) -> Document {
+    doc! {
+        "player": rating.player.clone(), "game_type": rating.game_type.clone(),
+        "wins": rating.wins, "losses": rating.losses, "draws": rating.draws
+    }
+}
+
+// Highest game number stored, or 0 before any numbered game
+fn highest_game_number(games: &Collection) -> i32 {
+    let options = FindOneOptions { sort: Some(doc! { "game_number": -1 }), ..Default::default() };
+    games.find_one(None, options).ok().flatten()
+        .and_then(|game| game.get_i32("game_number").ok())
+        .unwrap_or(0)
+}
+
+// Whether a write failed because it broke a unique index
+fn is_duplicate_key(error: &mongodb::error::Error) -> bool {
+    match error.kind.as_ref() {
+        ErrorKind::WriteError(WriteFailure::WriteError(write_error)) => write_error.code == DUPLICATE_KEY,
+        _ => false,
+    }
+}
+
+fn to_document<T: serde::Serialize>(value: &T) -> Result<Document, String> {
+    match bson::to_bson(value).map_err(|e| e.to_string())? {
+        Bson::Document(document) => Ok(document),
+        _ => Err(String::from("Error converting the BSON object into a MongoDB document")),
+    }
+}
+
+fn from_documents<T: serde::de::DeserializeOwned>(cursor: mongodb::Cursor) -> Vec<T> {
+    cursor
+        .filter_map(Result::ok)
+        .filter_map(|document| bson::from_bson(Bson::Document(document)).ok())
+        .collect()
+}
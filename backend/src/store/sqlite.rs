@@ -0,0 +1,317 @@
+use std::path::Path;
+use std::sync::{Mutex, MutexGuard};
+use rusqlite::{params, Connection, OptionalExtension, Row, NO_PARAMS};
+use rusqlite::types::Value;
+use chrono::Utc;
+use models::game::Game;
+use models::listing::{GamePage, GameQuery, SortOrder};
+use models::rating::{self, Rating};
+
+use super::{GameStore, User};
+
+/**
+ *  Schema changes, applied in order. The database's `user_version` records
+ *  how many have run, so each runs once. Only ever add to the end.
+ */
+const MIGRATIONS: [&str; 3] = [
+    // 1: games, with the moves kept as JSON
+    "CREATE TABLE games (
+        game_number INTEGER PRIMARY KEY,
+        game_type TEXT NOT NULL,
+        player1_name TEXT NOT NULL,
+        player2_name TEXT NOT NULL,
+        winner_name TEXT NOT NULL,
+        game_date INTEGER NOT NULL,
+        moves TEXT NOT NULL DEFAULT '[]',
+        columns INTEGER NOT NULL DEFAULT 0,
+        rows INTEGER NOT NULL DEFAULT 0,
+        connect INTEGER NOT NULL DEFAULT 0,
+        difficulty TEXT NOT NULL DEFAULT '',
+        player1_color TEXT NOT NULL DEFAULT '',
+        player2_color TEXT NOT NULL DEFAULT ''
+    );
+    CREATE INDEX games_by_date ON games (game_date);",
+    // 2: ratings, with the history kept as JSON
+    "CREATE TABLE ratings (
+        player TEXT NOT NULL,
+        game_type TEXT NOT NULL,
+        rating REAL NOT NULL,
+        wins INTEGER NOT NULL,
+        losses INTEGER NOT NULL,
+        draws INTEGER NOT NULL,
+        history TEXT NOT NULL,
+        anchored INTEGER NOT NULL,
+        PRIMARY KEY (player, game_type)
+    );",
+    // 3: accounts and their sessions
+    "CREATE TABLE users (
+        username TEXT PRIMARY KEY,
+        password_hash TEXT NOT NULL,
+        created INTEGER NOT NULL
+    );
+    CREATE TABLE sessions (
+        token TEXT PRIMARY KEY,
+        username TEXT NOT NULL REFERENCES users (username),
+        created INTEGER NOT NULL
+    );",
+];
+
+const GAME_COLUMNS: &str = "game_number, game_type, player1_name, player2_name, winner_name, game_date, \
+    moves, columns, rows, connect, difficulty, player1_color, player2_color";
+
+/**
+ *  Store backed by a SQLite file, for a single machine without MongoDB
+ */
+pub struct SqliteStore {
+    connection: Mutex<Connection>
+}
+
+impl SqliteStore {
+    /**
+     *  Open the database at `path`, creating it if needed, and bring its
+     *  schema up to date
+     */
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<SqliteStore, String> {
+        let connection = Connection::open(path).map_err(|e| e.to_string())?;
+        SqliteStore::with_connection(connection)
+    }
+
+    /**
+     *  A database that only lasts as long as the store
+     */
+    pub fn open_in_memory() -> Result<SqliteStore, String> {
+        let connection = Connection::open_in_memory().map_err(|e| e.to_string())?;
+        SqliteStore::with_connection(connection)
+    }
+
+    fn with_connection(mut connection: Connection) -> Result<SqliteStore, String> {
+        migrate(&mut connection).map_err(|e| e.to_string())?;
+        Ok(SqliteStore { connection: Mutex::new(connection) })
+    }
+
+    fn connection(&self) -> MutexGuard<Connection> {
+        // A panic mid-statement leaves SQLite consistent, so a poisoned lock is still usable
+        self.connection.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+// Number of migrations the database has had
+fn schema_version(connection: &Connection) -> rusqlite::Result<usize> {
+    connection.query_row("PRAGMA user_version", NO_PARAMS, |row| row.get::<_, i64>(0)).map(|version| version as usize)
+}
+
+// Run the migrations the database has not had yet, each in its own transaction
+fn migrate(connection: &mut Connection) -> rusqlite::Result<()> {
+    let version = schema_version(connection)?;
+    for (applied, migration) in MIGRATIONS.iter().enumerate().skip(version) {
+        let transaction = connection.transaction()?;
+        transaction.execute_batch(migration)?;
+        transaction.execute_batch(&format!("PRAGMA user_version = {}", applied + 1))?;
+        transaction.commit()?;
+    }
+    Ok(())
+}
+
+impl GameStore for SqliteStore {
+    fn insert_game(&self, mut game: Game) -> Result<Game, String> {
+        let moves = serde_json::to_string(&game.moves).map_err(|e| e.to_string())?;
+        let connection = self.connection();
+        // A null INTEGER PRIMARY KEY takes the next number, and the lock keeps saves apart
+        connection.execute(
+            &format!("INSERT INTO games ({}) VALUES (NULL, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", GAME_COLUMNS),
+            params![
+                game.game_type, game.player1_name, game.player2_name, game.winner_name, game.game_date,
+                moves, game.columns, game.rows, game.connect, game.difficulty, game.player1_color, game.player2_color
+            ],
+        ).map_err(|e| e.to_string())?;
+        game.game_number = connection.last_insert_rowid() as i32;
+        Ok(game)
+    }
+
+    fn list_games(&self, query: &GameQuery) -> Result<GamePage, String> {
+        let (filter, values) = where_clause(query);
+        let connection = self.connection();
+        let total: i64 = connection
+            .query_row(&format!("SELECT COUNT(*) FROM games{}", filter), values.clone(), |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+
+        // SQLite needs a limit before an offset, and -1 means no limit
+        let limit = if query.page_size() > 0 { query.page_size() } else { -1 };
+        let sql = format!(
+            "SELECT {} FROM games{} ORDER BY {} LIMIT {} OFFSET {}",
+            GAME_COLUMNS, filter, order_by(query), limit, query.skip()
+        );
+        let mut statement = connection.prepare(&sql).map_err(|e| e.to_string())?;
+        let games = statement.query_map(values, read_game).map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .collect();
+        Ok(GamePage { games, total, page: query.page(), page_size: query.page_size() })
+    }
+
+    fn update_ratings(&self, game: &Game) -> Result<(), String> {
+        let (name1, name2) = match rating::rated_players(game) {
+            Some(names) => names,
+            None => return Ok(()),
+        };
+        let mut connection = self.connection();
+        // Both ratings are read and written in one transaction, so a failure stores neither
+        let transaction = connection.transaction().map_err(|e| e.to_string())?;
+        let mut player1 = find_rating(&transaction, &name1, &game.game_type).map_err(|e| e.to_string())?;
+        let mut player2 = find_rating(&transaction, &name2, &game.game_type).map_err(|e| e.to_string())?;
+        rating::rate_game(game, &mut player1, &mut player2);
+        store_rating(&transaction, &player1)?;
+        store_rating(&transaction, &player2)?;
+        transaction.commit().map_err(|e| e.to_string())
+    }
+
+    fn ratings(&self, game_type: &str) -> Result<Vec<Rating>, String> {
+        let connection = self.connection();
+        let mut statement = connection.prepare(
+            "SELECT player, game_type, rating, wins, losses, draws, history, anchored FROM ratings
+                WHERE game_type = ? ORDER BY rating DESC"
+        ).map_err(|e| e.to_string())?;
+        let ratings = statement.query_map(params![game_type], read_rating).map_err(|e| e.to_string())?
+            .filter_map(Result::ok)
+            .collect();
+        Ok(ratings)
+    }
+
+    fn find_user(&self, username: &str) -> Result<Option<User>, String> {
+        self.connection().query_row(
+            "SELECT username, password_hash FROM users WHERE username = ?",
+            params![username],
+            |row| Ok(User { username: row.get(0)?, password_hash: row.get(1)? }),
+        ).optional().map_err(|e| e.to_string())
+    }
+
+    fn insert_user(&self, user: User) -> Result<bool, String> {
+        // The username is the primary key, so a taken one inserts nothing
+        let inserted = self.connection().execute(
+            "INSERT OR IGNORE INTO users (username, password_hash, created) VALUES (?, ?, ?)",
+            params![user.username, user.password_hash, Utc::now().timestamp_millis()],
+        ).map_err(|e| e.to_string())?;
+        Ok(inserted == 1)
+    }
+
+    fn insert_session(&self, token: &str, username: &str) -> Result<(), String> {
+        self.connection().execute(
+            "INSERT INTO sessions (token, username, created) VALUES (?, ?, ?)",
+            params![token, username, Utc::now().timestamp_millis()],
+        ).map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    fn session_user(&self, token: &str) -> Result<Option<String>, String> {
+        self.connection()
+            .query_row("SELECT username FROM sessions WHERE token = ?", params![token], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    fn delete_session(&self, token: &str) -> Result<(), String> {
+        self.connection()
+            .execute("DELETE FROM sessions WHERE token = ?", params![token])
+            .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+}
+
+// Stored rating of a player, or a new one before their first rated game
+fn find_rating(connection: &Connection, player: &str, game_type: &str) -> rusqlite::Result<Rating> {
+    let found = connection.query_row(
+        "SELECT player, game_type, rating, wins, losses, draws, history, anchored FROM ratings
+            WHERE player = ? AND game_type = ?",
+        params![player, game_type],
+        read_rating,
+    ).optional()?;
+    Ok(found.unwrap_or_else(|| Rating::new(player, game_type)))
+}
+
+fn store_rating(connection: &Connection, rating: &Rating) -> Result<(), String> {
+    let history = serde_json::to_string(&rating.history).map_err(|e| e.to_string())?;
+    connection.execute(
+        "INSERT OR REPLACE INTO ratings (player, game_type, rating, wins, losses, draws, history, anchored)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
+        params![
+            rating.player, rating.game_type, rating.rating, rating.wins, rating.losses, rating.draws,
+            history, rating.anchored
+        ],
+    ).map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// SQL version of GameQuery::filter, with the values to bind in order
+fn where_clause(query: &GameQuery) -> (String, Vec<Value>) {
+    let mut conditions = Vec::new();
+    let mut values = Vec::new();
+    if let Some(game_type) = &query.game_type {
+        conditions.push("game_type = ?");
+        values.push(Value::Text(game_type.clone()));
+    }
+    if let Some(player) = &query.player {
+        conditions.push("(player1_name = ? OR player2_name = ?)");
+        values.push(Value::Text(player.clone()));
+        values.push(Value::Text(player.clone()));
+    }
+    if let Some(winner) = &query.winner {
+        conditions.push("winner_name = ?");
+        values.push(Value::Text(winner.clone()));
+    }
+    if let Some(from) = query.from {
+        conditions.push("game_date >= ?");
+        values.push(Value::Integer(from));
+    }
+    if let Some(to) = query.to {
+        conditions.push("game_date <= ?");
+        values.push(Value::Integer(to));
+    }
+    if conditions.is_empty() {
+        (String::new(), values)
+    } else {
+        (format!(" WHERE {}", conditions.join(" AND ")), values)
+    }
+}
+
+// SQL version of GameQuery::sort_document, with ties broken by date and game number
+fn order_by(query: &GameQuery) -> &'static str {
+    match query.sort.unwrap_or_default() {
+        SortOrder::Newest => "game_date DESC, game_number DESC",
+        SortOrder::Oldest => "game_date ASC, game_number ASC",
+        SortOrder::GameType => "game_type ASC, game_date DESC, game_number DESC",
+        SortOrder::Winner => "winner_name ASC, game_date DESC, game_number DESC",
+    }
+}
+
+fn read_game(row: &Row) -> rusqlite::Result<Game> {
+    let moves: String = row.get(6)?;
+    Ok(Game {
+        game_number: row.get(0)?,
+        game_type: row.get(1)?,
+        player1_name: row.get(2)?,
+        player2_name: row.get(3)?,
+        winner_name: row.get(4)?,
+        game_date: row.get(5)?,
+        // Moves that cannot be read leave the game without them, as MongoDB's defaults do
+        moves: serde_json::from_str(&moves).unwrap_or_default(),
+        columns: row.get(7)?,
+        rows: row.get(8)?,
+        connect: row.get(9)?,
+        difficulty: row.get(10)?,
+        player1_color: row.get(11)?,
+        player2_color: row.get(12)?
+    })
+}
+
+fn read_rating(row: &Row) -> rusqlite::Result<Rating> {
+    let history: String = row.get(6)?;
+    Ok(Rating {
+        player: row.get(0)?,
+        game_type: row.get(1)?,
+        rating: row.get(2)?,
+        wins: row.get(3)?,
+        losses: row.get(4)?,
+        draws: row.get(5)?,
+        history: serde_json::from_str(&history).unwrap_or_default(),
+        anchored: row.get(7)?
+    })
+}
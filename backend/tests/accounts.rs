@@ -0,0 +1,22 @@
+use std::sync::Arc;
+use std::thread;
+use backend::store::{MemoryStore, SqliteStore, Store, User};
+
+const REGISTRATIONS: usize = 20;
+
+#[test]
+fn a_username_is_only_taken_once() {
+    let stores: Vec<Store> = vec![Arc::new(MemoryStore::new()), Arc::new(SqliteStore::open_in_memory().unwrap())];
+    for store in stores {
+        let threads: Vec<_> = (0..REGISTRATIONS).map(|attempt| {
+            let store = store.clone();
+            thread::spawn(move || {
+                let user = User { username: "Ann".into(), password_hash: format!("hash {}", attempt) };
+                store.insert_user(user).unwrap()
+            })
+        }).collect();
+        let inserted = threads.into_iter().map(|thread| thread.join().unwrap()).filter(|&inserted| inserted).count();
+        assert_eq!(inserted, 1);
+        assert!(store.find_user("Ann").unwrap().is_some());
+    }
+}
@@ -0,0 +1,200 @@
+use std::sync::Arc;
+use backend::config::ServerConfig;
+use backend::store::{GameStore, MemoryStore, SqliteStore, Store, User};
+use models::account::Session;
+use models::game::{Game, GameMove};
+use models::listing::{GamePage, GameQuery, DEFAULT_PAGE_SIZE};
+use models::rating::{Rating, INITIAL_RATING};
+use rocket::http::{ContentType, Header, Status};
+use rocket::local::Client;
+
+// Every test runs on each store that needs no server, so they behave the same
+fn clients() -> Vec<Client> {
+    let stores: Vec<Store> = vec![Arc::new(MemoryStore::new()), Arc::new(SqliteStore::open_in_memory().unwrap())];
+    stores.into_iter()
+        .map(|store| Client::new(backend::mount(rocket::ignite(), store, &ServerConfig::default()).unwrap()).unwrap())
+        .collect()
+}
+
+// Store whose accounts cannot be read, as when the database is failing
+struct AccountsDown(MemoryStore);
+
+impl GameStore for AccountsDown {
+    fn insert_game(&self, game: Game) -> Result<Game, String> { self.0.insert_game(game) }
+    fn list_games(&self, query: &GameQuery) -> Result<GamePage, String> { self.0.list_games(query) }
+    fn update_ratings(&self, game: &Game) -> Result<(), String> { self.0.update_ratings(game) }
+    fn ratings(&self, game_type: &str) -> Result<Vec<Rating>, String> { self.0.ratings(game_type) }
+    fn find_user(&self, _username: &str) -> Result<Option<User>, String> { Err(String::from("The accounts are down")) }
+    fn insert_user(&self, user: User) -> Result<bool, String> { self.0.insert_user(user) }
+    fn insert_session(&self, token: &str, username: &str) -> Result<(), String> { self.0.insert_session(token, username) }
+    fn session_user(&self, token: &str) -> Result<Option<String>, String> { self.0.session_user(token) }
+    fn delete_session(&self, token: &str) -> Result<(), String> { self.0.delete_session(token) }
+}
+
+// Player one wins down the first column
+fn finished_game(player1: &str, player2: &str, game_date: i64) -> Game {
+    Game {
+        game_type: "Connect4".into(),
+        player1_name: player1.into(),
+        player2_name: player2.into(),
+        game_date,
+        moves: [0, 1, 0, 1, 0, 1, 0].iter().map(|&column| GameMove { column, letter: None, pop: false }).collect(),
+        ..Game::default()
+    }
+}
+
+fn insert(client: &Client, game: &Game, token: Option<&str>) -> (Status, String) {
+    let mut request = client.post("/insert-game")
+        .header(ContentType::JSON)
+        .body(serde_json::to_string(game).unwrap());
+    if let Some(token) = token {
+        request = request.header(Header::new("Authorization", format!("Bearer {}", token)));
+    }
+    let mut response = request.dispatch();
+    (response.status(), response.body_string().unwrap_or_default())
+}
+
+fn get<T: serde::de::DeserializeOwned>(client: &Client, uri: &str) -> T {
+    let mut response = client.get(uri.to_string()).dispatch();
+    assert_eq!(response.status(), Status::Ok, "GET {}", uri);
+    serde_json::from_str(&response.body_string().unwrap()).unwrap()
+}
+
+fn register(client: &Client, username: &str, password: &str) -> Session {
+    let mut response = client.post("/register")
+        .header(ContentType::JSON)
+        .body(format!(r#"{{"username":"{}","password":"{}"}}"#, username, password))
+        .dispatch();
+    assert_eq!(response.status(), Status::Ok);
+    serde_json::from_str(&response.body_string().unwrap()).unwrap()
+}
+
+#[test]
+fn saved_games_are_numbered_and_judged() {
+    for client in clients() {
+        let mut numbers = Vec::new();
+        for date in 1..=3 {
+            let mut game = finished_game("Ann", "Bob", date);
+            // The server decides the number and the winner
+            game.game_number = 99;
+            game.winner_name = "Bob".into();
+            let (status, body) = insert(&client, &game, None);
+            assert_eq!(status, Status::Ok);
+            let saved: Game = serde_json::from_str(&body).unwrap();
+            assert_eq!(saved.winner_name, "Ann");
+            numbers.push(saved.game_number);
+        }
+        assert_eq!(numbers, vec![1, 2, 3]);
+
+        let unfinished = Game { moves: Vec::new(), ..finished_game("Ann", "Bob", 4) };
+        assert_eq!(insert(&client, &unfinished, None).0, Status::UnprocessableEntity);
+
+        // Every game goes through the checks above, there is no route around them
+        assert_eq!(client.get("/insert-default-test").dispatch().status(), Status::NotFound);
+        let listing: GamePage = get(&client, "/list-games");
+        assert_eq!(listing.total, 3);
+    }
+}
+
+#[test]
+fn games_are_listed_a_page_at_a_time() {
+    for client in clients() {
+        for date in 1..=5 {
+            insert(&client, &finished_game("Ann", "Bob", date), None);
+        }
+        insert(&client, &finished_game("Cat", "Dan", 6), None);
+
+        let first: GamePage = get(&client, "/list-games?page_size=2&player=Ann");
+        assert_eq!(first.total, 5);
+        assert_eq!(first.page_count(), 3);
+        assert_eq!(first.games.iter().map(|game| game.game_date).collect::<Vec<_>>(), vec![5, 4]);
+
+        let last: GamePage = get(&client, "/list-games?page=3&page_size=2&player=Ann&sort=oldest");
+        assert_eq!(last.games.iter().map(|game| game.game_date).collect::<Vec<_>>(), vec![5]);
+
+        let winners: GamePage = get(&client, "/list-games?winner=Cat");
+        assert_eq!(winners.total, 1);
+
+        let response = client.get("/list-games?sort=sideways").dispatch();
+        assert_eq!(response.status(), Status::UnprocessableEntity);
+    }
+}
+
+#[test]
+fn listings_stay_in_bounds() {
+    for client in clients() {
+        // Saved at the same time, so only their numbers tell them apart
+        for _ in 0..5 {
+            insert(&client, &finished_game("Ann", "Bob", 1), None);
+        }
+        let mut numbers = Vec::new();
+        for page in 1..=3 {
+            let listing: GamePage = get(&client, &format!("/list-games?page={}&page_size=2", page));
+            numbers.extend(listing.games.iter().map(|game| game.game_number));
+        }
+        assert_eq!(numbers, vec![5, 4, 3, 2, 1]);
+
+        // Every game at once is only for the server itself
+        let all: GamePage = get(&client, "/list-games?page_size=0");
+        assert_eq!(all.page_size, DEFAULT_PAGE_SIZE);
+
+        let beyond: GamePage = get(&client, &format!("/list-games?page={}&page_size=2", i64::MAX));
+        assert!(beyond.games.is_empty());
+        assert_eq!(beyond.total, 5);
+    }
+}
+
+#[test]
+fn accounts_keep_their_names() {
+    for client in clients() {
+        let session = register(&client, "Ann", "correct horse");
+
+        let response = client.post("/register")
+            .header(ContentType::JSON)
+            .body(r#"{"username":"Ann","password":"battery staple"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Conflict);
+
+        let response = client.post("/login")
+            .header(ContentType::JSON)
+            .body(r#"{"username":"Ann","password":"wrong password"}"#)
+            .dispatch();
+        assert_eq!(response.status(), Status::Unauthorized);
+
+        // Only Ann's session may save games as Ann
+        let game = finished_game("Ann", "Bob", 1);
+        assert_eq!(insert(&client, &game, None).0, Status::Forbidden);
+        assert_eq!(insert(&client, &game, Some(&session.token)).0, Status::Ok);
+
+        let response = client.post("/logout")
+            .header(Header::new("Authorization", session.bearer()))
+            .dispatch();
+        assert_eq!(response.status(), Status::NoContent);
+        assert_eq!(insert(&client, &game, Some(&session.token)).0, Status::Forbidden);
+    }
+}
+
+#[test]
+fn saved_games_move_the_leaderboard() {
+    for client in clients() {
+        insert(&client, &finished_game("Ann", "Bob", 1), None);
+
+        let ratings: Vec<Rating> = get(&client, "/leaderboard?game_type=Connect4");
+        assert_eq!(ratings.iter().map(|rating| rating.player.as_str()).collect::<Vec<_>>(), vec!["Ann", "Bob"]);
+        assert!(ratings[0].rating > INITIAL_RATING && ratings[1].rating < INITIAL_RATING);
+
+        let none: Vec<Rating> = get(&client, "/leaderboard?game_type=TOOT-OTTO");
+        assert!(none.is_empty());
+    }
+}
+
+#[test]
+fn games_wait_for_the_accounts_to_be_checked() {
+    let store: Store = Arc::new(AccountsDown(MemoryStore::new()));
+    let client = Client::new(backend::mount(rocket::ignite(), store, &ServerConfig::default()).unwrap()).unwrap();
+    let (status, body) = insert(&client, &finished_game("Ann", "Bob", 1), None);
+    assert_eq!(status, Status::InternalServerError);
+    assert!(body.contains("The accounts are down"));
+    let listing: GamePage = get(&client, "/list-games");
+    assert_eq!(listing.total, 0);
+}
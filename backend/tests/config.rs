@@ -0,0 +1,52 @@
+use std::sync::Arc;
+use backend::config::{ServerConfig, StoreKind};
+use backend::store::MemoryStore;
+use rocket::config::{Config, Environment};
+
+#[test]
+fn settings_come_from_the_rocket_config() {
+    let config = Config::build(Environment::Development)
+        .port(9000)
+        .extra("store", "sqlite")
+        .extra("sqlite_path", "/var/lib/connect4/games.sqlite")
+        .extra("allowed_origins", vec!["https://connect4.example.com", "https://www.connect4.example.com"])
+        .finalize()
+        .unwrap();
+    let server = ServerConfig::from_rocket(&config).unwrap();
+    assert_eq!(server.store, StoreKind::Sqlite);
+    assert_eq!(server.sqlite_path, "/var/lib/connect4/games.sqlite");
+    assert_eq!(server.allowed_origins.len(), 2);
+    assert_eq!(server.port, 9000);
+    // Settings left out keep their defaults
+    assert_eq!(server.mongo_uri, ServerConfig::default().mongo_uri);
+    assert_eq!(server.rooms_address, "localhost:8001");
+}
+
+#[test]
+fn one_origin_can_be_given_as_a_string() {
+    let config = Config::build(Environment::Development)
+        .extra("allowed_origins", "https://connect4.example.com")
+        .finalize()
+        .unwrap();
+    let server = ServerConfig::from_rocket(&config).unwrap();
+    assert_eq!(server.allowed_origins, vec!["https://connect4.example.com"]);
+    assert_eq!(server.store, StoreKind::Mongo);
+}
+
+#[test]
+fn bad_settings_are_refused() {
+    let unknown = Config::build(Environment::Development).extra("store", "postgres").finalize().unwrap();
+    assert!(ServerConfig::from_rocket(&unknown).unwrap_err().contains("postgres"));
+
+    let mistyped = Config::build(Environment::Development).extra("mongo_uri", 27017).finalize().unwrap();
+    assert_eq!(ServerConfig::from_rocket(&mistyped).unwrap_err(), "mongo_uri must be a string");
+}
+
+#[test]
+fn bad_origins_are_refused_at_startup() {
+    let config = ServerConfig { allowed_origins: vec![String::from("not an origin")], ..ServerConfig::default() };
+    match backend::mount(rocket::ignite(), Arc::new(MemoryStore::new()), &config) {
+        Err(error) => assert!(error.starts_with("allowed_origins"), "{}", error),
+        Ok(_) => panic!("A bad origin was allowed"),
+    }
+}
@@ -0,0 +1,43 @@
+use std::sync::Arc;
+use std::thread;
+use backend::store::{GameStore, MemoryStore, SqliteStore, Store};
+use models::game::Game;
+
+const GAMES: usize = 40;
+
+fn game(opponent: usize) -> Game {
+    Game {
+        game_type: "Connect4".into(),
+        player1_name: "Ann".into(),
+        player2_name: format!("Opponent {}", opponent),
+        winner_name: "Ann".into(),
+        ..Game::default()
+    }
+}
+
+#[test]
+fn games_finishing_together_all_count() {
+    let stores: Vec<Store> = vec![Arc::new(MemoryStore::new()), Arc::new(SqliteStore::open_in_memory().unwrap())];
+    for store in stores {
+        let threads: Vec<_> = (0..GAMES).map(|opponent| {
+            let store = store.clone();
+            thread::spawn(move || store.update_ratings(&game(opponent)).unwrap())
+        }).collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let ratings = store.ratings("Connect4").unwrap();
+        assert_eq!(ratings.len(), GAMES + 1);
+        assert_eq!(ratings[0].player, "Ann");
+        assert_eq!(ratings[0].wins as usize, GAMES);
+        assert_eq!(ratings[0].history.len(), GAMES);
+    }
+}
+
+#[test]
+fn games_that_cannot_be_rated_change_nothing() {
+    let store = SqliteStore::open_in_memory().unwrap();
+    store.update_ratings(&Game { winner_name: String::new(), ..game(0) }).unwrap();
+    assert!(store.ratings("Connect4").unwrap().is_empty());
+}
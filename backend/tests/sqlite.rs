@@ -0,0 +1,45 @@
+use std::fs;
+use backend::store::{GameStore, SqliteStore};
+use models::game::{Game, GameMove};
+use models::listing::GameQuery;
+
+fn game(game_date: i64) -> Game {
+    Game {
+        game_type: "Connect4".into(),
+        player1_name: "Ann".into(),
+        player2_name: "Bob".into(),
+        winner_name: "Ann".into(),
+        game_date,
+        moves: vec![GameMove { column: 3, letter: None, pop: false }, GameMove { column: 2, letter: None, pop: true }],
+        columns: 7,
+        rows: 6,
+        connect: 4,
+        difficulty: "hard".into(),
+        ..Game::default()
+    }
+}
+
+#[test]
+fn reopened_databases_keep_their_games() {
+    let path = std::env::temp_dir().join(format!("connect4-store-{}.sqlite", std::process::id()));
+    let _ = fs::remove_file(&path);
+
+    let store = SqliteStore::open(&path).unwrap();
+    let first = store.insert_game(game(1)).unwrap();
+    store.update_ratings(&first).unwrap();
+    drop(store);
+
+    // The migrations have already run, so opening again leaves the tables alone
+    let store = SqliteStore::open(&path).unwrap();
+    let second = store.insert_game(game(2)).unwrap();
+    assert_eq!((first.game_number, second.game_number), (1, 2));
+
+    let listing = store.list_games(&GameQuery::default()).unwrap();
+    assert_eq!(listing.total, 2);
+    assert_eq!(listing.games[1], first);
+    let ratings = store.ratings("Connect4").unwrap();
+    assert_eq!(ratings.iter().map(|rating| (rating.player.as_str(), rating.wins)).collect::<Vec<_>>(), vec![("Ann", 1), ("Bob", 0)]);
+
+    drop(store);
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,54 @@
+use serde::{Serialize, Deserialize};
+
+/// Shortest password an account may have
+pub const MIN_PASSWORD_LENGTH: usize = 8;
+/// Longest username an account may have, in characters
+pub const MAX_USERNAME_LENGTH: usize = 32;
+/// Names the games already use for something other than a person
+pub const RESERVED_NAMES: [&str; 2] = ["Computer", "Draw"];
+
+/// Username and password sent to `/register` and `/login`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String
+}
+
+impl Credentials {
+    /// Check the credentials are fit for a new account, giving the reason to
+    /// show the player if not
+    pub fn validate(&self) -> Result<(), String> {
+        let username = self.username.trim();
+        if username.is_empty() {
+            return Err(String::from("Please enter a username"));
+        }
+        if username != self.username {
+            return Err(String::from("Usernames cannot start or end with a space"));
+        }
+        if username.chars().count() > MAX_USERNAME_LENGTH {
+            return Err(format!("Usernames can be at most {} characters long", MAX_USERNAME_LENGTH));
+        }
+        if RESERVED_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(username)) {
+            return Err(format!("{} cannot be used as a username", username));
+        }
+        if self.password.chars().count() < MIN_PASSWORD_LENGTH {
+            return Err(format!("Passwords must be at least {} characters long", MIN_PASSWORD_LENGTH));
+        }
+        Ok(())
+    }
+}
+
+/// A logged in account, returned by `/register` and `/login`. Requests made
+/// on its behalf carry the token in an `Authorization: Bearer <token>` header.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Session {
+    pub username: String,
+    pub token: String
+}
+
+impl Session {
+    /// Value for the `Authorization` header
+    pub fn bearer(&self) -> String {
+        format!("Bearer {}", self.token)
+    }
+}
@@ -0,0 +1,284 @@
+use crate::connect4::{Connect4Move, Connect4State, Variant, MAX_SIZE, POP_OUT_MOVES_PER_CELL};
+use crate::player::Player;
+
+// Random keys for Zobrist hashing, one per player per bit, plus one for the side to move
+const KEYS: [[u64; 128]; 2] = zobrist_keys();
+const SIDE_KEY: u64 = 0x5851_f42d_4c95_7f2d;
+
+/// Connect 4 position packed into bit masks, for searching deeper than
+/// `Connect4State` allows.
+///
+/// Column `c` owns bits `c * (rows + 1)` upwards, bottom row first, with one
+/// spare bit on top of every column so lines never wrap from one column into
+/// the next. The largest board, 10 x 10, needs 110 bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bitboard {
+    discs: [u128; 2],
+    heights: [u8; MAX_SIZE],
+    columns: u8,
+    rows: u8,
+    connect: u8,
+    pop_out: bool,
+    moves: u32,
+    hash: u64
+}
+
+impl Bitboard {
+    pub fn from_state(state: &Connect4State) -> Self {
+        let mut board = Bitboard {
+            discs: [0; 2],
+            heights: [0; MAX_SIZE],
+            columns: state.columns() as u8,
+            rows: state.rows() as u8,
+            connect: state.connect() as u8,
+            pop_out: state.variant() == Variant::PopOut,
+            moves: state.moves().len() as u32,
+            hash: 0
+        };
+        // The state's row 0 is the top of the board
+        for (top_row, cells) in state.board().tokens.iter().enumerate() {
+            let row = state.rows() - 1 - top_row;
+            for (column, &token) in cells.iter().enumerate() {
+                if let Some(player) = Player::from_token(token) {
+                    board.discs[index(player)] |= board.cell(column, row);
+                    board.heights[column] += 1;
+                }
+            }
+        }
+        board.hash = (0..board.columns()).fold(0, |hash, column| hash ^ board.column_hash(column));
+        if board.current_player() == Player::Two {
+            board.hash ^= SIDE_KEY;
+        }
+        board
+    }
+
+    pub fn columns(&self) -> usize {
+        self.columns as usize
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows as usize
+    }
+
+    /// Number of discs in a line needed to win
+    pub fn connect(&self) -> usize {
+        self.connect as usize
+    }
+
+    /// Moves played so far
+    pub fn move_count(&self) -> u32 {
+        self.moves
+    }
+
+    pub fn current_player(&self) -> Player {
+        if self.moves % 2 == 1 {
+            Player::Two
+        } else {
+            Player::One
+        }
+    }
+
+    /// Whether pops are allowed
+    pub fn pop_out(&self) -> bool {
+        self.pop_out
+    }
+
+    /// Zobrist hash of the discs and the side to move, kept up to date as moves are played
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Cells held by `player`
+    pub fn discs(&self, player: Player) -> u128 {
+        self.discs[index(player)]
+    }
+
+    /// Cells holding a disc of either player
+    pub fn occupied(&self) -> u128 {
+        self.discs[0] | self.discs[1]
+    }
+
+    /// Cell a drop into `column` would fill
+    pub fn drop_cell(&self, column: usize) -> u128 {
+        self.cell(column, self.heights[column] as usize)
+    }
+
+    /// Cells the next drop into each column would fill
+    pub fn playable(&self) -> u128 {
+        (0..self.columns())
+            .filter(|&column| self.can_drop(column))
+            .fold(0, |cells, column| cells | self.drop_cell(column))
+    }
+
+    /// Empty cells where a disc would complete a line for `player`, whether
+    /// or not a disc can be dropped there yet
+    pub fn threats(&self, player: Player) -> u128 {
+        let discs = self.discs(player);
+        let height = self.rows as u32 + 1;
+        let connect = self.connect as i32;
+        let mut threats = 0;
+        for &step in [1, height, height + 1, height - 1].iter() {
+            // Cells that complete a line with the gap at position `gap` along it
+            for gap in 0..connect {
+                let mut cells = !0;
+                for k in (0..connect).filter(|&k| k != gap) {
+                    let offset = (k - gap).unsigned_abs() * step;
+                    cells &= if k > gap { discs >> offset } else { discs << offset };
+                }
+                threats |= cells;
+            }
+        }
+        threats & self.cells() & !self.occupied()
+    }
+
+    pub fn can_drop(&self, column: usize) -> bool {
+        self.heights[column] < self.rows
+    }
+
+    pub fn can_pop(&self, column: usize) -> bool {
+        self.pop_out && self.discs(self.current_player()) & self.cell(column, 0) != 0
+    }
+
+    /// Whether the player to move has anything to play
+    pub fn has_moves(&self) -> bool {
+        !self.at_move_limit() && (0..self.columns()).any(|column| self.can_drop(column) || self.can_pop(column))
+    }
+
+    /// Whether a PopOut game has lasted long enough to be drawn, as in `Connect4State::move_limit`
+    pub fn at_move_limit(&self) -> bool {
+        self.pop_out && self.moves as usize >= POP_OUT_MOVES_PER_CELL * self.columns() * self.rows()
+    }
+
+    /// Play a legal move for the current player, returning the winner if it ended the game.
+    ///
+    /// A drop can only complete a line for the player who made it, so only that
+    /// player's discs are checked. A pop shifts the whole column and is checked
+    /// for both; if both connect the player who popped wins.
+    pub fn play(&mut self, mv: Connect4Move) -> Option<Player> {
+        let player = self.current_player();
+        let me = index(player);
+        self.moves += 1;
+        self.hash ^= SIDE_KEY;
+        match mv {
+            Connect4Move::Drop(column) => {
+                let shift = self.shift(column, self.heights[column] as usize);
+                self.discs[me] |= 1 << shift;
+                self.hash ^= KEYS[me][shift];
+                self.heights[column] += 1;
+                if self.has_line(self.discs[me]) {
+                    return Some(player);
+                }
+                None
+            }
+            Connect4Move::Pop(column) => {
+                let mask = self.column_mask(column);
+                self.hash ^= self.column_hash(column);
+                for discs in self.discs.iter_mut() {
+                    *discs = (*discs & !mask) | (((*discs & mask) >> 1) & mask);
+                }
+                self.hash ^= self.column_hash(column);
+                self.heights[column] -= 1;
+                if self.has_line(self.discs[me]) {
+                    Some(player)
+                } else if self.has_line(self.discs[1 - me]) {
+                    Some(player.other())
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    /// Every line of `connect` cells on the board, as masks
+    pub fn lines(&self) -> Vec<u128> {
+        let (columns, rows, connect) = (self.columns as isize, self.rows as isize, self.connect as isize);
+        let mut lines = Vec::new();
+        for column in 0..columns {
+            for row in 0..rows {
+                for &(d_col, d_row) in [(1, 0), (0, 1), (1, 1), (1, -1)].iter() {
+                    let end_col = column + d_col * (connect - 1);
+                    let end_row = row + d_row * (connect - 1);
+                    if end_col >= columns || end_row < 0 || end_row >= rows {
+                        continue;
+                    }
+                    lines.push((0..connect).fold(0, |line, k| {
+                        line | self.cell((column + k * d_col) as usize, (row + k * d_row) as usize)
+                    }));
+                }
+            }
+        }
+        lines
+    }
+
+    /// Cell `row` (counted from the bottom) of `column`
+    pub fn cell(&self, column: usize, row: usize) -> u128 {
+        1 << self.shift(column, row)
+    }
+
+    fn shift(&self, column: usize, row: usize) -> usize {
+        column * (self.rows as usize + 1) + row
+    }
+
+    // Hash of the discs in one column, so a pop can swap it out in one go
+    fn column_hash(&self, column: usize) -> u64 {
+        let mut hash = 0;
+        for row in 0..self.rows() {
+            let shift = self.shift(column, row);
+            for (player, discs) in self.discs.iter().enumerate() {
+                if discs & (1 << shift) != 0 {
+                    hash ^= KEYS[player][shift];
+                }
+            }
+        }
+        hash
+    }
+
+    // Every cell on the board, leaving out the spare bit above each column
+    fn cells(&self) -> u128 {
+        (0..self.columns()).fold(0, |cells, column| cells | self.column_mask(column))
+    }
+
+    fn column_mask(&self, column: usize) -> u128 {
+        ((1 << self.rows) - 1) << (column * (self.rows as usize + 1))
+    }
+
+    // Shifting by one step in a direction and AND-ing `connect - 1` times
+    // leaves a bit set only where a full line starts
+    fn has_line(&self, discs: u128) -> bool {
+        let height = self.rows as u32 + 1;
+        [1, height, height + 1, height - 1].iter().any(|&step| {
+            let mut line = discs;
+            for k in 1..self.connect as u32 {
+                line &= discs >> (step * k);
+            }
+            line != 0
+        })
+    }
+}
+
+fn index(player: Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
+
+// Fixed keys from the splitmix64 generator, so hashes are the same on every run
+const fn zobrist_keys() -> [[u64; 128]; 2] {
+    let mut keys = [[0; 128]; 2];
+    let mut seed: u64 = 0;
+    let mut player = 0;
+    while player < 2 {
+        let mut shift = 0;
+        while shift < 128 {
+            seed = seed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = seed;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            keys[player][shift] = z ^ (z >> 31);
+            shift += 1;
+        }
+        player += 1;
+    }
+    keys
+}
@@ -0,0 +1,454 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::bitboard::Bitboard;
+use crate::connect4_eval::Evaluator;
+use crate::engine::Game;
+use crate::game_boards::Connect4GameBoard;
+use crate::player::Player;
+
+// Standard board size
+pub const ROWS: usize = 6;
+pub const COLUMNS: usize = 7;
+pub const CONNECT: usize = 4;
+
+// Limits for custom board sizes
+pub const MIN_SIZE: usize = 4;
+pub const MAX_SIZE: usize = 10;
+pub const MIN_CONNECT: usize = 3;
+
+/// Pops can bring back earlier positions, so a PopOut game is drawn once it
+/// has lasted this many moves for each cell of the board
+pub const POP_OUT_MOVES_PER_CELL: usize = 3;
+
+// Directions (row, column) that a line of discs can run in
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+
+/// Rule set a game is played under
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Variant {
+    Standard,
+    /// Players may also pop one of their own discs out of the bottom row
+    PopOut
+}
+
+impl Variant {
+    /// Name stored as the game type of a finished game
+    pub fn game_type(self) -> &'static str {
+        match self {
+            Variant::Standard => "Connect4",
+            Variant::PopOut => "Connect4-PopOut",
+        }
+    }
+
+    pub fn from_game_type(game_type: &str) -> Option<Variant> {
+        match game_type {
+            "Connect4" => Some(Variant::Standard),
+            "Connect4-PopOut" => Some(Variant::PopOut),
+            _ => None,
+        }
+    }
+}
+
+/// A disc dropped into the top of a column, or popped out of its bottom
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Connect4Move {
+    Drop(usize),
+    Pop(usize)
+}
+
+impl Connect4Move {
+    pub fn column(self) -> usize {
+        match self {
+            Connect4Move::Drop(column) | Connect4Move::Pop(column) => column,
+        }
+    }
+}
+
+/// Result of a move that was accepted by the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveOutcome {
+    Continue,
+    Win(Player),
+    Draw
+}
+
+/// Reason a move was rejected by the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    GameOver,
+    InvalidColumn(usize),
+    ColumnFull(usize),
+    CannotPop(usize)
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "The game is already over"),
+            MoveError::InvalidColumn(column) => write!(f, "Column {} is not on the board", column),
+            MoveError::ColumnFull(column) => write!(f, "Column {} is full", column),
+            MoveError::CannotPop(column) => write!(f, "Cannot pop a disc out of column {}", column),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// Reason a board size was rejected
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SizeError {
+    Columns(usize),
+    Rows(usize),
+    Connect(usize)
+}
+
+impl fmt::Display for SizeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SizeError::Columns(columns) =>
+                write!(f, "Board width must be between {} and {} (got {})", MIN_SIZE, MAX_SIZE, columns),
+            SizeError::Rows(rows) =>
+                write!(f, "Board height must be between {} and {} (got {})", MIN_SIZE, MAX_SIZE, rows),
+            SizeError::Connect(connect) =>
+                write!(f, "Connect length must be at least {} and fit on the board (got {})", MIN_CONNECT, connect),
+        }
+    }
+}
+
+impl std::error::Error for SizeError {}
+
+/// Connect 4 game state and rules, independent of any UI.
+///
+/// Row 0 of the board is the top row, so discs fall towards the last row.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Connect4State {
+    board: Connect4GameBoard,
+    connect: usize,
+    variant: Variant,
+    moves: Vec<Connect4Move>,
+    winner: Option<Player>
+}
+
+impl Default for Connect4State {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Connect4State {
+    /// Standard 7 x 6 board, connect four
+    pub fn new() -> Self {
+        Self::build(COLUMNS, ROWS, CONNECT)
+    }
+
+    /// Board with `columns` x `rows` cells where `connect` discs in a line win
+    pub fn with_size(columns: usize, rows: usize, connect: usize) -> Result<Self, SizeError> {
+        if !(MIN_SIZE..=MAX_SIZE).contains(&columns) {
+            return Err(SizeError::Columns(columns));
+        }
+        if !(MIN_SIZE..=MAX_SIZE).contains(&rows) {
+            return Err(SizeError::Rows(rows));
+        }
+        if connect < MIN_CONNECT || connect > columns.max(rows) {
+            return Err(SizeError::Connect(connect));
+        }
+        Ok(Self::build(columns, rows, connect))
+    }
+
+    fn build(columns: usize, rows: usize, connect: usize) -> Self {
+        Connect4State {
+            board: Connect4GameBoard {
+                rows: rows as u8,
+                columns: columns as u8,
+                tokens: vec![vec![0; columns]; rows],
+            },
+            connect,
+            variant: Variant::Standard,
+            moves: Vec::new(),
+            winner: None
+        }
+    }
+
+    /// Same board, played under the given rules
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    pub fn variant(&self) -> Variant {
+        self.variant
+    }
+
+    pub fn board(&self) -> &Connect4GameBoard {
+        &self.board
+    }
+
+    pub fn columns(&self) -> usize {
+        self.board.columns as usize
+    }
+
+    pub fn rows(&self) -> usize {
+        self.board.rows as usize
+    }
+
+    /// Number of discs in a line needed to win
+    pub fn connect(&self) -> usize {
+        self.connect
+    }
+
+    /// Number of moves played so far
+    pub fn move_num(&self) -> usize {
+        self.moves.len()
+    }
+
+    /// Moves after which the game is drawn, for PopOut only
+    pub fn move_limit(&self) -> Option<usize> {
+        match self.variant {
+            Variant::Standard => None,
+            Variant::PopOut => Some(POP_OUT_MOVES_PER_CELL * self.columns() * self.rows()),
+        }
+    }
+
+    /// Moves played so far, in order
+    pub fn moves(&self) -> &[Connect4Move] {
+        &self.moves
+    }
+
+    pub fn current_player(&self) -> Player {
+        if self.moves.len() % 2 == 1 {
+            Player::Two
+        } else {
+            Player::One
+        }
+    }
+
+    /// Moves open to the current player, drops before pops, empty once the game is over
+    pub fn legal_moves(&self) -> Vec<Connect4Move> {
+        if self.winner.is_some() || self.at_move_limit() {
+            return Vec::new();
+        }
+        self.available_moves()
+    }
+
+    /// Drop a disc for the current player into `column`
+    pub fn play(&mut self, column: usize) -> Result<MoveOutcome, MoveError> {
+        self.play_move(Connect4Move::Drop(column))
+    }
+
+    /// Pop one of the current player's discs out of the bottom of `column`
+    pub fn pop(&mut self, column: usize) -> Result<MoveOutcome, MoveError> {
+        self.play_move(Connect4Move::Pop(column))
+    }
+
+    pub fn play_move(&mut self, mv: Connect4Move) -> Result<MoveOutcome, MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        let column = mv.column();
+        if column >= self.columns() {
+            return Err(MoveError::InvalidColumn(column));
+        }
+        let player = self.current_player();
+
+        match mv {
+            Connect4Move::Drop(column) => {
+                let row = match self.drop_row(column) {
+                    Some(row) => row,
+                    None => return Err(MoveError::ColumnFull(column)),
+                };
+                self.board.tokens[row][column] = player.token();
+                self.moves.push(mv);
+                if self.connects(row, column) {
+                    self.winner = Some(player);
+                }
+            }
+            Connect4Move::Pop(column) => {
+                if !self.can_pop(column, player) {
+                    return Err(MoveError::CannotPop(column));
+                }
+                for row in (1..self.rows()).rev() {
+                    self.board.tokens[row][column] = self.board.tokens[row - 1][column];
+                }
+                self.board.tokens[0][column] = 0;
+                self.moves.push(mv);
+                self.winner = self.pop_winner(column, player);
+            }
+        }
+
+        if let Some(winner) = self.winner {
+            return Ok(MoveOutcome::Win(winner));
+        }
+        if self.is_draw() {
+            return Ok(MoveOutcome::Draw);
+        }
+        Ok(MoveOutcome::Continue)
+    }
+
+    /// Take back the last move
+    pub fn undo(&mut self) -> Option<Connect4Move> {
+        let mv = self.moves.pop()?;
+        match mv {
+            Connect4Move::Drop(column) => {
+                if let Some(row) = (0..self.rows()).find(|&row| self.board.tokens[row][column] != 0) {
+                    self.board.tokens[row][column] = 0;
+                }
+            }
+            Connect4Move::Pop(column) => {
+                // The history is one move shorter, so the current player is the one who popped
+                let bottom = self.rows() - 1;
+                for row in 0..bottom {
+                    self.board.tokens[row][column] = self.board.tokens[row + 1][column];
+                }
+                self.board.tokens[bottom][column] = self.current_player().token();
+            }
+        }
+        self.winner = None;
+        Some(mv)
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.winner
+    }
+
+    /// Cells (row, column) of the winner's line, or `None` if nobody has won.
+    /// A line longer than `connect` is returned in full.
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        let token = self.winner?.token();
+        for row in 0..self.rows() {
+            for column in (0..self.columns()).filter(|&column| self.board.tokens[row][column] == token) {
+                for &(d_row, d_col) in DIRECTIONS.iter() {
+                    // Only count each line from its first disc
+                    if self.count_from(row, column, -d_row, -d_col, token) > 0 {
+                        continue;
+                    }
+                    let length = 1 + self.count_from(row, column, d_row, d_col, token);
+                    if length >= self.connect {
+                        return Some((0..length as isize)
+                            .map(|k| ((row as isize + k * d_row) as usize, (column as isize + k * d_col) as usize))
+                            .collect());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Nobody has won and the player to move has nothing left to play,
+    /// which in the standard game means the board is full. A PopOut game is
+    /// also drawn when it reaches its move limit.
+    pub fn is_draw(&self) -> bool {
+        self.winner.is_none() && (self.at_move_limit() || self.available_moves().is_empty())
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.is_draw()
+    }
+
+    fn available_moves(&self) -> Vec<Connect4Move> {
+        let mut moves: Vec<Connect4Move> = (0..self.columns())
+            .filter(|&column| self.board.tokens[0][column] == 0)
+            .map(Connect4Move::Drop)
+            .collect();
+        if self.variant == Variant::PopOut {
+            let player = self.current_player();
+            moves.extend((0..self.columns())
+                .filter(|&column| self.can_pop(column, player))
+                .map(Connect4Move::Pop));
+        }
+        moves
+    }
+
+    fn at_move_limit(&self) -> bool {
+        self.move_limit().is_some_and(|limit| self.moves.len() >= limit)
+    }
+
+    fn can_pop(&self, column: usize, player: Player) -> bool {
+        self.variant == Variant::PopOut && self.board.tokens[self.rows() - 1][column] == player.token()
+    }
+
+    // A pop moves every disc in the column, so it can complete lines for
+    // either player. If both players connect at once the player who popped wins.
+    fn pop_winner(&self, column: usize, popper: Player) -> Option<Player> {
+        let mut opponent_connects = false;
+        for row in (0..self.rows()).filter(|&row| self.board.tokens[row][column] != 0) {
+            if self.connects(row, column) {
+                if self.board.tokens[row][column] == popper.token() {
+                    return Some(popper);
+                }
+                opponent_connects = true;
+            }
+        }
+        if opponent_connects {
+            Some(popper.other())
+        } else {
+            None
+        }
+    }
+
+    // Lowest empty row in a column, if there is one
+    fn drop_row(&self, column: usize) -> Option<usize> {
+        (0..self.rows()).rev().find(|&row| self.board.tokens[row][column] == 0)
+    }
+
+    // Whether the disc at (row, column) is part of a line of `connect` discs
+    fn connects(&self, row: usize, column: usize) -> bool {
+        let token = self.board.tokens[row][column];
+        DIRECTIONS.iter().any(|&(d_row, d_col)| {
+            1 + self.count_from(row, column, d_row, d_col, token)
+                + self.count_from(row, column, -d_row, -d_col, token) >= self.connect
+        })
+    }
+
+    // Number of consecutive `token` discs starting next to (row, column) in one direction
+    fn count_from(&self, row: usize, column: usize, d_row: isize, d_col: isize, token: i8) -> usize {
+        let mut count = 0;
+        let (mut r, mut c) = (row as isize + d_row, column as isize + d_col);
+        while r >= 0 && r < self.rows() as isize && c >= 0 && c < self.columns() as isize
+            && self.board.tokens[r as usize][c as usize] == token {
+            count += 1;
+            r += d_row;
+            c += d_col;
+        }
+        count
+    }
+}
+
+impl Game for Connect4State {
+    type Move = Connect4Move;
+
+    fn current_player(&self) -> Player {
+        Connect4State::current_player(self)
+    }
+
+    fn legal_moves(&self) -> Vec<Connect4Move> {
+        Connect4State::legal_moves(self)
+    }
+
+    fn apply(&mut self, mv: Connect4Move) {
+        let result = self.play_move(mv);
+        debug_assert!(result.is_ok(), "illegal move {:?}", mv);
+    }
+
+    fn undo(&mut self) {
+        Connect4State::undo(self);
+    }
+
+    fn is_over(&self) -> bool {
+        Connect4State::is_over(self)
+    }
+
+    fn winner(&self) -> Option<Player> {
+        Connect4State::winner(self)
+    }
+
+    // Scored on a bitboard, the same way as the deeper Connect 4 search
+    fn evaluate(&self, player: Player) -> i64 {
+        let board = Bitboard::from_state(self);
+        let score = Evaluator::new(&board).evaluate(&board);
+        if player == self.current_player() {
+            score
+        } else {
+            -score
+        }
+    }
+}
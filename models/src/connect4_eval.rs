@@ -0,0 +1,109 @@
+use crate::bitboard::Bitboard;
+use crate::player::Player;
+
+/// Score of a position where the player to move can win with their next disc,
+/// or cannot stop the opponent from winning with theirs. Well below
+/// `search::WIN_SCORE`, so a win found by searching always counts for more.
+pub const IMMEDIATE_WIN: i64 = 100_000;
+
+// Weights of the parts of the evaluation
+const THREAT: i64 = 40;
+const PARITY_THREAT: i64 = 120;
+const CENTER: i64 = 3;
+
+/// Static evaluation of Connect 4 positions, set up once for a board size.
+///
+/// A position is scored from the point of view of the player to move by
+/// adding up, for each player:
+///
+/// * windows of `connect` cells holding only that player's discs, by the
+///   square of the number of discs (a window with both colours can never
+///   become a line, so it scores nothing);
+/// * threats, the empty cells that would complete a line, with a bonus for
+///   threats on rows of the player's parity;
+/// * discs in the center column(s), which take part in the most lines.
+///
+/// When a board has an even number of cells and the rest of it fills up,
+/// player one ends up with the odd rows (counting the bottom as row 1) and
+/// player two with the even rows, so only threats on those rows are likely to
+/// be forced through at the end.
+pub struct Evaluator {
+    lines: Vec<u128>,
+    odd_rows: u128,
+    even_rows: u128,
+    center: u128,
+    parity: bool
+}
+
+impl Evaluator {
+    pub fn new(board: &Bitboard) -> Self {
+        let (columns, rows) = (board.columns(), board.rows());
+        let mut odd_rows = 0;
+        let mut even_rows = 0;
+        let mut center = 0;
+        for column in 0..columns {
+            let middle = (2 * column + 1).abs_diff(columns) <= 1;
+            for row in 0..rows {
+                let cell = board.cell(column, row);
+                if row % 2 == 1 {
+                    even_rows |= cell;
+                } else {
+                    odd_rows |= cell;
+                }
+                if middle {
+                    center |= cell;
+                }
+            }
+        }
+        Evaluator {
+            lines: board.lines(),
+            odd_rows,
+            even_rows,
+            center,
+            parity: (columns * rows) % 2 != 1
+        }
+    }
+
+    /// Score of `board` for the player to move
+    pub fn evaluate(&self, board: &Bitboard) -> i64 {
+        let me = board.current_player();
+        let playable = board.playable();
+        if board.threats(me) & playable != 0 {
+            return IMMEDIATE_WIN;
+        }
+        if (board.threats(me.other()) & playable).count_ones() > 1 {
+            return -IMMEDIATE_WIN;
+        }
+        self.score(board, me) - self.score(board, me.other())
+    }
+
+    fn score(&self, board: &Bitboard, player: Player) -> i64 {
+        let mine = board.discs(player);
+        let theirs = board.discs(player.other());
+        let connect = board.connect() as u32;
+
+        let windows: i64 = self.lines.iter()
+            .filter(|&&line| line & theirs == 0)
+            .map(|&line| {
+                let discs = (line & mine).count_ones();
+                // Windows one disc short are counted as threats instead
+                if discs + 1 < connect {
+                    i64::from(discs * discs)
+                } else {
+                    0
+                }
+            })
+            .sum();
+
+        let threats = board.threats(player);
+        let good_rows = match player {
+            Player::One => self.odd_rows,
+            Player::Two => self.even_rows,
+        };
+        let parity_threats = if self.parity { threats & good_rows } else { 0 };
+        let threat_score = THREAT * i64::from(threats.count_ones())
+            + (PARITY_THREAT - THREAT) * i64::from(parity_threats.count_ones());
+
+        windows + threat_score + CENTER * i64::from((mine & self.center).count_ones())
+    }
+}
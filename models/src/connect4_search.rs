@@ -0,0 +1,236 @@
+use crate::bitboard::Bitboard;
+use crate::connect4::{Connect4Move, Connect4State};
+use crate::connect4_eval::Evaluator;
+use crate::search::WIN_SCORE;
+use crate::transposition::{Bound, Entry, TranspositionTable};
+
+/// Deepest iteration `best_move` will run, in plies
+pub const MAX_DEPTH: u32 = 64;
+
+// Slots in the transposition table, as a power of two (about 5 MB)
+const TABLE_BITS: u32 = 17;
+
+// Scores beyond this are forced wins or losses rather than evaluations
+const WIN_THRESHOLD: i64 = WIN_SCORE - 1000;
+
+// The clock is read once every this many nodes, which must be a power of two
+const CLOCK_INTERVAL: u64 = 1024;
+
+/// How long and how deep `best_move` may search
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Limits {
+    /// Milliseconds to spend on the move, as measured by the `now` clock
+    pub time_budget: f64,
+    /// Deepest iteration to run, in plies
+    pub max_depth: u32
+}
+
+impl Limits {
+    /// Keep deepening until the time budget runs out
+    pub fn time(time_budget: f64) -> Self {
+        Limits { time_budget, max_depth: MAX_DEPTH }
+    }
+}
+
+/// Move chosen by `best_move` with its score for the player to move
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SearchResult {
+    pub best_move: Connect4Move,
+    pub score: i64,
+    /// Depth of the last iteration that finished in time
+    pub depth: u32,
+    /// Positions visited during the search
+    pub nodes: u64
+}
+
+/// Iterative deepening alpha-beta search of a Connect 4 position on bitboards.
+///
+/// Each iteration searches one ply deeper than the last, trying the best move
+/// found so far first and reusing earlier results from a transposition table.
+/// When the time budget runs out the unfinished iteration is dropped and the
+/// result of the last complete one is returned, so a move is always ready on
+/// time. Returns `None` if the game is already over.
+pub fn best_move(state: &Connect4State, limits: Limits, now: &dyn Fn() -> f64) -> Option<SearchResult> {
+    if state.is_over() {
+        return None;
+    }
+    let board = Bitboard::from_state(state);
+    let mut search = Search {
+        evaluator: Evaluator::new(&board),
+        order: center_first(board.columns()),
+        table: TranspositionTable::new(TABLE_BITS),
+        deadline: now() + limits.time_budget,
+        now,
+        nodes: 0,
+        out_of_time: false
+    };
+
+    // A standard game cannot last longer than it takes to fill the board
+    let empty = state.rows() * state.columns() - state.moves().len();
+    let max_depth = if board.pop_out() {
+        limits.max_depth
+    } else {
+        limits.max_depth.min(empty as u32)
+    };
+
+    let mut result: Option<SearchResult> = None;
+    for depth in 1..=max_depth.max(1) {
+        let first = result.map(|result| result.best_move);
+        let (best_move, score) = search.root(&board, depth, first);
+        // The first iteration is too small to look at the clock, so it always completes
+        if search.out_of_time {
+            break;
+        }
+        result = Some(SearchResult { best_move, score, depth, nodes: search.nodes });
+        if score.abs() > WIN_THRESHOLD {
+            break;
+        }
+    }
+    result.map(|result| SearchResult { nodes: search.nodes, ..result })
+}
+
+struct Search<'a> {
+    evaluator: Evaluator,
+    order: Vec<usize>,
+    table: TranspositionTable,
+    deadline: f64,
+    now: &'a dyn Fn() -> f64,
+    nodes: u64,
+    out_of_time: bool
+}
+
+impl Search<'_> {
+    fn root(&mut self, board: &Bitboard, depth: u32, first: Option<Connect4Move>) -> (Connect4Move, i64) {
+        let moves = self.moves(board, first);
+        let mut best = (moves[0], -WIN_SCORE - 1);
+        for mv in moves {
+            let score = self.score_move(board, mv, depth - 1, best.1, WIN_SCORE + 1, 0);
+            if score > best.1 {
+                best = (mv, score);
+            }
+        }
+        best
+    }
+
+    // `first` (if legal), then drops in center-first order, then pops
+    fn moves(&self, board: &Bitboard, first: Option<Connect4Move>) -> Vec<Connect4Move> {
+        let mut moves = Vec::with_capacity(2 * board.columns());
+        if board.at_move_limit() {
+            return moves;
+        }
+        moves.extend(self.order.iter()
+            .filter(|&&column| board.can_drop(column))
+            .map(|&column| Connect4Move::Drop(column)));
+        moves.extend(self.order.iter()
+            .filter(|&&column| board.can_pop(column))
+            .map(|&column| Connect4Move::Pop(column)));
+        if let Some(position) = first.and_then(|first| moves.iter().position(|&mv| mv == first)) {
+            let first = moves.remove(position);
+            moves.insert(0, first);
+        }
+        moves
+    }
+
+    // Score of playing `mv` at `ply` for the player making it
+    fn score_move(&mut self, board: &Bitboard, mv: Connect4Move, depth: u32, alpha: i64, beta: i64, ply: i64) -> i64 {
+        let player = board.current_player();
+        let mut child = *board;
+        match child.play(mv) {
+            Some(winner) if winner == player => WIN_SCORE - ply - 1,
+            Some(_) => -(WIN_SCORE - ply - 1),
+            None => -self.negamax(&child, depth, -beta, -alpha, ply + 1),
+        }
+    }
+
+    fn negamax(&mut self, board: &Bitboard, depth: u32, mut alpha: i64, mut beta: i64, ply: i64) -> i64 {
+        self.nodes += 1;
+        if self.nodes & (CLOCK_INTERVAL - 1) == 0 && (self.now)() >= self.deadline {
+            self.out_of_time = true;
+        }
+        if self.out_of_time {
+            // The unfinished iteration is thrown away, so any score will do
+            return 0;
+        }
+
+        let original_alpha = alpha;
+        let entry = self.table.get(board.hash());
+        if let Some(entry) = entry.filter(|entry| entry.depth >= depth) {
+            let score = from_table(entry.score, ply);
+            match entry.bound {
+                Bound::Exact => return score,
+                Bound::Lower => alpha = alpha.max(score),
+                Bound::Upper => beta = beta.min(score),
+            }
+            if alpha >= beta {
+                return score;
+            }
+        }
+
+        let moves = self.moves(board, entry.and_then(|entry| entry.best_move));
+        if moves.is_empty() {
+            return 0;
+        }
+        if depth == 0 {
+            return self.evaluator.evaluate(board);
+        }
+
+        let mut best = (None, -WIN_SCORE - 1);
+        for mv in moves {
+            let score = self.score_move(board, mv, depth - 1, alpha, beta, ply);
+            if score > best.1 {
+                best = (Some(mv), score);
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        if !self.out_of_time {
+            let bound = if best.1 <= original_alpha {
+                Bound::Upper
+            } else if best.1 >= beta {
+                Bound::Lower
+            } else {
+                Bound::Exact
+            };
+            self.table.store(Entry {
+                hash: board.hash(),
+                depth,
+                score: to_table(best.1, ply),
+                bound,
+                best_move: best.0
+            });
+        }
+        best.1
+    }
+}
+
+// Win scores count plies from the root, but the table may be probed from a
+// different ply, so they are stored relative to the position itself
+fn to_table(score: i64, ply: i64) -> i64 {
+    if score > WIN_THRESHOLD {
+        score + ply
+    } else if score < -WIN_THRESHOLD {
+        score - ply
+    } else {
+        score
+    }
+}
+
+fn from_table(score: i64, ply: i64) -> i64 {
+    if score > WIN_THRESHOLD {
+        score - ply
+    } else if score < -WIN_THRESHOLD {
+        score + ply
+    } else {
+        score
+    }
+}
+
+// Columns ordered by distance from the middle of the board
+pub(crate) fn center_first(columns: usize) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..columns).collect();
+    order.sort_by_key(|&column| (2 * column as isize - (columns as isize - 1)).abs());
+    order
+}
@@ -0,0 +1,33 @@
+use crate::connect4::Variant;
+
+/// A level the computer can be played at
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Difficulty {
+    /// Value stored in `Game::difficulty`
+    pub name: &'static str,
+    /// Name shown on the difficulty buttons
+    pub label: &'static str,
+    /// Fixed rating of the computer at this level, for the leaderboard
+    pub anchor_rating: f64,
+    /// Whether the level plays with the solver, so only standard Connect 4
+    /// offers it
+    pub solver: bool
+}
+
+/// Every level, easiest first. The difficulty buttons and the ratings both
+/// come from this list.
+pub const DIFFICULTIES: [Difficulty; 4] = [
+    Difficulty { name: "easy", label: "Easy", anchor_rating: 1000.0, solver: false },
+    Difficulty { name: "medium", label: "Medium", anchor_rating: 1400.0, solver: false },
+    Difficulty { name: "hard", label: "Hard", anchor_rating: 1800.0, solver: false },
+    // Hard's search until the position can be solved in time, which in the
+    // opening it usually cannot
+    Difficulty { name: "expert", label: "Expert", anchor_rating: 2200.0, solver: true },
+];
+
+/// Levels offered for `game_type`, easiest first. PopOut and TOOT-OTTO have
+/// no solver, so they stop at hard.
+pub fn offered(game_type: &str) -> impl Iterator<Item = &'static Difficulty> {
+    let solved = game_type == Variant::Standard.game_type();
+    DIFFICULTIES.iter().filter(move |difficulty| solved || !difficulty.solver)
+}
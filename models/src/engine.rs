@@ -0,0 +1,26 @@
+use std::fmt::Debug;
+use crate::player::Player;
+
+/// Common interface of the two-player games, used by the computer opponents
+/// and by any tooling that should work for every variant.
+pub trait Game: Clone {
+    type Move: Copy + PartialEq + Debug;
+
+    fn current_player(&self) -> Player;
+
+    /// Moves the current player may make, empty once the game is over
+    fn legal_moves(&self) -> Vec<Self::Move>;
+
+    /// Play a move taken from `legal_moves`
+    fn apply(&mut self, mv: Self::Move);
+
+    /// Take back the most recently applied move
+    fn undo(&mut self);
+
+    fn is_over(&self) -> bool;
+
+    fn winner(&self) -> Option<Player>;
+
+    /// Heuristic score of an unfinished position from `player`'s point of view
+    fn evaluate(&self, player: Player) -> i64;
+}
@@ -1,4 +1,6 @@
 use serde::{Serialize, Deserialize};
+use crate::connect4::Connect4Move;
+use crate::toot_otto::TootOttoMove;
 // use bson::oid::ObjectId;
 
 // #[derive(Serialize, Deserialize, Debug)]
@@ -13,23 +15,73 @@ use serde::{Serialize, Deserialize};
 //     pub game_date: i64
 // }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A finished game as stored by the backend.
+///
+/// Everything after `game_date` was added later, so it defaults to empty (or
+/// zero) when an older document without it is read.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 pub struct Game {
     pub game_number: i32,
     pub game_type: String,
     pub player1_name: String,
     pub player2_name: String,
     pub winner_name: String,
-    pub game_date: i64
+    pub game_date: i64,
+    /// Every move in the order it was played
+    #[serde(default)]
+    pub moves: Vec<GameMove>,
+    /// Board size, or 0 if it was not recorded
+    #[serde(default)]
+    pub columns: i32,
+    #[serde(default)]
+    pub rows: i32,
+    /// Discs in a line needed to win Connect 4, or 0 if it was not recorded
+    #[serde(default)]
+    pub connect: i32,
+    /// Difficulty of the computer player, empty for games between two people
+    #[serde(default)]
+    pub difficulty: String,
+    #[serde(default)]
+    pub player1_color: String,
+    #[serde(default)]
+    pub player2_color: String
 }
 
-impl Game {
-    pub fn to_json_string(&self) -> String {
-        format!(r#"{{"game_number": {}, "game_type": "{}", "player1_name": "{}", "player2_name": "{}", "winner_name": "{}", "game_date": {} }}"#,
-                self.game_number, self.game_type, self.player1_name, self.player2_name, self.winner_name, self.game_date)
+/// One move of a stored game: the column, the letter for TOOT-OTTO, and
+/// whether a PopOut disc was popped rather than dropped
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameMove {
+    pub column: i32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub letter: Option<char>,
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub pop: bool
+}
+
+impl From<Connect4Move> for GameMove {
+    fn from(mv: Connect4Move) -> Self {
+        GameMove {
+            column: mv.column() as i32,
+            letter: None,
+            pop: matches!(mv, Connect4Move::Pop(_))
+        }
     }
 }
 
+impl From<TootOttoMove> for GameMove {
+    fn from(mv: TootOttoMove) -> Self {
+        GameMove {
+            column: mv.column as i32,
+            letter: Some(mv.letter.to_char()),
+            pop: false
+        }
+    }
+}
+
+fn is_false(value: &bool) -> bool {
+    !*value
+}
+
 // impl Game {
 //     pub fn set_game_number(&mut self, number: i32) {
 //         self.game_number = number;
@@ -1,9 +1,11 @@
+#[derive(Debug, Clone, PartialEq)]
 pub struct Connect4GameBoard {
     pub rows: u8,
     pub columns: u8,
-    pub tokens: [[i8; 7];6]
+    pub tokens: Vec<Vec<i8>>
 }
 
+#[derive(Debug, Clone, PartialEq)]
 pub struct TootOttoGameBoard {
     pub rows: u8,
     pub columns: u8,
@@ -1,2 +1,21 @@
 pub mod game;
-pub mod game_boards;
\ No newline at end of file
+pub mod game_boards;
+pub mod player;
+pub mod engine;
+pub mod search;
+pub mod mcts;
+pub mod connect4;
+pub mod bitboard;
+pub mod connect4_eval;
+pub mod transposition;
+pub mod connect4_search;
+pub mod solver;
+pub mod toot_otto;
+pub mod replay;
+pub mod notation;
+pub mod online;
+pub mod account;
+pub mod difficulty;
+pub mod rating;
+pub mod stats;
+pub mod listing;
@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+use bson::{doc, Bson, Document};
+use serde::{Serialize, Deserialize};
+use crate::game::Game;
+
+/// Games on a page when the query does not say
+pub const DEFAULT_PAGE_SIZE: i64 = 20;
+/// Most games a page may hold, other than a page size of 0 for every game
+pub const MAX_PAGE_SIZE: i64 = 100;
+
+/// Order of the games in a listing
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    #[default]
+    Newest,
+    Oldest,
+    GameType,
+    Winner
+}
+
+impl SortOrder {
+    pub const ALL: [SortOrder; 4] = [SortOrder::Newest, SortOrder::Oldest, SortOrder::GameType, SortOrder::Winner];
+
+    /// Name used in the `sort` query parameter
+    pub fn name(self) -> &'static str {
+        match self {
+            SortOrder::Newest => "newest",
+            SortOrder::Oldest => "oldest",
+            SortOrder::GameType => "game_type",
+            SortOrder::Winner => "winner",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<SortOrder> {
+        SortOrder::ALL.iter().cloned().find(|order| order.name() == name)
+    }
+}
+
+/// Filters, sort order and page of a `/list-games` request. Filters left as
+/// None match every game.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GameQuery {
+    /// Page wanted, counting from 1
+    pub page: Option<i64>,
+    /// Games on each page, with 0 asking for every matching game at once.
+    /// The `/list-games` route takes 0 as the default instead.
+    pub page_size: Option<i64>,
+    pub game_type: Option<String>,
+    /// Games this player played in, on either side
+    pub player: Option<String>,
+    pub winner: Option<String>,
+    /// First and last time of the date range, in milliseconds since 1970
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub sort: Option<SortOrder>
+}
+
+impl GameQuery {
+    pub fn page(&self) -> i64 {
+        self.page.unwrap_or(1).max(1)
+    }
+
+    pub fn page_size(&self) -> i64 {
+        match self.page_size {
+            Some(size) if size <= 0 => 0,
+            Some(size) => size.min(MAX_PAGE_SIZE),
+            None => DEFAULT_PAGE_SIZE,
+        }
+    }
+
+    /// Games skipped to reach the page
+    pub fn skip(&self) -> i64 {
+        // A page far past the end skips every game rather than overflowing
+        (self.page() - 1).saturating_mul(self.page_size())
+    }
+
+    /// MongoDB filter matching the games the query asks for
+    pub fn filter(&self) -> Document {
+        let mut filter = Document::new();
+        if let Some(game_type) = &self.game_type {
+            filter.insert("game_type", game_type.clone());
+        }
+        if let Some(player) = &self.player {
+            filter.insert("$or", vec![
+                Bson::Document(doc! { "player1_name": player.clone() }),
+                Bson::Document(doc! { "player2_name": player.clone() }),
+            ]);
+        }
+        if let Some(winner) = &self.winner {
+            filter.insert("winner_name", winner.clone());
+        }
+        if self.from.is_some() || self.to.is_some() {
+            let mut range = Document::new();
+            if let Some(from) = self.from {
+                range.insert("$gte", from);
+            }
+            if let Some(to) = self.to {
+                range.insert("$lte", to);
+            }
+            filter.insert("game_date", range);
+        }
+        filter
+    }
+
+    /// Whether `game` passes the filters, for stores that cannot run `filter`
+    pub fn matches(&self, game: &Game) -> bool {
+        self.game_type.iter().all(|game_type| &game.game_type == game_type)
+            && self.player.iter().all(|player| &game.player1_name == player || &game.player2_name == player)
+            && self.winner.iter().all(|winner| &game.winner_name == winner)
+            && self.from.iter().all(|&from| game.game_date >= from)
+            && self.to.iter().all(|&to| game.game_date <= to)
+    }
+
+    /// Order of two games in the listing, the same as `sort_document` gives
+    pub fn compare(&self, a: &Game, b: &Game) -> Ordering {
+        let newest = b.game_date.cmp(&a.game_date).then(b.game_number.cmp(&a.game_number));
+        match self.sort.unwrap_or_default() {
+            SortOrder::Newest => newest,
+            SortOrder::Oldest => a.game_date.cmp(&b.game_date).then(a.game_number.cmp(&b.game_number)),
+            SortOrder::GameType => a.game_type.cmp(&b.game_type).then(newest),
+            SortOrder::Winner => a.winner_name.cmp(&b.winner_name).then(newest),
+        }
+    }
+
+    /// MongoDB sort document for the sort order. Ties are broken by date and
+    /// then by game number, which no two games share, so pages never overlap.
+    pub fn sort_document(&self) -> Document {
+        match self.sort.unwrap_or_default() {
+            SortOrder::Newest => doc! { "game_date": -1, "game_number": -1 },
+            SortOrder::Oldest => doc! { "game_date": 1, "game_number": 1 },
+            SortOrder::GameType => doc! { "game_type": 1, "game_date": -1, "game_number": -1 },
+            SortOrder::Winner => doc! { "winner_name": 1, "game_date": -1, "game_number": -1 },
+        }
+    }
+
+    /// The query as URL query parameters, without the leading `?`
+    pub fn to_query_string(&self) -> String {
+        let mut parameters = Vec::new();
+        let mut add = |name: &str, value: String| parameters.push(format!("{}={}", name, percent_encode(&value)));
+        if let Some(page) = self.page {
+            add("page", page.to_string());
+        }
+        if let Some(page_size) = self.page_size {
+            add("page_size", page_size.to_string());
+        }
+        if let Some(game_type) = &self.game_type {
+            add("game_type", game_type.clone());
+        }
+        if let Some(player) = &self.player {
+            add("player", player.clone());
+        }
+        if let Some(winner) = &self.winner {
+            add("winner", winner.clone());
+        }
+        if let Some(from) = self.from {
+            add("from", from.to_string());
+        }
+        if let Some(to) = self.to {
+            add("to", to.to_string());
+        }
+        if let Some(sort) = self.sort {
+            add("sort", sort.name().to_string());
+        }
+        parameters.join("&")
+    }
+}
+
+/// One page of a game listing, with the number of games matching in total
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct GamePage {
+    pub games: Vec<Game>,
+    pub total: i64,
+    pub page: i64,
+    pub page_size: i64
+}
+
+impl GamePage {
+    /// Pages needed for every matching game, at least 1
+    pub fn page_count(&self) -> i64 {
+        if self.page_size <= 0 || self.total == 0 {
+            1
+        } else {
+            (self.total + self.page_size - 1) / self.page_size
+        }
+    }
+}
+
+// Escape everything but the characters URLs leave alone
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::new();
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => encoded.push(byte as char),
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
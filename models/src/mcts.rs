@@ -0,0 +1,152 @@
+use crate::engine::Game;
+use crate::player::Player;
+
+// Weight of the exploration term in the UCT formula
+const EXPLORATION: f64 = std::f64::consts::SQRT_2;
+
+/// Monte Carlo tree search: grow a tree of moves over `playouts` random games
+/// and return the most visited move, with the share of the playouts through
+/// it that the player to move won (draws count as half).
+///
+/// Each playout walks down the tree picking moves by UCT, adds one new move,
+/// then plays randomly to the end of the game. The same `seed` always gives
+/// the same move. Returns `None` if the game is already over.
+pub fn best_move<G: Game>(game: &G, playouts: u32, seed: u64) -> Option<(G::Move, f64)> {
+    if game.is_over() {
+        return None;
+    }
+    let mut tree = Tree {
+        nodes: vec![Node::new(None, game)],
+        random: Random(seed)
+    };
+    let mut game = game.clone();
+    for _ in 0..playouts.max(1) {
+        tree.playout(&mut game);
+    }
+
+    let root = &tree.nodes[0];
+    root.children.iter()
+        .map(|&child| &tree.nodes[child])
+        .max_by_key(|node| node.visits)
+        .and_then(|node| node.mv.map(|mv| (mv, node.score / f64::from(node.visits))))
+}
+
+struct Node<M> {
+    // Move leading here from the parent, and the player who made it
+    mv: Option<M>,
+    player: Player,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    untried: Vec<M>,
+    visits: u32,
+    // Sum of the playout results for `player`
+    score: f64
+}
+
+impl<M: Copy> Node<M> {
+    fn new<G: Game<Move = M>>(parent: Option<(usize, M)>, game: &G) -> Self {
+        Node {
+            mv: parent.map(|(_, mv)| mv),
+            player: game.current_player().other(),
+            parent: parent.map(|(parent, _)| parent),
+            children: Vec::new(),
+            untried: game.legal_moves(),
+            visits: 0,
+            score: 0.0
+        }
+    }
+}
+
+struct Tree<M> {
+    nodes: Vec<Node<M>>,
+    random: Random
+}
+
+impl<M: Copy> Tree<M> {
+    fn playout<G: Game<Move = M>>(&mut self, game: &mut G) {
+        let mut node = 0;
+        let mut applied = 0;
+
+        // Selection
+        while self.nodes[node].untried.is_empty() && !self.nodes[node].children.is_empty() {
+            node = self.select(node);
+            game.apply(self.nodes[node].mv.unwrap());
+            applied += 1;
+        }
+
+        // Expansion
+        if !self.nodes[node].untried.is_empty() {
+            let untried = &mut self.nodes[node].untried;
+            let mv = untried.swap_remove(self.random.below(untried.len()));
+            game.apply(mv);
+            applied += 1;
+            self.nodes.push(Node::new(Some((node, mv)), game));
+            let child = self.nodes.len() - 1;
+            self.nodes[node].children.push(child);
+            node = child;
+        }
+
+        // Simulation
+        while !game.is_over() {
+            let moves = game.legal_moves();
+            game.apply(moves[self.random.below(moves.len())]);
+            applied += 1;
+        }
+        let winner = game.winner();
+        for _ in 0..applied {
+            game.undo();
+        }
+
+        // Backpropagation
+        let mut current = Some(node);
+        while let Some(index) = current {
+            let node = &mut self.nodes[index];
+            node.visits += 1;
+            node.score += match winner {
+                Some(player) if player == node.player => 1.0,
+                Some(_) => 0.0,
+                None => 0.5,
+            };
+            current = node.parent;
+        }
+    }
+
+    // Child with the best upper confidence bound
+    fn select(&self, node: usize) -> usize {
+        let log_visits = f64::from(self.nodes[node].visits).ln();
+        let uct = |child: usize| {
+            let child = &self.nodes[child];
+            let visits = f64::from(child.visits);
+            child.score / visits + EXPLORATION * (log_visits / visits).sqrt()
+        };
+        let children = &self.nodes[node].children;
+        let mut best = children[0];
+        for &child in children[1..].iter() {
+            if uct(child) > uct(best) {
+                best = child;
+            }
+        }
+        best
+    }
+}
+
+// Small xorshift generator, so the models crate needs no randomness of its own
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        // Zero is a fixed point of xorshift, so nudge it off
+        if self.0 == 0 {
+            self.0 = 0x9e37_79b9_7f4a_7c15;
+        }
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // Uniform-enough index below `n`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
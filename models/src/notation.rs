@@ -0,0 +1,299 @@
+use std::fmt;
+use crate::connect4;
+use crate::game::{Game, GameMove};
+use crate::player::Player;
+use crate::replay::{Replay, ReplayError, TOOT_OTTO};
+
+/// Reason a game could not be read from text
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NotationError {
+    /// A header line that is not of the form `[Name "value"]`
+    BadHeader(String),
+    MissingGameType,
+    BadDate(String),
+    BadSize(String),
+    BadResult(String),
+    /// The Result header disagrees with the outcome of the moves
+    WrongResult { claimed: String, played: String },
+    BadMove(String),
+    /// The moves were read but break the rules of the game
+    Illegal(ReplayError)
+}
+
+impl fmt::Display for NotationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NotationError::BadHeader(line) => write!(f, "Cannot read header {}", line),
+            NotationError::MissingGameType => write!(f, "The Game header is missing"),
+            NotationError::BadDate(date) => write!(f, "Cannot read date {:?}", date),
+            NotationError::BadSize(size) => write!(f, "Cannot read board size {:?}", size),
+            NotationError::BadResult(result) => write!(f, "Unknown result {:?}", result),
+            NotationError::WrongResult { claimed, played } =>
+                write!(f, "The Result header says {} but the moves end in {}", claimed, played),
+            NotationError::BadMove(token) => write!(f, "Cannot read move {:?}", token),
+            NotationError::Illegal(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for NotationError {}
+
+/// Write a game as text, in a format modelled on PGN: a block of
+/// `[Name "value"]` headers, a blank line, then the moves.
+///
+/// ```text
+/// [Game "Connect4"]
+/// [Player1 "Ann"]
+/// [Player2 "Computer"]
+/// [Date "2020.03.23 21:46:40"]
+/// [Size "7x6"]
+/// [Connect "4"]
+/// [Result "1-0"]
+///
+/// 4453p4
+/// ```
+///
+/// Columns are numbered from 1 on the left. Connect 4 moves are a run of
+/// columns, with `p` before a column a PopOut disc was popped out of; boards
+/// wider than 9 columns separate the columns with spaces. TOOT-OTTO moves are
+/// a column and a letter each, such as `3T 4O`.
+///
+/// The Result is `1-0` or `0-1` for a win by player one or two, `1/2-1/2` for
+/// a draw and `*` for a game still in progress. The Date is in UTC and left
+/// out when it was never set, as are Difficulty for games between two people
+/// and Size and Connect for TOOT-OTTO.
+pub fn to_notation(game: &Game) -> String {
+    let mut headers = vec![
+        ("Game", game.game_type.clone()),
+        ("Player1", game.player1_name.clone()),
+        ("Player2", game.player2_name.clone()),
+    ];
+    if game.game_date != 0 {
+        headers.push(("Date", format_date(game.game_date)));
+    }
+    let columns = if game.game_type == TOOT_OTTO {
+        0
+    } else if game.columns == 0 {
+        connect4::COLUMNS
+    } else {
+        game.columns as usize
+    };
+    if game.game_type != TOOT_OTTO && game.columns != 0 {
+        headers.push(("Size", format!("{}x{}", game.columns, game.rows)));
+        headers.push(("Connect", game.connect.to_string()));
+    }
+    if !game.difficulty.is_empty() {
+        headers.push(("Difficulty", game.difficulty.clone()));
+    }
+    headers.push(("Result", result(game).to_string()));
+
+    let mut text = String::new();
+    for (name, value) in headers {
+        text.push_str(&format!("[{} \"{}\"]\n", name, value.replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+    text.push('\n');
+
+    let moves: Vec<String> = game.moves.iter().map(|mv| {
+        let pop = if mv.pop { "p" } else { "" };
+        let letter = mv.letter.map(|letter| letter.to_string()).unwrap_or_default();
+        format!("{}{}{}", pop, mv.column + 1, letter)
+    }).collect();
+    // Single digit columns need nothing between them
+    let separator = if columns == 0 || columns > 9 { " " } else { "" };
+    text.push_str(&moves.join(separator));
+    text.push('\n');
+    text
+}
+
+/// Read a game written by `to_notation`. Unknown headers are ignored, and the
+/// moves are replayed to check that they follow the rules and end in the
+/// Result given.
+pub fn from_notation(text: &str) -> Result<Game, NotationError> {
+    let mut game = Game::default();
+    let mut game_type = None;
+    let mut result = "*".to_string();
+    let mut move_text = String::new();
+
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        if !move_text.is_empty() || !line.starts_with('[') {
+            move_text.push_str(line);
+            move_text.push(' ');
+            continue;
+        }
+        let (name, value) = header(line).ok_or_else(|| NotationError::BadHeader(line.to_string()))?;
+        match name {
+            "Game" => game_type = Some(value),
+            "Player1" => game.player1_name = value,
+            "Player2" => game.player2_name = value,
+            "Date" => game.game_date = parse_date(&value).ok_or(NotationError::BadDate(value))?,
+            "Size" => {
+                let size = value.split_once('x')
+                    .and_then(|(columns, rows)| Some((columns.trim().parse().ok()?, rows.trim().parse().ok()?)));
+                let (columns, rows) = size.ok_or(NotationError::BadSize(value))?;
+                game.columns = columns;
+                game.rows = rows;
+            },
+            "Connect" => game.connect = value.parse().map_err(|_| NotationError::BadSize(value))?,
+            "Difficulty" => game.difficulty = value,
+            "Result" => result = value,
+            _ => {},
+        }
+    }
+
+    game.game_type = game_type.ok_or(NotationError::MissingGameType)?;
+    game.winner_name = match result.as_str() {
+        "1-0" => game.player1_name.clone(),
+        "0-1" => game.player2_name.clone(),
+        "1/2-1/2" => "Draw".to_string(),
+        "*" => String::new(),
+        _ => return Err(NotationError::BadResult(result)),
+    };
+    game.moves = if game.game_type == TOOT_OTTO {
+        move_text.split_whitespace().map(toot_otto_move).collect::<Result<_, _>>()?
+    } else {
+        let columns = if game.columns == 0 { connect4::COLUMNS } else { game.columns as usize };
+        let mut moves = Vec::new();
+        for token in move_text.split_whitespace() {
+            if columns > 9 {
+                moves.push(connect4_move(token)?);
+            } else {
+                moves.extend(connect4_moves(token)?);
+            }
+        }
+        moves
+    };
+
+    let replay = Replay::new(&game).map_err(NotationError::Illegal)?;
+    let played = match replay.last().winner() {
+        Some(Player::One) => "1-0",
+        Some(Player::Two) => "0-1",
+        None if replay.last().is_over() => "1/2-1/2",
+        None => "*",
+    };
+    if result != played {
+        return Err(NotationError::WrongResult { claimed: result, played: played.to_string() });
+    }
+    Ok(game)
+}
+
+fn result(game: &Game) -> &'static str {
+    if game.winner_name.is_empty() {
+        "*"
+    } else if game.winner_name == game.player1_name {
+        "1-0"
+    } else if game.winner_name == game.player2_name {
+        "0-1"
+    } else {
+        "1/2-1/2"
+    }
+}
+
+// Name and unescaped value of a `[Name "value"]` line
+fn header(line: &str) -> Option<(&str, String)> {
+    let inner = line.strip_prefix('[')?.strip_suffix(']')?;
+    let (name, value) = inner.split_once(' ')?;
+    let value = value.trim().strip_prefix('"')?.strip_suffix('"')?;
+    let mut unescaped = String::new();
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        unescaped.push(if c == '\\' { chars.next()? } else { c });
+    }
+    Some((name, unescaped))
+}
+
+// Columns are written from 1, but stored from 0
+fn column(digits: &str, token: &str) -> Result<i32, NotationError> {
+    match digits.parse::<i32>() {
+        Ok(column) if column >= 1 => Ok(column - 1),
+        _ => Err(NotationError::BadMove(token.to_string())),
+    }
+}
+
+// One column, perhaps popped, such as `10` or `p3`
+fn connect4_move(token: &str) -> Result<GameMove, NotationError> {
+    let (pop, digits) = match token.strip_prefix(|c| c == 'p' || c == 'P') {
+        Some(digits) => (true, digits),
+        None => (false, token),
+    };
+    Ok(GameMove { column: column(digits, token)?, letter: None, pop })
+}
+
+// A run of single digit columns, such as `4453p4`
+fn connect4_moves(token: &str) -> Result<Vec<GameMove>, NotationError> {
+    let mut moves = Vec::new();
+    let mut pop = false;
+    for c in token.chars() {
+        match c {
+            'p' | 'P' if !pop => pop = true,
+            '0'..='9' => {
+                moves.push(GameMove { column: column(&c.to_string(), token)?, letter: None, pop });
+                pop = false;
+            },
+            _ => return Err(NotationError::BadMove(token.to_string())),
+        }
+    }
+    if pop {
+        return Err(NotationError::BadMove(token.to_string()));
+    }
+    Ok(moves)
+}
+
+// A column and a letter, such as `3T`
+fn toot_otto_move(token: &str) -> Result<GameMove, NotationError> {
+    let bad = || NotationError::BadMove(token.to_string());
+    let letter = token.chars().last().map(|c| c.to_ascii_uppercase()).filter(|&c| c == 'T' || c == 'O').ok_or_else(bad)?;
+    let digits = &token[..token.len() - 1];
+    Ok(GameMove { column: column(digits, token)?, letter: Some(letter), pop: false })
+}
+
+// Milliseconds since 1970 as `YYYY.MM.DD HH:MM:SS` in UTC
+fn format_date(timestamp: i64) -> String {
+    let seconds = timestamp.div_euclid(1000);
+    let (year, month, day) = civil_from_days(seconds.div_euclid(86_400));
+    let time = seconds.rem_euclid(86_400);
+    format!("{:04}.{:02}.{:02} {:02}:{:02}:{:02}", year, month, day, time / 3600, time / 60 % 60, time % 60)
+}
+
+// Reverse of `format_date`; the time of day may be left off
+fn parse_date(date: &str) -> Option<i64> {
+    let mut parts = date.split_whitespace();
+    let numbers = |part: &str, separator: char| -> Option<Vec<i64>> {
+        part.split(separator).map(|number| number.parse().ok()).collect()
+    };
+    let day = numbers(parts.next()?, '.')?;
+    let time = match parts.next() {
+        Some(part) => numbers(part, ':')?,
+        None => vec![0, 0, 0],
+    };
+    if parts.next().is_some() || day.len() != 3 || time.len() != 3
+        || !(0..=9999).contains(&day[0]) || !(1..=12).contains(&day[1]) || !(1..=31).contains(&day[2])
+        || !(0..24).contains(&time[0]) || !(0..60).contains(&time[1]) || !(0..60).contains(&time[2]) {
+        return None;
+    }
+    let days = days_from_civil(day[0], day[1], day[2]);
+    Some(((days * 24 + time[0]) * 60 + time[1]) * 60_000 + time[2] * 1000)
+}
+
+// Date algorithms from http://howardhinnant.github.io/date_algorithms.html
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 { shifted_month + 3 } else { shifted_month - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = if month > 2 { month - 3 } else { month + 9 };
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
@@ -0,0 +1,43 @@
+use serde::{Serialize, Deserialize};
+use crate::game::{Game, GameMove};
+use crate::player::Player;
+
+/// Message from a browser to the room server, sent as JSON over a WebSocket
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ClientMessage {
+    /// Open a room for a new game and wait for an opponent. The creator plays
+    /// first; the board size is ignored for TOOT-OTTO. Playing under the name
+    /// of an account needs that account's session token.
+    Create {
+        name: String,
+        game_type: String,
+        columns: i32,
+        rows: i32,
+        connect: i32,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>
+    },
+    /// Take the empty seat in the room with the given code
+    Join {
+        code: String,
+        name: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        token: Option<String>
+    },
+    /// Play a move, which the server checks before passing it on
+    Move(GameMove)
+}
+
+/// Message from the room server to a browser
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum ServerMessage {
+    /// The room is open under `code`, which the opponent needs to join it
+    Created { code: String },
+    /// Both players are in; `game` holds their names and the game settings
+    Started { game: Game, you: Player },
+    /// A move accepted by the server, sent to both players
+    Moved(GameMove),
+    /// A request that was turned down, with the reason to show the player
+    Rejected { reason: String },
+    OpponentLeft
+}
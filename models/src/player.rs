@@ -0,0 +1,36 @@
+use serde::{Serialize, Deserialize};
+
+/// One of the two sides of a game. `One` always makes the first move.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Player {
+    One,
+    Two
+}
+
+impl Player {
+    /// Token stored on a game board for this player (1 for player one, -1 for player two)
+    pub fn token(self) -> i8 {
+        match self {
+            Player::One => 1,
+            Player::Two => -1,
+        }
+    }
+
+    /// Player owning a board token, or `None` for an empty cell
+    pub fn from_token(token: i8) -> Option<Player> {
+        if token > 0 {
+            Some(Player::One)
+        } else if token < 0 {
+            Some(Player::Two)
+        } else {
+            None
+        }
+    }
+
+    pub fn other(self) -> Player {
+        match self {
+            Player::One => Player::Two,
+            Player::Two => Player::One,
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use serde::{Serialize, Deserialize};
+use crate::difficulty;
+use crate::game::Game;
+
+/// Rating of a player before their first game
+pub const INITIAL_RATING: f64 = 1500.0;
+/// Most a single game can move a rating by
+pub const K_FACTOR: f64 = 32.0;
+/// Ratings kept in each player's history, for showing the recent trend
+pub const HISTORY_LENGTH: usize = 50;
+/// Name the games give the computer player
+pub const COMPUTER: &str = "Computer";
+
+/// Fixed rating of the computer at `difficulty` in `game_type`, or None for a
+/// level that game does not offer
+pub fn anchor_rating(game_type: &str, difficulty: &str) -> Option<f64> {
+    difficulty::offered(game_type).find(|level| level.name == difficulty).map(|level| level.anchor_rating)
+}
+
+/// Name the computer is rated under at `difficulty`, such as `Computer (hard)`
+pub fn computer_name(difficulty: &str) -> String {
+    format!("{} ({})", COMPUTER, difficulty)
+}
+
+/// Elo rating of one player at one game type, with their results so far
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Rating {
+    pub player: String,
+    pub game_type: String,
+    pub rating: f64,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32,
+    /// Rating after each of the most recent games, oldest first
+    pub history: Vec<f64>,
+    /// Set for the computer, whose rating never changes so that the others
+    /// have something fixed to be measured against
+    pub anchored: bool
+}
+
+impl Rating {
+    /// Rating for a player's first game, anchored if they are the computer
+    pub fn new(player: &str, game_type: &str) -> Rating {
+        let anchor = player.strip_prefix(COMPUTER)
+            .and_then(|rest| rest.trim().strip_prefix('('))
+            .and_then(|rest| rest.strip_suffix(')'))
+            .and_then(|difficulty| anchor_rating(game_type, difficulty));
+        Rating {
+            player: player.to_string(),
+            game_type: game_type.to_string(),
+            rating: anchor.unwrap_or(INITIAL_RATING),
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            history: Vec::new(),
+            anchored: anchor.is_some()
+        }
+    }
+
+    pub fn games(&self) -> i32 {
+        self.wins + self.losses + self.draws
+    }
+
+    // Score is 1 for a win, 0.5 for a draw and 0 for a loss
+    fn record(&mut self, opponent: f64, score: f64) {
+        if !self.anchored {
+            self.rating += K_FACTOR * (score - expected_score(self.rating, opponent));
+        }
+        if score > 0.5 {
+            self.wins += 1;
+        } else if score < 0.5 {
+            self.losses += 1;
+        } else {
+            self.draws += 1;
+        }
+        self.history.push(self.rating);
+        if self.history.len() > HISTORY_LENGTH {
+            self.history.remove(0);
+        }
+    }
+}
+
+/// Chance of a player rated `rating` beating one rated `opponent`, counting
+/// a draw as half a win
+pub fn expected_score(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+/// Names the two sides of `game` are rated under, the computer's including
+/// its difficulty. Games still in progress, and computer games at a
+/// difficulty their game type does not offer, are not rated.
+pub fn rated_players(game: &Game) -> Option<(String, String)> {
+    if game.winner_name.is_empty() || game.player1_name == game.player2_name {
+        return None;
+    }
+    let rated_name = |name: &String| {
+        if name == COMPUTER {
+            anchor_rating(&game.game_type, &game.difficulty).map(|_| computer_name(&game.difficulty))
+        } else {
+            Some(name.clone())
+        }
+    };
+    Some((rated_name(&game.player1_name)?, rated_name(&game.player2_name)?))
+}
+
+/// Update the ratings of both players with the result of `game`. Both new
+/// ratings are worked out from the ratings before the game.
+pub fn rate_game(game: &Game, player1: &mut Rating, player2: &mut Rating) {
+    let score = if game.winner_name == game.player1_name {
+        1.0
+    } else if game.winner_name == game.player2_name {
+        0.0
+    } else {
+        0.5
+    };
+    let (rating1, rating2) = (player1.rating, player2.rating);
+    player1.record(rating2, score);
+    player2.record(rating1, 1.0 - score);
+}
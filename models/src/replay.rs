@@ -0,0 +1,160 @@
+use std::fmt;
+use crate::connect4::{self, Connect4Move, Connect4State, SizeError, Variant};
+use crate::game::{Game, GameMove};
+use crate::player::Player;
+use crate::toot_otto::{Letter, TootOttoMove, TootOttoState};
+
+/// Game type stored for TOOT-OTTO games
+pub const TOOT_OTTO: &str = "TOOT-OTTO";
+
+/// Reason a stored game cannot be replayed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    UnknownGameType(String),
+    Size(SizeError),
+    /// Move `index` (counting from 0) could not be played
+    IllegalMove { index: usize, reason: String },
+    /// Nobody has won and the board has room for more moves
+    Unfinished
+}
+
+impl fmt::Display for ReplayError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ReplayError::UnknownGameType(game_type) => write!(f, "Unknown game type {:?}", game_type),
+            ReplayError::Size(error) => error.fmt(f),
+            ReplayError::IllegalMove { index, reason } => write!(f, "Move {} is illegal: {}", index + 1, reason),
+            ReplayError::Unfinished => write!(f, "The game is not over after its last move"),
+        }
+    }
+}
+
+impl std::error::Error for ReplayError {}
+
+/// Board after some number of moves of either game
+#[derive(Debug, Clone, PartialEq)]
+pub enum Position {
+    Connect4(Connect4State),
+    TootOtto(TootOttoState)
+}
+
+impl Position {
+    /// Empty board for the type and size of `game`. Games saved before the
+    /// board size was recorded are played on the standard board.
+    pub fn new(game: &Game) -> Result<Self, ReplayError> {
+        if game.game_type == TOOT_OTTO {
+            return Ok(Position::TootOtto(TootOttoState::new()));
+        }
+        let variant = Variant::from_game_type(&game.game_type)
+            .ok_or_else(|| ReplayError::UnknownGameType(game.game_type.clone()))?;
+        let state = if game.columns == 0 {
+            Connect4State::new()
+        } else {
+            let size = |value: i32| value.max(0) as usize;
+            let connect = if game.connect == 0 { connect4::CONNECT } else { size(game.connect) };
+            Connect4State::with_size(size(game.columns), size(game.rows), connect).map_err(ReplayError::Size)?
+        };
+        Ok(Position::Connect4(state.with_variant(variant)))
+    }
+
+    /// Play a stored move, or say why the rules do not allow it
+    pub fn play(&mut self, mv: GameMove) -> Result<(), String> {
+        if mv.column < 0 {
+            return Err(format!("Column {} is not on the board", mv.column));
+        }
+        let column = mv.column as usize;
+        match self {
+            Position::Connect4(state) => {
+                let mv = if mv.pop { Connect4Move::Pop(column) } else { Connect4Move::Drop(column) };
+                state.play_move(mv).map(|_| ()).map_err(|error| error.to_string())
+            },
+            Position::TootOtto(state) => {
+                let letter = mv.letter.and_then(Letter::from_char).ok_or("No T or O letter given")?;
+                state.play(TootOttoMove { column, letter }).map(|_| ()).map_err(|error| error.to_string())
+            },
+        }
+    }
+
+    pub fn current_player(&self) -> Player {
+        match self {
+            Position::Connect4(state) => state.current_player(),
+            Position::TootOtto(state) => state.current_player(),
+        }
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        match self {
+            Position::Connect4(state) => state.winner(),
+            Position::TootOtto(state) => state.winner(),
+        }
+    }
+
+    /// Cells (row, column) of the winning line, if the game has been won
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        match self {
+            Position::Connect4(state) => state.winning_line(),
+            Position::TootOtto(state) => state.winning_line(),
+        }
+    }
+
+    pub fn is_over(&self) -> bool {
+        match self {
+            Position::Connect4(state) => state.is_over(),
+            Position::TootOtto(state) => state.is_over(),
+        }
+    }
+}
+
+/// Every position of a stored game, from the empty board to the last move
+#[derive(Debug, Clone, PartialEq)]
+pub struct Replay {
+    positions: Vec<Position>
+}
+
+impl Replay {
+    /// Play the moves of `game` from the start under its rules
+    pub fn new(game: &Game) -> Result<Self, ReplayError> {
+        let mut position = Position::new(game)?;
+        let mut positions = vec![position.clone()];
+        for (index, &mv) in game.moves.iter().enumerate() {
+            position.play(mv).map_err(|reason| ReplayError::IllegalMove { index, reason })?;
+            positions.push(position.clone());
+        }
+        Ok(Replay { positions })
+    }
+
+    /// Number of moves in the game
+    pub fn len(&self) -> usize {
+        self.positions.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Board after the first `step` moves; steps past the end give the final board
+    pub fn position(&self, step: usize) -> &Position {
+        &self.positions[step.min(self.len())]
+    }
+
+    /// Board after the last move
+    pub fn last(&self) -> &Position {
+        self.position(self.len())
+    }
+
+    /// Step at which the game was won, if it was
+    pub fn winning_step(&self) -> Option<usize> {
+        self.last().winner().map(|_| self.len())
+    }
+
+    /// Name of the winner of `game` as worked out from its moves, or "Draw"
+    pub fn winner_name(game: &Game) -> Result<String, ReplayError> {
+        let replay = Replay::new(game)?;
+        match replay.last().winner() {
+            Some(Player::One) => Ok(game.player1_name.clone()),
+            Some(Player::Two) => Ok(game.player2_name.clone()),
+            None if replay.last().is_over() => Ok("Draw".to_string()),
+            None => Err(ReplayError::Unfinished),
+        }
+    }
+}
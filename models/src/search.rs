@@ -0,0 +1,57 @@
+use crate::engine::Game;
+
+/// Score of a won position; wins found sooner score slightly higher
+pub const WIN_SCORE: i64 = 1_000_000;
+
+/// Search `depth` plies ahead with alpha-beta pruning and return the score for
+/// the player to move together with every move that reaches it, so callers can
+/// break ties however they like.
+pub fn best_moves<G: Game>(game: &G, depth: u32) -> (i64, Vec<G::Move>) {
+    let mut game = game.clone();
+    let mut best = -WIN_SCORE - 1;
+    let mut choices = Vec::new();
+    for mv in game.legal_moves() {
+        game.apply(mv);
+        // A window just below the best score keeps equally good moves exact
+        let score = -negamax(&mut game, depth.saturating_sub(1), -WIN_SCORE - 1, -(best - 1), 1);
+        game.undo();
+
+        if score > best {
+            best = score;
+            choices.clear();
+            choices.push(mv);
+        } else if score == best {
+            choices.push(mv);
+        }
+    }
+    (best, choices)
+}
+
+// Score of the position for the player to move
+fn negamax<G: Game>(game: &mut G, depth: u32, alpha: i64, beta: i64, ply: i64) -> i64 {
+    if game.is_over() {
+        return match game.winner() {
+            Some(player) if player == game.current_player() => WIN_SCORE - ply,
+            Some(_) => -(WIN_SCORE - ply),
+            None => 0,
+        };
+    }
+    if depth == 0 {
+        return game.evaluate(game.current_player());
+    }
+
+    let mut alpha = alpha;
+    let mut best = -WIN_SCORE - 1;
+    for mv in game.legal_moves() {
+        game.apply(mv);
+        let score = -negamax(game, depth - 1, -beta, -alpha, ply + 1);
+        game.undo();
+
+        best = best.max(score);
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+    best
+}
@@ -0,0 +1,271 @@
+use std::cmp::Reverse;
+use crate::bitboard::Bitboard;
+use crate::connect4::{Connect4Move, Connect4State, Variant};
+use crate::connect4_search::center_first;
+use crate::transposition::{Bound, Entry, TranspositionTable};
+
+// Slots in the transposition table, as a power of two (about 10 MB)
+const TABLE_BITS: u32 = 18;
+
+// The clock is read once every this many nodes, which must be a power of two
+const CLOCK_INTERVAL: u64 = 4096;
+
+/// Game-theoretic value of a position for the player to move, with perfect play from both sides
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Value {
+    /// The player to move wins with a disc played this many plies from now (1 is the next move)
+    Win(u32),
+    /// The opponent wins with a disc played this many plies from now
+    Loss(u32),
+    Draw
+}
+
+/// Value of a standard Connect 4 position, or `None` for PopOut games, which
+/// can repeat positions forever and are not solved.
+pub fn solve(state: &Connect4State) -> Option<Value> {
+    if state.variant() != Variant::Standard {
+        return None;
+    }
+    if let Some(value) = finished(state) {
+        return Some(value);
+    }
+    let board = Bitboard::from_state(state);
+    let mut solver = Solver::new(board.columns(), f64::INFINITY, &|| 0.0);
+    let score = solver.score(&board);
+    Some(value(&board, score))
+}
+
+/// Best move of a standard Connect 4 position with its value, looked up in the
+/// opening book or solved within `time_budget` milliseconds of the `now` clock.
+///
+/// Among winning moves the quickest win is chosen, and among losing moves the
+/// one that holds out longest. Returns `None` if the game is over, is a PopOut
+/// game, or could not be solved in time. The book only holds the first move,
+/// and positions in the first dozen or so plies usually take longer than a
+/// second or two to solve, so callers need another way to move until the
+/// board has filled up.
+pub fn best_move(state: &Connect4State, time_budget: f64, now: &dyn Fn() -> f64) -> Option<(Connect4Move, Value)> {
+    if state.variant() != Variant::Standard || state.is_over() {
+        return None;
+    }
+    if let Some(found) = book_move(state) {
+        return Some(found);
+    }
+
+    let board = Bitboard::from_state(state);
+    let mut solver = Solver::new(board.columns(), now() + time_budget, now);
+    let score = solver.score(&board);
+    let (cells, n) = size(&board);
+    // The score already says how fast the win or how slow the loss is, so
+    // the first move that keeps it is as good as any
+    for column in solver.order.clone() {
+        if solver.out_of_time {
+            return None;
+        }
+        if !board.can_drop(column) {
+            continue;
+        }
+        let mut child = board;
+        // A zero-width search is enough to tell whether the move reaches the score
+        let keeps_score = if child.play(Connect4Move::Drop(column)).is_some() {
+            score == cells - n
+        } else if !child.has_moves() {
+            score == 0
+        } else if can_win_now(&child) {
+            score == -(cells - n - 1)
+        } else {
+            solver.negamax(&child, -score, -score + 1) <= -score
+        };
+        if keeps_score && !solver.out_of_time {
+            return Some((Connect4Move::Drop(column), value(&board, score)));
+        }
+    }
+    None
+}
+
+// Value of a game that has already ended, from the point of view of the player to move
+fn finished(state: &Connect4State) -> Option<Value> {
+    if state.winner().is_some() {
+        Some(Value::Loss(0))
+    } else if state.is_draw() {
+        Some(Value::Draw)
+    } else {
+        None
+    }
+}
+
+// Scores count from the end of the game: a win for the player to move that is
+// completed by the disc filling `n` of `cells` scores `cells + 1 - n`, a loss
+// scores the negative of that and a draw scores zero. Faster wins score higher.
+struct Solver<'a> {
+    table: TranspositionTable,
+    order: Vec<usize>,
+    deadline: f64,
+    now: &'a dyn Fn() -> f64,
+    nodes: u64,
+    out_of_time: bool
+}
+
+impl<'a> Solver<'a> {
+    fn new(columns: usize, deadline: f64, now: &'a dyn Fn() -> f64) -> Self {
+        Solver {
+            table: TranspositionTable::new(TABLE_BITS),
+            order: center_first(columns),
+            deadline,
+            now,
+            nodes: 0,
+            out_of_time: false
+        }
+    }
+
+    // Exact score of a position that is not over, narrowing the possible range
+    // with zero-width searches
+    fn score(&mut self, board: &Bitboard) -> i64 {
+        let (cells, n) = size(board);
+        if can_win_now(board) {
+            return cells - n;
+        }
+
+        let (mut min, mut max) = (-(cells - n - 1), cells - n - 2);
+        while min < max && !self.out_of_time {
+            // Probe closer to zero first, where most positions end up
+            let mut middle = min + (max - min) / 2;
+            if middle <= 0 && min / 2 < middle {
+                middle = min / 2;
+            } else if middle >= 0 && max / 2 > middle {
+                middle = max / 2;
+            }
+            let score = self.negamax(board, middle, middle + 1);
+            if score <= middle {
+                max = score;
+            } else {
+                min = score;
+            }
+        }
+        min
+    }
+
+    // Only called on positions where the player to move cannot win at once,
+    // which holds for the root (checked by `score`) and for every position
+    // reached through a non-losing move
+    fn negamax(&mut self, board: &Bitboard, mut alpha: i64, mut beta: i64) -> i64 {
+        self.nodes += 1;
+        if self.nodes & (CLOCK_INTERVAL - 1) == 0 && (self.now)() >= self.deadline {
+            self.out_of_time = true;
+        }
+        if self.out_of_time {
+            return alpha;
+        }
+
+        let (cells, n) = size(board);
+        let me = board.current_player();
+        let opponent_threats = board.threats(me.other());
+        let playable: Vec<usize> = self.order.iter().cloned()
+            .filter(|&column| board.can_drop(column))
+            .collect();
+
+        // A threat the opponent can play next must be blocked, and two cannot be
+        let forced: Vec<usize> = playable.iter().cloned()
+            .filter(|&column| board.drop_cell(column) & opponent_threats != 0)
+            .collect();
+        let mut candidates = match forced.len() {
+            0 => playable,
+            1 => forced,
+            _ => return -(cells - n - 1),
+        };
+        // Never fill the cell under one of the opponent's threats
+        candidates.retain(|&column| (board.drop_cell(column) << 1) & opponent_threats == 0);
+        if candidates.is_empty() {
+            return -(cells - n - 1);
+        }
+        if n >= cells - 2 {
+            return 0;
+        }
+
+        // The opponent cannot win with their next disc, and neither can we with this one
+        let min = -(cells - n - 3);
+        if alpha < min {
+            alpha = min;
+            if alpha >= beta {
+                return alpha;
+            }
+        }
+        let mut max = cells - n - 2;
+        if let Some(entry) = self.table.get(board.hash()) {
+            max = entry.score;
+        }
+        if beta > max {
+            beta = max;
+            if alpha >= beta {
+                return beta;
+            }
+        }
+
+        // Moves that leave us the most threats first
+        let mut children: Vec<(u32, Bitboard)> = candidates.into_iter()
+            .map(|column| {
+                let mut child = *board;
+                child.play(Connect4Move::Drop(column));
+                (child.threats(me).count_ones(), child)
+            })
+            .collect();
+        children.sort_by_key(|&(threats, _)| Reverse(threats));
+
+        for (_, child) in children {
+            let score = -self.negamax(&child, -beta, -alpha);
+            if score >= beta {
+                return score;
+            }
+            alpha = alpha.max(score);
+        }
+
+        if !self.out_of_time {
+            self.table.store(Entry {
+                hash: board.hash(),
+                depth: 0,
+                score: alpha,
+                bound: Bound::Upper,
+                best_move: None
+            });
+        }
+        alpha
+    }
+}
+
+fn can_win_now(board: &Bitboard) -> bool {
+    let threats = board.threats(board.current_player());
+    (0..board.columns()).any(|column| board.can_drop(column) && board.drop_cell(column) & threats != 0)
+}
+
+fn value(board: &Bitboard, score: i64) -> Value {
+    let (cells, n) = size(board);
+    if score > 0 {
+        Value::Win((cells + 1 - score - n) as u32)
+    } else if score < 0 {
+        Value::Loss((cells + 1 + score - n) as u32)
+    } else {
+        Value::Draw
+    }
+}
+
+fn size(board: &Bitboard) -> (i64, i64) {
+    ((board.columns() * board.rows()) as i64, board.move_count() as i64)
+}
+
+// Opening book for the standard 7 x 6 board, keyed by the columns played so far.
+// The empty board takes far too long to solve in the browser, so its answer is
+// written in. Every later position is left to the solver and its time budget.
+fn book_move(state: &Connect4State) -> Option<(Connect4Move, Value)> {
+    if (state.columns(), state.rows(), state.connect()) != (7, 6, 4) {
+        return None;
+    }
+    let played: String = state.moves().iter().map(|mv| char::from(b'0' + mv.column() as u8)).collect();
+    BOOK.iter()
+        .find(|&&(moves, _, _)| moves == played)
+        .map(|&(_, column, value)| (Connect4Move::Drop(column), value))
+}
+
+const BOOK: &[(&str, usize, Value)] = &[
+    // The first player wins by starting in the center, with their 21st disc
+    ("", 3, Value::Win(41)),
+];
@@ -0,0 +1,121 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::game::Game;
+use crate::rating::COMPUTER;
+
+/// Wins, losses and draws over some set of games
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Record {
+    pub played: i32,
+    pub wins: i32,
+    pub losses: i32,
+    pub draws: i32
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Outcome {
+    Win,
+    Loss,
+    Draw
+}
+
+impl Record {
+    fn add(&mut self, outcome: Outcome) {
+        self.played += 1;
+        match outcome {
+            Outcome::Win => self.wins += 1,
+            Outcome::Loss => self.losses += 1,
+            Outcome::Draw => self.draws += 1,
+        }
+    }
+}
+
+/// Everything known about one player from their finished games
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct PlayerStats {
+    pub player: String,
+    pub overall: Record,
+    /// Most played first, as are the other breakdowns
+    pub by_game_type: Vec<(String, Record)>,
+    pub by_opponent: Vec<(String, Record)>,
+    /// Games against the computer, by the difficulty it played at
+    pub by_difficulty: Vec<(String, Record)>,
+    pub longest_win_streak: i32,
+    /// Moves in an average game, counting only games saved with their moves
+    pub average_moves: f64,
+    /// Column of the player's first move in each game, from 0, and how many
+    /// games it was played in
+    pub opening_columns: Vec<(i32, i32)>
+}
+
+impl PlayerStats {
+    /// Gather the statistics of `player` from `games`. Games they did not play
+    /// in, or that have no result, are left out.
+    pub fn from_games(player: &str, games: &[Game]) -> PlayerStats {
+        let mut played: Vec<&Game> = games.iter()
+            .filter(|game| !game.winner_name.is_empty())
+            .filter(|game| game.player1_name == player || game.player2_name == player)
+            .collect();
+        // Streaks run in the order the games were played
+        played.sort_by_key(|game| game.game_date);
+
+        let mut stats = PlayerStats { player: player.to_string(), ..PlayerStats::default() };
+        let mut by_game_type = HashMap::new();
+        let mut by_opponent = HashMap::new();
+        let mut by_difficulty = HashMap::new();
+        let mut openings = HashMap::new();
+        let mut streak = 0;
+        let mut total_moves = 0;
+        let mut games_with_moves = 0;
+
+        for game in played {
+            let (opponent, first_move) = if game.player1_name == player {
+                (&game.player2_name, 0)
+            } else {
+                (&game.player1_name, 1)
+            };
+            let outcome = if game.winner_name == player {
+                Outcome::Win
+            } else if game.winner_name == *opponent {
+                Outcome::Loss
+            } else {
+                Outcome::Draw
+            };
+
+            stats.overall.add(outcome);
+            by_game_type.entry(game.game_type.clone()).or_insert_with(Record::default).add(outcome);
+            by_opponent.entry(opponent.clone()).or_insert_with(Record::default).add(outcome);
+            if opponent == COMPUTER && !game.difficulty.is_empty() {
+                by_difficulty.entry(game.difficulty.clone()).or_insert_with(Record::default).add(outcome);
+            }
+
+            streak = if outcome == Outcome::Win { streak + 1 } else { 0 };
+            stats.longest_win_streak = stats.longest_win_streak.max(streak);
+
+            if !game.moves.is_empty() {
+                total_moves += game.moves.len();
+                games_with_moves += 1;
+            }
+            if let Some(mv) = game.moves.get(first_move) {
+                *openings.entry(mv.column).or_insert(0) += 1;
+            }
+        }
+
+        stats.by_game_type = most_played(by_game_type);
+        stats.by_opponent = most_played(by_opponent);
+        stats.by_difficulty = most_played(by_difficulty);
+        if games_with_moves > 0 {
+            stats.average_moves = total_moves as f64 / games_with_moves as f64;
+        }
+        let mut openings: Vec<(i32, i32)> = openings.into_iter().collect();
+        openings.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        stats.opening_columns = openings;
+        stats
+    }
+}
+
+fn most_played(records: HashMap<String, Record>) -> Vec<(String, Record)> {
+    let mut records: Vec<(String, Record)> = records.into_iter().collect();
+    records.sort_by(|a, b| b.1.played.cmp(&a.1.played).then_with(|| a.0.cmp(&b.0)));
+    records
+}
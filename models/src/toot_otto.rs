@@ -0,0 +1,350 @@
+use std::fmt;
+use serde::{Serialize, Deserialize};
+use crate::engine::Game;
+use crate::game_boards::TootOttoGameBoard;
+use crate::player::Player;
+
+pub use crate::connect4::MoveOutcome;
+
+pub const ROWS: usize = 4;
+pub const COLUMNS: usize = 6;
+/// Discs of each letter every player starts with
+pub const DISCS_PER_LETTER: u8 = 6;
+
+// Character used in the disc map for an empty cell
+const EMPTY: char = '0';
+
+// Directions (row, column) that a word can run in
+const DIRECTIONS: [(isize, isize); 4] = [(0, 1), (1, 0), (1, 1), (-1, 1)];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Letter {
+    T,
+    O
+}
+
+impl Letter {
+    pub fn to_char(self) -> char {
+        match self {
+            Letter::T => 'T',
+            Letter::O => 'O',
+        }
+    }
+
+    pub fn from_char(c: char) -> Option<Letter> {
+        match c {
+            'T' | 't' => Some(Letter::T),
+            'O' | 'o' => Some(Letter::O),
+            _ => None,
+        }
+    }
+
+    pub fn other(self) -> Letter {
+        match self {
+            Letter::T => Letter::O,
+            Letter::O => Letter::T,
+        }
+    }
+
+    fn index(self) -> usize {
+        match self {
+            Letter::T => 0,
+            Letter::O => 1,
+        }
+    }
+}
+
+/// A disc with the given letter dropped into a column
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TootOttoMove {
+    pub column: usize,
+    pub letter: Letter
+}
+
+/// Reason a move was rejected by the rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MoveError {
+    GameOver,
+    InvalidColumn(usize),
+    ColumnFull(usize),
+    OutOfDiscs(Letter)
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::GameOver => write!(f, "The game is already over"),
+            MoveError::InvalidColumn(column) => write!(f, "Column {} is not on the board", column),
+            MoveError::ColumnFull(column) => write!(f, "Column {} is full", column),
+            MoveError::OutOfDiscs(letter) => write!(f, "No {} discs left", letter.to_char()),
+        }
+    }
+}
+
+impl std::error::Error for MoveError {}
+
+/// TOOT-OTTO game state and rules, independent of any UI.
+///
+/// Player one spells TOOT and player two spells OTTO; either player may use
+/// both letters, but only has `DISCS_PER_LETTER` discs of each. A move that
+/// spells both words at once is a draw.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TootOttoState {
+    board: TootOttoGameBoard,
+    moves: Vec<TootOttoMove>,
+    discs: [[u8; 2]; 2],
+    winner: Option<Player>,
+    both_spelled: bool
+}
+
+impl Default for TootOttoState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TootOttoState {
+    pub fn new() -> Self {
+        TootOttoState {
+            board: TootOttoGameBoard {
+                rows: ROWS as u8,
+                columns: COLUMNS as u8,
+                tokens: [[0; COLUMNS]; ROWS],
+                disc_map: [[EMPTY; COLUMNS]; ROWS]
+            },
+            moves: Vec::new(),
+            discs: [[DISCS_PER_LETTER; 2]; 2],
+            winner: None,
+            both_spelled: false
+        }
+    }
+
+    pub fn board(&self) -> &TootOttoGameBoard {
+        &self.board
+    }
+
+    /// Number of discs played so far
+    pub fn move_num(&self) -> u8 {
+        self.moves.len() as u8
+    }
+
+    /// Moves played so far, in order
+    pub fn moves(&self) -> &[TootOttoMove] {
+        &self.moves
+    }
+
+    pub fn current_player(&self) -> Player {
+        if self.moves.len() % 2 == 1 {
+            Player::Two
+        } else {
+            Player::One
+        }
+    }
+
+    /// Discs of `letter` that `player` has not played yet
+    pub fn discs_left(&self, player: Player, letter: Letter) -> u8 {
+        self.discs[player_index(player)][letter.index()]
+    }
+
+    /// Every (column, letter) pair the current player may play, empty once the game is over
+    pub fn legal_moves(&self) -> Vec<TootOttoMove> {
+        if self.is_over() {
+            return Vec::new();
+        }
+        let player = self.current_player();
+        let mut moves = Vec::new();
+        for column in (0..COLUMNS).filter(|&column| self.board.disc_map[0][column] == EMPTY) {
+            for &letter in [Letter::T, Letter::O].iter() {
+                if self.discs_left(player, letter) > 0 {
+                    moves.push(TootOttoMove { column, letter });
+                }
+            }
+        }
+        moves
+    }
+
+    /// Drop a disc of the chosen letter for the current player
+    pub fn play(&mut self, mv: TootOttoMove) -> Result<MoveOutcome, MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        }
+        if mv.column >= COLUMNS {
+            return Err(MoveError::InvalidColumn(mv.column));
+        }
+        let player = self.current_player();
+        if self.discs_left(player, mv.letter) == 0 {
+            return Err(MoveError::OutOfDiscs(mv.letter));
+        }
+        let row = match self.drop_row(mv.column) {
+            Some(row) => row,
+            None => return Err(MoveError::ColumnFull(mv.column)),
+        };
+
+        self.board.tokens[row][mv.column] = player.token();
+        self.board.disc_map[row][mv.column] = mv.letter.to_char();
+        self.discs[player_index(player)][mv.letter.index()] -= 1;
+        self.moves.push(mv);
+
+        let toot = self.spells(row, mv.column, "TOOT");
+        let otto = self.spells(row, mv.column, "OTTO");
+        if toot && otto {
+            self.both_spelled = true;
+            return Ok(MoveOutcome::Draw);
+        } else if toot {
+            self.winner = Some(Player::One);
+            return Ok(MoveOutcome::Win(Player::One));
+        } else if otto {
+            self.winner = Some(Player::Two);
+            return Ok(MoveOutcome::Win(Player::Two));
+        }
+        if self.is_draw() {
+            return Ok(MoveOutcome::Draw);
+        }
+        Ok(MoveOutcome::Continue)
+    }
+
+    /// Take back the last move and return the disc to its owner
+    pub fn undo(&mut self) -> Option<TootOttoMove> {
+        let mv = self.moves.pop()?;
+        if let Some(row) = (0..ROWS).find(|&row| self.board.disc_map[row][mv.column] != EMPTY) {
+            self.board.tokens[row][mv.column] = 0;
+            self.board.disc_map[row][mv.column] = EMPTY;
+        }
+        let player = self.current_player();
+        self.discs[player_index(player)][mv.letter.index()] += 1;
+        self.winner = None;
+        self.both_spelled = false;
+        Some(mv)
+    }
+
+    pub fn winner(&self) -> Option<Player> {
+        self.winner
+    }
+
+    /// Cells (row, column) spelling the winner's word, or `None` if nobody has won
+    pub fn winning_line(&self) -> Option<Vec<(usize, usize)>> {
+        let word: Vec<char> = match self.winner? {
+            Player::One => "TOOT",
+            Player::Two => "OTTO",
+        }.chars().collect();
+        for row in 0..ROWS as isize {
+            for column in 0..COLUMNS as isize {
+                for &(d_row, d_col) in DIRECTIONS.iter() {
+                    let cells: Vec<(isize, isize)> = (0..4).map(|k| (row + k * d_row, column + k * d_col)).collect();
+                    if cells.iter().zip(word.iter()).all(|(&(r, c), &letter)| self.cell(r, c) == Some(letter)) {
+                        return Some(cells.into_iter().map(|(r, c)| (r as usize, c as usize)).collect());
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Both words were spelled by the same move, or the board filled up without either
+    pub fn is_draw(&self) -> bool {
+        self.both_spelled
+            || (self.winner.is_none() && self.moves.len() == ROWS * COLUMNS)
+    }
+
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.is_draw()
+    }
+
+    // Lowest empty row in a column, if there is one
+    fn drop_row(&self, column: usize) -> Option<usize> {
+        (0..ROWS).rev().find(|&row| self.board.disc_map[row][column] == EMPTY)
+    }
+
+    // Whether any four-cell window through (row, column) reads `word` in either direction
+    fn spells(&self, row: usize, column: usize, word: &str) -> bool {
+        let word: Vec<char> = word.chars().collect();
+        let len = word.len() as isize;
+        DIRECTIONS.iter().any(|&(d_row, d_col)| {
+            (0..len).any(|offset| {
+                let start_row = row as isize - offset * d_row;
+                let start_col = column as isize - offset * d_col;
+                (0..len).all(|k| {
+                    match self.cell(start_row + k * d_row, start_col + k * d_col) {
+                        Some(c) => c == word[k as usize],
+                        None => false,
+                    }
+                })
+            })
+        })
+    }
+
+    fn cell(&self, row: isize, column: isize) -> Option<char> {
+        if row < 0 || row >= ROWS as isize || column < 0 || column >= COLUMNS as isize {
+            return None;
+        }
+        Some(self.board.disc_map[row as usize][column as usize])
+    }
+}
+
+impl Game for TootOttoState {
+    type Move = TootOttoMove;
+
+    fn current_player(&self) -> Player {
+        TootOttoState::current_player(self)
+    }
+
+    fn legal_moves(&self) -> Vec<TootOttoMove> {
+        TootOttoState::legal_moves(self)
+    }
+
+    fn apply(&mut self, mv: TootOttoMove) {
+        let result = self.play(mv);
+        debug_assert!(result.is_ok(), "illegal move {:?}", mv);
+    }
+
+    fn undo(&mut self) {
+        TootOttoState::undo(self);
+    }
+
+    fn is_over(&self) -> bool {
+        TootOttoState::is_over(self)
+    }
+
+    fn winner(&self) -> Option<Player> {
+        TootOttoState::winner(self)
+    }
+
+    // Every window that can still become a word scores the square of the
+    // letters already in place, TOOT for player one and OTTO for player two
+    fn evaluate(&self, player: Player) -> i64 {
+        let mut score = 0;
+        for row in 0..ROWS as isize {
+            for column in 0..COLUMNS as isize {
+                for &(d_row, d_col) in DIRECTIONS.iter() {
+                    let window: Option<Vec<char>> = (0..4)
+                        .map(|k| self.cell(row + k * d_row, column + k * d_col))
+                        .collect();
+                    if let Some(window) = window {
+                        score += word_progress(&window, "TOOT") - word_progress(&window, "OTTO");
+                    }
+                }
+            }
+        }
+        score * player.token() as i64
+    }
+}
+
+// Squared count of letters already matching `word`, or 0 once the window is blocked
+fn word_progress(window: &[char], word: &str) -> i64 {
+    let mut matched = 0;
+    for (&cell, letter) in window.iter().zip(word.chars()) {
+        if cell == letter {
+            matched += 1;
+        } else if cell != EMPTY {
+            return 0;
+        }
+    }
+    matched * matched
+}
+
+fn player_index(player: Player) -> usize {
+    match player {
+        Player::One => 0,
+        Player::Two => 1,
+    }
+}
@@ -0,0 +1,60 @@
+use crate::connect4::Connect4Move;
+
+/// How a stored score relates to the true value of the position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    /// The search was cut off, the position is worth at least this much
+    Lower,
+    /// No move reached alpha, the position is worth at most this much
+    Upper
+}
+
+/// What a search learned about one position
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub hash: u64,
+    pub depth: u32,
+    pub score: i64,
+    pub bound: Bound,
+    pub best_move: Option<Connect4Move>
+}
+
+/// Fixed-size table of searched positions, indexed by Zobrist hash.
+///
+/// Every hash has one slot. A new entry takes the slot over unless the slot
+/// already holds the same position searched more deeply.
+pub struct TranspositionTable {
+    entries: Vec<Option<Entry>>,
+    mask: u64
+}
+
+impl TranspositionTable {
+    /// Table with `2^bits` slots
+    pub fn new(bits: u32) -> Self {
+        TranspositionTable {
+            entries: vec![None; 1 << bits],
+            mask: (1 << bits) - 1
+        }
+    }
+
+    pub fn get(&self, hash: u64) -> Option<Entry> {
+        self.entries[(hash & self.mask) as usize].filter(|entry| entry.hash == hash)
+    }
+
+    pub fn store(&mut self, entry: Entry) {
+        let slot = &mut self.entries[(entry.hash & self.mask) as usize];
+        if let Some(old) = slot {
+            if old.hash == entry.hash && old.depth > entry.depth {
+                return;
+            }
+        }
+        *slot = Some(entry);
+    }
+
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+    }
+}
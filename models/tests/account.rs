@@ -0,0 +1,30 @@
+use models::account::{Credentials, Session};
+
+fn credentials(username: &str, password: &str) -> Credentials {
+    Credentials { username: username.into(), password: password.into() }
+}
+
+#[test]
+fn accepts_reasonable_accounts() {
+    assert_eq!(credentials("Ann", "correct horse").validate(), Ok(()));
+    assert_eq!(credentials("Ann Smith", "12345678").validate(), Ok(()));
+}
+
+#[test]
+fn rejects_unusable_accounts() {
+    assert!(credentials("", "correct horse").validate().is_err());
+    assert!(credentials(" Ann", "correct horse").validate().is_err());
+    assert!(credentials(&"a".repeat(33), "correct horse").validate().is_err());
+    assert!(credentials("Ann", "short").validate().is_err());
+    // The games already use these names for the computer and for drawn games
+    assert!(credentials("Computer", "correct horse").validate().is_err());
+    assert!(credentials("draw", "correct horse").validate().is_err());
+}
+
+#[test]
+fn sessions_authorize_with_a_bearer_token() {
+    let session = Session { username: "Ann".into(), token: "abc123".into() };
+    assert_eq!(session.bearer(), "Bearer abc123");
+    let json = serde_json::to_string(&session).unwrap();
+    assert_eq!(serde_json::from_str::<Session>(&json).unwrap(), session);
+}
@@ -0,0 +1,135 @@
+use models::bitboard::Bitboard;
+use models::connect4::{Connect4Move, Connect4State, MoveError, MoveOutcome, SizeError, Variant, COLUMNS, MAX_SIZE, ROWS};
+use models::player::Player;
+
+fn play_all(state: &mut Connect4State, columns: &[usize]) -> MoveOutcome {
+    let mut outcome = MoveOutcome::Continue;
+    for &column in columns {
+        outcome = state.play(column).unwrap();
+    }
+    outcome
+}
+
+#[test]
+fn discs_fall_to_lowest_empty_row() {
+    let mut state = Connect4State::new();
+    play_all(&mut state, &[3, 3]);
+    assert_eq!(state.board().tokens[ROWS - 1][3], 1);
+    assert_eq!(state.board().tokens[ROWS - 2][3], -1);
+    assert_eq!(state.current_player(), Player::One);
+}
+
+#[test]
+fn vertical_win() {
+    let mut state = Connect4State::new();
+    let outcome = play_all(&mut state, &[0, 1, 0, 1, 0, 1, 0]);
+    assert_eq!(outcome, MoveOutcome::Win(Player::One));
+    assert_eq!(state.winner(), Some(Player::One));
+    assert!(state.legal_moves().is_empty());
+    assert_eq!(state.play(2), Err(MoveError::GameOver));
+}
+
+#[test]
+fn diagonal_win_for_second_player() {
+    let mut state = Connect4State::new();
+    let outcome = play_all(&mut state, &[6, 1, 2, 2, 3, 3, 4, 3, 4, 4, 6]);
+    assert_eq!(outcome, MoveOutcome::Continue);
+    let outcome = play_all(&mut state, &[4]);
+    assert_eq!(outcome, MoveOutcome::Win(Player::Two));
+}
+
+#[test]
+fn full_column_and_bad_column_are_rejected() {
+    let mut state = Connect4State::new();
+    play_all(&mut state, &[0, 0, 0, 0, 0, 0]);
+    assert_eq!(state.play(0), Err(MoveError::ColumnFull(0)));
+    assert_eq!(state.play(COLUMNS), Err(MoveError::InvalidColumn(COLUMNS)));
+    assert!(!state.legal_moves().contains(&Connect4Move::Drop(0)));
+}
+
+#[test]
+fn full_board_without_line_is_a_draw() {
+    let mut state = Connect4State::new();
+    // Fill column pairs in a pattern that never lines up four
+    let order = [0, 1, 0, 1, 0, 1, 1, 0, 1, 0, 1, 0,
+                 2, 3, 2, 3, 2, 3, 3, 2, 3, 2, 3, 2,
+                 4, 5, 4, 5, 4, 5, 5, 4, 5, 4, 5, 4,
+                 6, 6, 6, 6, 6, 6];
+    let outcome = play_all(&mut state, &order);
+    assert_eq!(outcome, MoveOutcome::Draw);
+    assert!(state.is_draw());
+    assert_eq!(state.winner(), None);
+}
+
+#[test]
+fn larger_boards_use_their_own_size() {
+    let mut state = Connect4State::with_size(9, 7, 5).unwrap();
+    assert_eq!(state.legal_moves().len(), 9);
+    play_all(&mut state, &[8, 8]);
+    assert_eq!(state.board().tokens[6][8], 1);
+    // Four in a row is not enough when connecting five
+    let outcome = play_all(&mut state, &[0, 0, 1, 1, 2, 2, 3, 3]);
+    assert_eq!(outcome, MoveOutcome::Continue);
+    assert_eq!(play_all(&mut state, &[4]), MoveOutcome::Win(Player::One));
+}
+
+#[test]
+fn board_sizes_are_validated() {
+    assert_eq!(Connect4State::with_size(3, 6, 3), Err(SizeError::Columns(3)));
+    assert_eq!(Connect4State::with_size(7, 11, 4), Err(SizeError::Rows(11)));
+    assert_eq!(Connect4State::with_size(7, 6, 8), Err(SizeError::Connect(8)));
+    assert!(Connect4State::with_size(8, 7, 4).is_ok());
+}
+
+#[test]
+fn pop_out_shifts_the_column_down() {
+    let mut state = Connect4State::new().with_variant(Variant::PopOut);
+    assert_eq!(state.pop(3), Err(MoveError::CannotPop(3)));
+    play_all(&mut state, &[3, 3]);
+    let before = state.clone();
+    assert!(state.legal_moves().contains(&Connect4Move::Pop(3)));
+    assert_eq!(state.pop(3), Ok(MoveOutcome::Continue));
+    assert_eq!(state.board().tokens[ROWS - 1][3], -1);
+    assert_eq!(state.board().tokens[ROWS - 2][3], 0);
+    assert_eq!(state.undo(), Some(Connect4Move::Pop(3)));
+    assert_eq!(state, before);
+}
+
+#[test]
+fn standard_game_does_not_allow_pops() {
+    let mut state = Connect4State::new();
+    play_all(&mut state, &[3, 4]);
+    assert_eq!(state.pop(3), Err(MoveError::CannotPop(3)));
+    assert!(state.legal_moves().iter().all(|mv| matches!(mv, Connect4Move::Drop(_))));
+}
+
+#[test]
+fn pop_connecting_both_players_wins_for_the_popper() {
+    let mut state = Connect4State::new().with_variant(Variant::PopOut);
+    play_all(&mut state, &[3, 0, 0, 1, 1, 2, 2, 3, 3, 6]);
+    assert_eq!(state.pop(0), Err(MoveError::CannotPop(0)));
+    // Player two completes the bottom row and player one the row above it
+    assert_eq!(state.pop(3), Ok(MoveOutcome::Win(Player::One)));
+    assert_eq!(state.winner(), Some(Player::One));
+}
+
+#[test]
+fn pop_out_games_are_drawn_at_the_move_limit() {
+    assert_eq!(Connect4State::new().move_limit(), None);
+    let mut state = Connect4State::with_size(MAX_SIZE, MAX_SIZE, 4).unwrap().with_variant(Variant::PopOut);
+    let limit = state.move_limit().unwrap();
+    assert_eq!(limit, 300);
+
+    // Both players drop a disc and pop it out again, back to the empty board
+    let cycle = [Connect4Move::Drop(0), Connect4Move::Drop(1), Connect4Move::Pop(0), Connect4Move::Pop(1)];
+    let mut outcome = MoveOutcome::Continue;
+    for &mv in cycle.iter().cycle().take(limit) {
+        assert_eq!(outcome, MoveOutcome::Continue);
+        outcome = state.play_move(mv).unwrap();
+    }
+    assert_eq!(outcome, MoveOutcome::Draw);
+    assert_eq!(state.move_num(), limit);
+    assert!(state.is_draw() && state.legal_moves().is_empty());
+    assert_eq!(state.play(0), Err(MoveError::GameOver));
+    assert!(!Bitboard::from_state(&state).has_moves());
+}
@@ -0,0 +1,88 @@
+use models::bitboard::Bitboard;
+use models::connect4::{Connect4Move, Connect4State};
+use models::connect4_eval::{Evaluator, IMMEDIATE_WIN};
+use models::search;
+use models::solver::{self, Value};
+
+fn connect4(columns: &[usize]) -> Connect4State {
+    let mut state = Connect4State::new();
+    for &column in columns {
+        state.play(column).unwrap();
+    }
+    state
+}
+
+fn evaluate(state: &Connect4State) -> i64 {
+    let board = Bitboard::from_state(state);
+    Evaluator::new(&board).evaluate(&board)
+}
+
+// Every move the search would pick keeps the solved result of the position
+fn search_keeps_value(columns: &[usize], depth: u32, value: Value) {
+    let state = connect4(columns);
+    assert_eq!(solver::solve(&state), Some(value));
+    let (_, moves) = search::best_moves(&state, depth);
+    for mv in moves {
+        let mut child = state.clone();
+        child.play_move(mv).unwrap();
+        let after = solver::solve(&child).unwrap();
+        let kept = match value {
+            Value::Win(_) => matches!(after, Value::Loss(_)),
+            Value::Draw => !matches!(after, Value::Win(_)),
+            Value::Loss(_) => true,
+        };
+        assert!(kept, "{:?} turns {:?} into {:?} for the opponent", mv, value, after);
+    }
+}
+
+#[test]
+fn blocked_windows_score_nothing() {
+    // Player two's disc on top spoils the column for both players alike
+    let mixed = evaluate(&connect4(&[0, 0]));
+    let open = evaluate(&connect4(&[0, 6]));
+    assert!(mixed < open);
+}
+
+#[test]
+fn playable_threats_decide_the_position() {
+    // Player one to move can complete the bottom row
+    assert_eq!(evaluate(&connect4(&[0, 6, 1, 6, 2, 5])), IMMEDIATE_WIN);
+    // Player two to move cannot block both ends of the bottom row
+    assert_eq!(evaluate(&connect4(&[2, 2, 3, 3, 4])), -IMMEDIATE_WIN);
+}
+
+#[test]
+fn threats_on_the_players_own_rows_count_for_more() {
+    // Player one threatens the fourth cell of row 3 (odd) in one game and of
+    // row 2 (even) in the other, with column 3 still empty below it
+    let odd = evaluate(&connect4(&[0, 2, 1, 0, 2, 1, 0, 6, 1, 6, 2, 5]));
+    let even = evaluate(&connect4(&[2, 0, 0, 1, 1, 6, 2, 5]));
+    assert!(odd > even, "{} <= {}", odd, even);
+}
+
+#[test]
+fn center_discs_count_for_more() {
+    let center = connect4(&[3, 0]);
+    let edge = connect4(&[0, 3]);
+    // Player one to move in both, holding the center in the first
+    assert!(evaluate(&center) > evaluate(&edge));
+    assert_eq!(search::best_moves(&Connect4State::new(), 2).1, vec![Connect4Move::Drop(3)]);
+}
+
+// Positions where searching with the old cubed window sums threw away the
+// result perfect play would keep
+
+#[test]
+fn keeps_the_draw_instead_of_dropping_into_column_four() {
+    search_keeps_value(&[1, 0, 2, 4, 0, 4, 3, 1, 6, 6, 5, 0, 3, 2], 4, Value::Draw);
+}
+
+#[test]
+fn keeps_the_win_at_easy_depth() {
+    search_keeps_value(&[2, 4, 1, 4, 5, 1, 4, 0, 1, 2, 6, 3, 4, 1, 3, 5, 0, 3], 2, Value::Win(21));
+}
+
+#[test]
+fn keeps_the_draw_at_depth_five() {
+    search_keeps_value(&[0, 3, 1, 3, 4, 6, 6, 3, 0, 5, 4, 2, 5, 0, 3, 1], 5, Value::Draw);
+}
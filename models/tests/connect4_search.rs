@@ -0,0 +1,129 @@
+use std::cell::Cell;
+use std::time::Instant;
+use models::bitboard::Bitboard;
+use models::connect4::{Connect4Move, Connect4State, Variant};
+use models::connect4_search::{self, Limits, MAX_DEPTH};
+use models::player::Player;
+use models::search::WIN_SCORE;
+
+fn connect4(columns: &[usize]) -> Connect4State {
+    let mut state = Connect4State::new();
+    for &column in columns {
+        state.play(column).unwrap();
+    }
+    state
+}
+
+// Search with a budget generous enough that it never runs out
+fn search(state: &Connect4State, max_depth: u32) -> connect4_search::SearchResult {
+    let start = Instant::now();
+    let now = move || start.elapsed().as_secs_f64() * 1000.0;
+    connect4_search::best_move(state, Limits { time_budget: 60_000.0, max_depth }, &now).unwrap()
+}
+
+#[test]
+fn bitboard_detects_the_same_wins_as_the_state() {
+    let mut state = Connect4State::new();
+    let mut board = Bitboard::from_state(&state);
+    // Player two connects on a diagonal with the last disc
+    for &column in [6, 1, 2, 2, 3, 3, 4, 3, 4, 4, 6, 4].iter() {
+        let winner = board.play(Connect4Move::Drop(column));
+        state.play(column).unwrap();
+        assert_eq!(winner, state.winner());
+        assert_eq!(board, Bitboard::from_state(&state));
+    }
+    assert_eq!(state.winner(), Some(Player::Two));
+}
+
+#[test]
+fn bitboard_pops_shift_the_column() {
+    let mut state = Connect4State::new().with_variant(Variant::PopOut);
+    for &column in [3, 0, 0, 1, 1, 2, 2, 3, 3, 6].iter() {
+        state.play(column).unwrap();
+    }
+    let mut board = Bitboard::from_state(&state);
+    assert!(board.can_pop(3));
+    assert!(!board.can_pop(0));
+    assert_eq!(board.play(Connect4Move::Pop(3)), Some(Player::One));
+    state.pop(3).unwrap();
+    assert_eq!(board, Bitboard::from_state(&state));
+}
+
+#[test]
+fn deep_search_takes_the_immediate_win() {
+    let result = search(&connect4(&[0, 1, 0, 1, 0, 1]), 8);
+    assert_eq!(result.best_move, Connect4Move::Drop(0));
+    assert_eq!(result.score, WIN_SCORE - 1);
+    // No need to look further once the win is found
+    assert_eq!(result.depth, 1);
+}
+
+#[test]
+fn deep_search_blocks_the_only_threat() {
+    // Player one needs column 3 to complete the bottom row
+    let result = search(&connect4(&[0, 6, 1, 6, 2]), 8);
+    assert_eq!(result.best_move, Connect4Move::Drop(3));
+}
+
+#[test]
+fn deep_search_sees_a_forced_win() {
+    // Player one can set up two threats at once by playing the bottom row
+    let result = search(&connect4(&[3, 3, 2, 2]), 10);
+    assert!(result.best_move == Connect4Move::Drop(1) || result.best_move == Connect4Move::Drop(4));
+    assert!(result.score > WIN_SCORE - 10);
+}
+
+#[test]
+fn search_stops_when_the_time_is_up() {
+    // A clock that is already at the deadline still yields a move from the first iterations
+    let result = connect4_search::best_move(&Connect4State::new(), Limits::time(0.0), &|| 1.0).unwrap();
+    assert!(Connect4State::new().legal_moves().contains(&result.best_move));
+    assert!(result.nodes < 10_000);
+}
+
+#[test]
+fn search_deepens_until_the_budget_is_spent() {
+    // A clock that advances one millisecond per reading
+    let ticks = Cell::new(0.0);
+    let now = || {
+        ticks.set(ticks.get() + 1.0);
+        ticks.get()
+    };
+    let result = connect4_search::best_move(&Connect4State::new(), Limits::time(50.0), &now).unwrap();
+    assert!(result.depth > 4);
+    assert!(result.depth < MAX_DEPTH);
+    assert!(ticks.get() <= 52.0);
+}
+
+#[test]
+fn small_boards_are_searched_to_the_end() {
+    // Every line on a 4 x 4 board can be blocked, so perfect play is a draw
+    let state = Connect4State::with_size(4, 4, 4).unwrap();
+    let result = search(&state, MAX_DEPTH);
+    assert_eq!(result.depth, 16);
+    assert!(result.score.abs() < WIN_SCORE - 1000);
+}
+
+#[test]
+fn bitboard_hash_follows_the_position() {
+    let mut state = Connect4State::new().with_variant(Variant::PopOut);
+    let mut board = Bitboard::from_state(&state);
+    for &mv in [Connect4Move::Drop(3), Connect4Move::Drop(3), Connect4Move::Pop(3), Connect4Move::Drop(4)].iter() {
+        board.play(mv);
+        state.play_move(mv).unwrap();
+        assert_eq!(board.hash(), Bitboard::from_state(&state).hash());
+    }
+    // The same position reached by other moves hashes the same
+    let mut other = Bitboard::from_state(&Connect4State::new().with_variant(Variant::PopOut));
+    for &mv in [Connect4Move::Drop(0), Connect4Move::Drop(3), Connect4Move::Pop(0), Connect4Move::Drop(4)].iter() {
+        other.play(mv);
+    }
+    assert_eq!(board, other);
+    assert_eq!(board.hash(), other.hash());
+}
+
+#[test]
+fn finished_games_have_no_move() {
+    let state = connect4(&[0, 1, 0, 1, 0, 1, 0]);
+    assert_eq!(connect4_search::best_move(&state, Limits::time(60_000.0), &|| 0.0), None);
+}
@@ -0,0 +1,56 @@
+use bson::{doc, Bson};
+use models::connect4::Connect4Move;
+use models::game::{Game, GameMove};
+use models::toot_otto::{Letter, TootOttoMove};
+
+#[test]
+fn older_documents_still_load() {
+    let document = doc! {
+        "game_number": 3,
+        "game_type": "Connect4",
+        "player1_name": "Ann",
+        "player2_name": "Computer",
+        "winner_name": "Ann",
+        "game_date": 1_585_000_000_000_i64
+    };
+    let game: Game = bson::from_bson(Bson::Document(document)).unwrap();
+    assert_eq!(game.player1_name, "Ann");
+    assert!(game.moves.is_empty());
+    assert_eq!((game.columns, game.rows, game.connect), (0, 0, 0));
+    assert_eq!(game.difficulty, "");
+}
+
+#[test]
+fn moves_and_settings_survive_a_round_trip() {
+    let game = Game {
+        game_number: 1,
+        game_type: "Connect4-PopOut".into(),
+        player1_name: "Ann".into(),
+        player2_name: "Computer".into(),
+        winner_name: "Computer".into(),
+        game_date: 1_585_000_000_000,
+        moves: vec![Connect4Move::Drop(3).into(), Connect4Move::Pop(3).into()],
+        columns: 8,
+        rows: 7,
+        connect: 5,
+        difficulty: "hard".into(),
+        player1_color: "#ff4136".into(),
+        player2_color: "#ffff00".into()
+    };
+    let stored = bson::to_bson(&game).unwrap();
+    assert_eq!(bson::from_bson::<Game>(stored).unwrap(), game);
+}
+
+#[test]
+fn moves_record_letters_and_pops() {
+    assert_eq!(GameMove::from(Connect4Move::Drop(2)), GameMove { column: 2, letter: None, pop: false });
+    assert_eq!(GameMove::from(Connect4Move::Pop(5)), GameMove { column: 5, letter: None, pop: true });
+    let toot = TootOttoMove { column: 1, letter: Letter::O };
+    assert_eq!(GameMove::from(toot), GameMove { column: 1, letter: Some('O'), pop: false });
+}
+
+#[test]
+fn unused_move_fields_are_left_out() {
+    let stored = bson::to_bson(&GameMove::from(Connect4Move::Drop(2))).unwrap();
+    assert_eq!(stored, Bson::Document(doc! { "column": 2 }));
+}
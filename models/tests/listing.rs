@@ -0,0 +1,102 @@
+use bson::{doc, Bson};
+use models::game::Game;
+use models::listing::{GamePage, GameQuery, SortOrder, DEFAULT_PAGE_SIZE, MAX_PAGE_SIZE};
+
+#[test]
+fn pages_default_and_are_capped() {
+    let query = GameQuery::default();
+    assert_eq!((query.page(), query.page_size(), query.skip()), (1, DEFAULT_PAGE_SIZE, 0));
+
+    let query = GameQuery { page: Some(3), page_size: Some(1000), ..GameQuery::default() };
+    assert_eq!(query.page_size(), MAX_PAGE_SIZE);
+    assert_eq!(query.skip(), 2 * MAX_PAGE_SIZE);
+
+    // Page size 0 lists every game, and pages before the first mean the first
+    let query = GameQuery { page: Some(-2), page_size: Some(0), ..GameQuery::default() };
+    assert_eq!((query.page(), query.page_size(), query.skip()), (1, 0, 0));
+
+    let query = GameQuery { page: Some(i64::MAX), page_size: Some(MAX_PAGE_SIZE), ..GameQuery::default() };
+    assert_eq!(query.skip(), i64::MAX);
+}
+
+#[test]
+fn filters_become_a_mongo_query() {
+    assert_eq!(GameQuery::default().filter(), doc! {});
+
+    let query = GameQuery {
+        game_type: Some("TOOT-OTTO".into()),
+        player: Some("Ann".into()),
+        from: Some(1000),
+        ..GameQuery::default()
+    };
+    let players = vec![Bson::Document(doc! { "player1_name": "Ann" }), Bson::Document(doc! { "player2_name": "Ann" })];
+    assert_eq!(query.filter(), doc! {
+        "game_type": "TOOT-OTTO",
+        "$or": players,
+        "game_date": { "$gte": 1000i64 }
+    });
+}
+
+#[test]
+fn sort_orders_round_trip_through_their_names() {
+    for &order in SortOrder::ALL.iter() {
+        assert_eq!(SortOrder::from_name(order.name()), Some(order));
+    }
+    assert_eq!(SortOrder::from_name("sideways"), None);
+    let oldest = GameQuery { sort: Some(SortOrder::Oldest), ..GameQuery::default() };
+    assert_eq!(oldest.sort_document(), doc! { "game_date": 1, "game_number": 1 });
+}
+
+#[test]
+fn queries_are_written_as_url_parameters() {
+    let query = GameQuery {
+        page: Some(2),
+        player: Some("Ann & Bob".into()),
+        sort: Some(SortOrder::Winner),
+        ..GameQuery::default()
+    };
+    assert_eq!(query.to_query_string(), "page=2&player=Ann%20%26%20Bob&sort=winner");
+    assert_eq!(GameQuery::default().to_query_string(), "");
+}
+
+#[test]
+fn counts_pages() {
+    let page = |total, page_size| GamePage { total, page_size, ..GamePage::default() };
+    assert_eq!(page(0, 20).page_count(), 1);
+    assert_eq!(page(20, 20).page_count(), 1);
+    assert_eq!(page(21, 20).page_count(), 2);
+    assert_eq!(page(21, 0).page_count(), 1);
+}
+
+#[test]
+fn queries_filter_and_sort_games_in_memory() {
+    let game = |date, game_type: &str, winner: &str| Game {
+        game_type: game_type.into(),
+        player1_name: "Ann".into(),
+        player2_name: "Bob".into(),
+        winner_name: winner.into(),
+        game_date: date,
+        ..Game::default()
+    };
+    let games = vec![game(1, "Connect4", "Ann"), game(2, "TOOT-OTTO", "Bob"), game(3, "Connect4", "Bob")];
+
+    let query = GameQuery { player: Some("Bob".into()), winner: Some("Bob".into()), to: Some(2), ..GameQuery::default() };
+    let found: Vec<i64> = games.iter().filter(|game| query.matches(game)).map(|game| game.game_date).collect();
+    assert_eq!(found, vec![2]);
+
+    let mut sorted = games.clone();
+    let by_type = GameQuery { sort: Some(SortOrder::GameType), ..GameQuery::default() };
+    sorted.sort_by(|a, b| by_type.compare(a, b));
+    assert_eq!(sorted.iter().map(|game| game.game_date).collect::<Vec<_>>(), vec![3, 1, 2]);
+    sorted.sort_by(|a, b| GameQuery::default().compare(a, b));
+    assert_eq!(sorted.iter().map(|game| game.game_date).collect::<Vec<_>>(), vec![3, 2, 1]);
+
+    // Games saved at the same time are ordered by number
+    let same_time: Vec<Game> = (1..=3).map(|game_number| Game { game_number, ..game(1, "Connect4", "Ann") }).collect();
+    let mut sorted = same_time.clone();
+    sorted.sort_by(|a, b| GameQuery::default().compare(a, b));
+    assert_eq!(sorted.iter().map(|game| game.game_number).collect::<Vec<_>>(), vec![3, 2, 1]);
+    let oldest = GameQuery { sort: Some(SortOrder::Oldest), ..GameQuery::default() };
+    sorted.sort_by(|a, b| oldest.compare(a, b));
+    assert_eq!(sorted.iter().map(|game| game.game_number).collect::<Vec<_>>(), vec![1, 2, 3]);
+}
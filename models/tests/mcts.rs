@@ -0,0 +1,56 @@
+use models::connect4::{Connect4Move, Connect4State};
+use models::mcts;
+use models::toot_otto::{Letter, TootOttoMove, TootOttoState};
+
+fn mv(column: usize, letter: char) -> TootOttoMove {
+    TootOttoMove { column, letter: Letter::from_char(letter).unwrap() }
+}
+
+fn toot_otto(moves: &[(usize, char)]) -> TootOttoState {
+    let mut state = TootOttoState::new();
+    for &(column, letter) in moves {
+        state.play(mv(column, letter)).unwrap();
+    }
+    state
+}
+
+#[test]
+fn completes_toot() {
+    // Player one has T O O along the bottom row
+    let state = toot_otto(&[(0, 'T'), (0, 'T'), (1, 'O'), (0, 'T'), (2, 'O'), (1, 'T')]);
+    let (choice, value) = mcts::best_move(&state, 2000, 1).unwrap();
+    assert_eq!(choice, mv(3, 'T'));
+    assert!(value > 0.9, "{}", value);
+}
+
+#[test]
+fn spoils_the_opponents_otto() {
+    // The bottom row reads O T T, and an O in column 3 would spell OTTO for player two
+    let state = toot_otto(&[(0, 'O'), (5, 'O'), (1, 'T'), (5, 'O'), (2, 'T'), (4, 'T')]);
+    let (choice, _) = mcts::best_move(&state, 4000, 7).unwrap();
+    assert_eq!(choice, mv(3, 'T'));
+}
+
+#[test]
+fn same_seed_same_move() {
+    let state = toot_otto(&[(2, 'T'), (3, 'O')]);
+    let first = mcts::best_move(&state, 500, 42);
+    assert_eq!(mcts::best_move(&state, 500, 42), first);
+}
+
+#[test]
+fn works_for_connect4() {
+    let mut state = Connect4State::new();
+    for &column in [0, 6, 1, 6, 2, 6].iter() {
+        state.play(column).unwrap();
+    }
+    let (choice, _) = mcts::best_move(&state, 1000, 3).unwrap();
+    assert_eq!(choice, Connect4Move::Drop(3));
+}
+
+#[test]
+fn nothing_to_play_once_the_game_is_over() {
+    let state = toot_otto(&[(0, 'T'), (1, 'O'), (5, 'O'), (3, 'T'), (2, 'O')]);
+    assert!(state.is_over());
+    assert_eq!(mcts::best_move(&state, 100, 1), None);
+}
@@ -0,0 +1,118 @@
+use models::game::{Game, GameMove};
+use models::notation::{from_notation, to_notation, NotationError};
+use models::replay::ReplayError;
+
+fn drops(columns: &[i32]) -> Vec<GameMove> {
+    columns.iter().map(|&column| GameMove { column, letter: None, pop: false }).collect()
+}
+
+fn connect4_game() -> Game {
+    Game {
+        game_type: "Connect4".into(),
+        player1_name: "Ann".into(),
+        player2_name: "Computer".into(),
+        winner_name: "Ann".into(),
+        game_date: 1_585_000_000_000,
+        moves: drops(&[3, 3, 4, 2, 0, 1, 2, 5, 5]),
+        columns: 7,
+        rows: 6,
+        connect: 4,
+        difficulty: "hard".into(),
+        ..Game::default()
+    }
+}
+
+#[test]
+fn writes_headers_and_a_column_string() {
+    let text = to_notation(&Game { winner_name: String::new(), ..connect4_game() });
+    assert_eq!(text, "[Game \"Connect4\"]\n\
+        [Player1 \"Ann\"]\n\
+        [Player2 \"Computer\"]\n\
+        [Date \"2020.03.23 21:46:40\"]\n\
+        [Size \"7x6\"]\n\
+        [Connect \"4\"]\n\
+        [Difficulty \"hard\"]\n\
+        [Result \"*\"]\n\
+        \n\
+        445312366\n");
+}
+
+#[test]
+fn games_survive_a_round_trip() {
+    let game = Game { winner_name: String::new(), ..connect4_game() };
+    assert_eq!(from_notation(&to_notation(&game)), Ok(game));
+
+    let popout = Game {
+        game_type: "Connect4-PopOut".into(),
+        player1_name: "Say \"hi\"".into(),
+        player2_name: "Bob".into(),
+        moves: vec![GameMove { column: 0, letter: None, pop: false }, GameMove { column: 1, letter: None, pop: false },
+            GameMove { column: 0, letter: None, pop: true }],
+        ..Game::default()
+    };
+    let text = to_notation(&popout);
+    assert!(text.ends_with("\n12p1\n"));
+    assert_eq!(from_notation(&text), Ok(popout));
+
+    let wide = Game { game_type: "Connect4".into(), moves: drops(&[9, 0, 9]), columns: 10, rows: 8, connect: 5, ..Game::default() };
+    let text = to_notation(&wide);
+    assert!(text.ends_with("\n10 1 10\n"));
+    assert_eq!(from_notation(&text), Ok(wide));
+}
+
+#[test]
+fn reads_toot_otto_moves_and_results() {
+    let text = "[Game \"TOOT-OTTO\"]\n[Player1 \"Ann\"]\n[Player2 \"Bob\"]\n[Result \"1-0\"]\n\n1T 2O 6O 4t\n3O\n";
+    let game = from_notation(text).unwrap();
+    assert_eq!(game.winner_name, "Ann");
+    assert_eq!(game.moves.len(), 5);
+    assert_eq!(game.moves[3], GameMove { column: 3, letter: Some('T'), pop: false });
+    assert!(to_notation(&game).ends_with("\n1T 2O 6O 4T 3O\n"));
+}
+
+#[test]
+fn standard_games_need_only_a_type() {
+    let game = from_notation("[Game \"Connect4\"]\n4453").unwrap();
+    assert_eq!(game.moves, drops(&[3, 3, 4, 2]));
+    assert_eq!(game.winner_name, "");
+    assert_eq!(game.game_date, 0);
+}
+
+#[test]
+fn reports_what_it_cannot_read() {
+    assert_eq!(from_notation("4453"), Err(NotationError::MissingGameType));
+    assert_eq!(from_notation("[Game Connect4]\n"), Err(NotationError::BadHeader("[Game Connect4]".into())));
+    assert_eq!(from_notation("[Game \"Connect4\"]\n44x3"), Err(NotationError::BadMove("44x3".into())));
+    assert_eq!(from_notation("[Game \"Connect4\"]\n[Result \"2-0\"]\n"), Err(NotationError::BadResult("2-0".into())));
+    assert_eq!(from_notation("[Game \"Connect4\"]\n[Date \"yesterday\"]\n"), Err(NotationError::BadDate("yesterday".into())));
+    assert_eq!(from_notation("[Game \"TOOT-OTTO\"]\n3X"), Err(NotationError::BadMove("3X".into())));
+    assert!(matches!(from_notation("[Game \"Connect4\"]\n1111111"),
+        Err(NotationError::Illegal(ReplayError::IllegalMove { index: 6, .. }))));
+}
+
+#[test]
+fn dates_must_be_in_range() {
+    for date in ["99999999999999999.01.01", "2020.01.01 99:99:99", "2020.01.01 24:00:00", "2020.01.01 12:60:00", "2020.01.01 12:00:60"].iter() {
+        let notation = format!("[Game \"Connect4\"]\n[Date \"{}\"]\n", date);
+        assert_eq!(from_notation(&notation), Err(NotationError::BadDate(date.to_string())));
+    }
+    let game = from_notation("[Game \"Connect4\"]\n[Date \"9999.12.31 23:59:59\"]\n").unwrap();
+    assert_eq!(game.game_date, 253_402_300_799_000);
+}
+
+#[test]
+fn the_result_must_match_the_moves() {
+    // Player one wins down the first column
+    let won = "[Game \"Connect4\"]\n[Result \"1-0\"]\n1212121\n";
+    assert_eq!(from_notation(won).map(|game| game.moves.len()), Ok(7));
+
+    let tampered = won.replace("1-0", "0-1");
+    assert_eq!(from_notation(&tampered),
+        Err(NotationError::WrongResult { claimed: "0-1".into(), played: "1-0".into() }));
+    let unfinished = won.replace("1-0", "*");
+    assert!(matches!(from_notation(&unfinished), Err(NotationError::WrongResult { .. })));
+    // Nor can a game in progress claim a winner
+    let claimed = "[Game \"Connect4\"]\n[Result \"1-0\"]\n121\n";
+    assert_eq!(from_notation(claimed),
+        Err(NotationError::WrongResult { claimed: "1-0".into(), played: "*".into() }));
+}
@@ -0,0 +1,29 @@
+use models::game::{Game, GameMove};
+use models::online::{ClientMessage, ServerMessage};
+use models::player::Player;
+use models::replay::Position;
+
+#[test]
+fn messages_are_plain_json() {
+    let request: ClientMessage = serde_json::from_str(r#"{"Join":{"code":"ABCD","name":"Bob"}}"#).unwrap();
+    assert_eq!(request, ClientMessage::Join { code: "ABCD".into(), name: "Bob".into(), token: None });
+
+    let moved = ServerMessage::Moved(GameMove { column: 3, letter: Some('T'), pop: false });
+    assert_eq!(serde_json::to_string(&moved).unwrap(), r#"{"Moved":{"column":3,"letter":"T"}}"#);
+
+    let started = ServerMessage::Started { game: Game::default(), you: Player::Two };
+    let text = serde_json::to_string(&started).unwrap();
+    assert_eq!(serde_json::from_str::<ServerMessage>(&text).unwrap(), started);
+}
+
+#[test]
+fn positions_check_moves_one_at_a_time() {
+    let game = Game { game_type: "Connect4-PopOut".into(), columns: 5, rows: 4, connect: 3, ..Game::default() };
+    let mut position = Position::new(&game).unwrap();
+    assert_eq!(position.current_player(), Player::One);
+    position.play(GameMove { column: 0, letter: None, pop: false }).unwrap();
+    assert_eq!(position.current_player(), Player::Two);
+    assert!(position.play(GameMove { column: 0, letter: None, pop: true }).is_err());
+    assert!(position.play(GameMove { column: 5, letter: None, pop: false }).is_err());
+    assert!(position.play(GameMove { column: -1, letter: None, pop: false }).is_err());
+}
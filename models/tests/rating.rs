@@ -0,0 +1,88 @@
+use models::game::Game;
+use models::difficulty::{self, DIFFICULTIES};
+use models::rating::{self, Rating, INITIAL_RATING};
+
+fn game(player1: &str, player2: &str, winner: &str) -> Game {
+    Game {
+        game_type: "Connect4".into(),
+        player1_name: player1.into(),
+        player2_name: player2.into(),
+        winner_name: winner.into(),
+        ..Game::default()
+    }
+}
+
+#[test]
+fn equal_players_swap_half_the_k_factor() {
+    let mut ann = Rating::new("Ann", "Connect4");
+    let mut bob = Rating::new("Bob", "Connect4");
+    rating::rate_game(&game("Ann", "Bob", "Ann"), &mut ann, &mut bob);
+    assert_eq!(ann.rating, INITIAL_RATING + 16.0);
+    assert_eq!(bob.rating, INITIAL_RATING - 16.0);
+    assert_eq!((ann.wins, ann.losses, bob.wins, bob.losses), (1, 0, 0, 1));
+    assert_eq!(ann.history, vec![INITIAL_RATING + 16.0]);
+
+    // A draw between them pulls the ratings back together
+    rating::rate_game(&game("Ann", "Bob", "Draw"), &mut ann, &mut bob);
+    assert!(ann.rating < INITIAL_RATING + 16.0 && bob.rating > INITIAL_RATING - 16.0);
+    assert_eq!((ann.draws, bob.draws), (1, 1));
+}
+
+#[test]
+fn computer_ratings_are_anchored() {
+    let hard = Game { difficulty: "hard".into(), ..game("Ann", "Computer", "Ann") };
+    let (player1, player2) = rating::rated_players(&hard).unwrap();
+    assert_eq!(player2, "Computer (hard)");
+
+    let mut ann = Rating::new(&player1, "Connect4");
+    let mut computer = Rating::new(&player2, "Connect4");
+    assert!(computer.anchored);
+    assert_eq!(computer.rating, 1800.0);
+    rating::rate_game(&hard, &mut ann, &mut computer);
+    assert_eq!(computer.rating, 1800.0);
+    assert_eq!(computer.losses, 1);
+    // Beating a much stronger opponent is worth nearly the whole K factor
+    assert!(ann.rating > INITIAL_RATING + 27.0);
+}
+
+#[test]
+fn only_finished_games_are_rated() {
+    assert_eq!(rating::rated_players(&game("Ann", "Bob", "")), None);
+    assert_eq!(rating::rated_players(&game("Ann", "Computer", "Ann")), None);
+    assert_eq!(rating::rated_players(&game("Ann", "Bob", "Bob")), Some(("Ann".into(), "Bob".into())));
+}
+
+#[test]
+fn history_keeps_the_latest_ratings() {
+    let mut ann = Rating::new("Ann", "TOOT-OTTO");
+    let mut bob = Rating::new("Bob", "TOOT-OTTO");
+    for _ in 0..rating::HISTORY_LENGTH + 5 {
+        rating::rate_game(&game("Ann", "Bob", "Ann"), &mut ann, &mut bob);
+    }
+    assert_eq!(ann.history.len(), rating::HISTORY_LENGTH);
+    assert_eq!(ann.history.last(), Some(&ann.rating));
+    assert_eq!(ann.games() as usize, rating::HISTORY_LENGTH + 5);
+}
+
+#[test]
+fn every_difficulty_offered_is_rated() {
+    let expert = Game { difficulty: "expert".into(), ..game("Ann", "Computer", "Computer") };
+    let (player1, player2) = rating::rated_players(&expert).unwrap();
+    let mut ann = Rating::new(&player1, "Connect4");
+    let mut computer = Rating::new(&player2, "Connect4");
+    assert_eq!(computer.rating, 2200.0);
+    rating::rate_game(&expert, &mut ann, &mut computer);
+    assert_eq!((ann.losses, computer.wins), (1, 1));
+
+    for level in DIFFICULTIES.iter() {
+        let against = Game { difficulty: level.name.into(), ..game("Ann", "Computer", "Ann") };
+        assert!(rating::rated_players(&against).is_some(), "{} games are not rated", level.name);
+    }
+    assert_eq!(difficulty::offered("TOOT-OTTO").count(), 3);
+    assert_eq!(difficulty::offered("Connect4").last().map(|level| level.name), Some("expert"));
+
+    // PopOut has no solver, so no expert to be rated against
+    assert_eq!(difficulty::offered("Connect4-PopOut").count(), 3);
+    let pop_out = Game { game_type: "Connect4-PopOut".into(), ..expert };
+    assert_eq!(rating::rated_players(&pop_out), None);
+}
@@ -0,0 +1,84 @@
+use models::game::{Game, GameMove};
+use models::player::Player;
+use models::replay::{Position, Replay, ReplayError, TOOT_OTTO};
+
+fn drops(columns: &[i32]) -> Vec<GameMove> {
+    columns.iter().map(|&column| GameMove { column, letter: None, pop: false }).collect()
+}
+
+fn letters(moves: &[(i32, char)]) -> Vec<GameMove> {
+    moves.iter().map(|&(column, letter)| GameMove { column, letter: Some(letter), pop: false }).collect()
+}
+
+fn game(game_type: &str, moves: Vec<GameMove>) -> Game {
+    Game { game_type: game_type.into(), moves, ..Game::default() }
+}
+
+#[test]
+fn steps_through_every_position() {
+    let replay = Replay::new(&game("Connect4", drops(&[0, 1, 0, 1, 0, 1, 0]))).unwrap();
+    assert_eq!(replay.len(), 7);
+    match replay.position(3) {
+        Position::Connect4(state) => assert_eq!(state.moves().len(), 3),
+        other => panic!("expected a Connect 4 board, got {:?}", other),
+    }
+    assert_eq!(replay.position(0).winner(), None);
+    assert_eq!(replay.position(100), replay.last());
+}
+
+#[test]
+fn finds_the_winning_four() {
+    let replay = Replay::new(&game("Connect4", drops(&[0, 1, 0, 1, 0, 1, 0]))).unwrap();
+    assert_eq!(replay.winning_step(), Some(7));
+    assert_eq!(replay.last().winner(), Some(Player::One));
+    assert_eq!(replay.last().winning_line(), Some(vec![(2, 0), (3, 0), (4, 0), (5, 0)]));
+    assert_eq!(replay.position(6).winning_line(), None);
+}
+
+#[test]
+fn finds_the_winning_word() {
+    let moves = letters(&[(0, 'T'), (1, 'O'), (5, 'O'), (3, 'T'), (2, 'O')]);
+    let replay = Replay::new(&game(TOOT_OTTO, moves)).unwrap();
+    assert_eq!(replay.winning_step(), Some(5));
+    assert_eq!(replay.last().winning_line(), Some(vec![(3, 0), (3, 1), (3, 2), (3, 3)]));
+}
+
+#[test]
+fn uses_the_recorded_board_size() {
+    let stored = Game { columns: 5, rows: 4, connect: 3, ..game("Connect4", drops(&[0, 0, 1, 1, 2])) };
+    let replay = Replay::new(&stored).unwrap();
+    assert_eq!(replay.last().winner(), Some(Player::One));
+
+    let too_small = Game { columns: 2, rows: 4, ..game("Connect4", Vec::new()) };
+    assert!(matches!(Replay::new(&too_small), Err(ReplayError::Size(_))));
+}
+
+#[test]
+fn rejects_games_that_cannot_be_replayed() {
+    assert_eq!(Replay::new(&game("Chess", Vec::new())),
+        Err(ReplayError::UnknownGameType("Chess".into())));
+
+    // Column 0 holds six discs, so the seventh does not fit
+    let full = Replay::new(&game("Connect4", drops(&[0, 0, 0, 0, 0, 0, 0])));
+    assert!(matches!(full, Err(ReplayError::IllegalMove { index: 6, .. })));
+
+    // Nothing may be played after the winning move
+    let late = Replay::new(&game("Connect4", drops(&[0, 1, 0, 1, 0, 1, 0, 1])));
+    assert!(matches!(late, Err(ReplayError::IllegalMove { index: 7, .. })));
+
+    let no_letter = Replay::new(&game(TOOT_OTTO, drops(&[2])));
+    assert!(matches!(no_letter, Err(ReplayError::IllegalMove { index: 0, .. })));
+}
+
+#[test]
+fn names_the_winner_from_the_moves() {
+    let won = Game { player1_name: "Ann".into(), player2_name: "Bob".into(), ..game("Connect4", drops(&[1, 0, 1, 0, 1, 0, 2, 0])) };
+    assert_eq!(Replay::winner_name(&won), Ok("Bob".to_string()));
+
+    // Fill a 4x4 board without anyone connecting four
+    let columns = [0, 1, 0, 1, 1, 0, 1, 0, 2, 3, 2, 3, 3, 2, 3, 2];
+    let drawn = Game { columns: 4, rows: 4, connect: 4, ..game("Connect4", drops(&columns)) };
+    assert_eq!(Replay::winner_name(&drawn), Ok("Draw".to_string()));
+
+    assert_eq!(Replay::winner_name(&game("Connect4", drops(&[3, 3]))), Err(ReplayError::Unfinished));
+}
@@ -0,0 +1,57 @@
+use models::connect4::{Connect4Move, Connect4State};
+use models::search::{self, WIN_SCORE};
+use models::toot_otto::{Letter, TootOttoMove, TootOttoState};
+
+fn connect4(columns: &[usize]) -> Connect4State {
+    let mut state = Connect4State::new();
+    for &column in columns {
+        state.play(column).unwrap();
+    }
+    state
+}
+
+#[test]
+fn connect4_takes_the_immediate_win() {
+    let state = connect4(&[0, 1, 0, 1, 0, 1]);
+    let (score, moves) = search::best_moves(&state, 3);
+    assert_eq!(moves, vec![Connect4Move::Drop(0)]);
+    assert!(score > WIN_SCORE - 10);
+}
+
+#[test]
+fn connect4_blocks_the_immediate_loss() {
+    let state = connect4(&[0, 1, 0, 1, 0]);
+    let (_, moves) = search::best_moves(&state, 2);
+    assert_eq!(moves, vec![Connect4Move::Drop(0)]);
+}
+
+#[test]
+fn connect4_undo_restores_the_position() {
+    let before = connect4(&[3, 3, 4]);
+    let mut state = before.clone();
+    state.play(5).unwrap();
+    assert_eq!(state.undo(), Some(Connect4Move::Drop(5)));
+    assert_eq!(state, before);
+}
+
+#[test]
+fn toot_otto_completes_its_own_word() {
+    // Player two (OTTO) to move with O T T _ on the bottom row
+    let mut state = TootOttoState::new();
+    for &(column, letter) in [(0, Letter::O), (1, Letter::T), (2, Letter::T), (5, Letter::T)].iter() {
+        state.play(TootOttoMove { column, letter }).unwrap();
+    }
+    state.play(TootOttoMove { column: 5, letter: Letter::T }).unwrap();
+    let (score, moves) = search::best_moves(&state, 2);
+    assert!(moves.contains(&TootOttoMove { column: 3, letter: Letter::O }));
+    assert!(score > WIN_SCORE - 10);
+}
+
+#[test]
+fn toot_otto_undo_returns_the_disc() {
+    let mut state = TootOttoState::new();
+    let before = state.clone();
+    state.play(TootOttoMove { column: 2, letter: Letter::O }).unwrap();
+    state.undo();
+    assert_eq!(state, before);
+}
@@ -0,0 +1,153 @@
+use std::time::Instant;
+use models::connect4::{Connect4Move, Connect4State, Variant};
+use models::connect4_search::{self, Limits};
+use models::search;
+use models::solver::{self, Value};
+
+fn connect4(columns: &[usize]) -> Connect4State {
+    let mut state = Connect4State::new();
+    for &column in columns {
+        state.play(column).unwrap();
+    }
+    state
+}
+
+fn clock() -> impl Fn() -> f64 {
+    let start = Instant::now();
+    move || start.elapsed().as_secs_f64() * 1000.0
+}
+
+// Whether playing `mv` keeps the value the position had with perfect play
+fn keeps_value(state: &Connect4State, mv: Connect4Move, value: Value) -> bool {
+    let mut child = state.clone();
+    child.play_move(mv).unwrap();
+    let after = solver::solve(&child).unwrap();
+    match value {
+        Value::Win(_) => matches!(after, Value::Loss(_)),
+        Value::Draw => !matches!(after, Value::Win(_)),
+        Value::Loss(_) => true,
+    }
+}
+
+#[test]
+fn solves_immediate_wins_and_losses() {
+    assert_eq!(solver::solve(&connect4(&[0, 1, 0, 1, 0, 1])), Some(Value::Win(1)));
+    // Player two cannot block both ends of the bottom row
+    assert_eq!(solver::solve(&connect4(&[2, 2, 3, 3, 4])), Some(Value::Loss(2)));
+    assert_eq!(solver::solve(&connect4(&[0, 1, 0, 1, 0, 1, 0])), Some(Value::Loss(0)));
+}
+
+#[test]
+fn small_board_is_a_draw() {
+    let state = Connect4State::with_size(4, 4, 4).unwrap();
+    assert_eq!(solver::solve(&state), Some(Value::Draw));
+}
+
+#[test]
+fn best_move_takes_the_quickest_win() {
+    // Column 0 wins at once, though other moves still win later
+    let state = connect4(&[0, 6, 0, 6, 0, 5]);
+    let now = clock();
+    assert_eq!(solver::best_move(&state, 60_000.0, &now), Some((Connect4Move::Drop(0), Value::Win(1))));
+}
+
+#[test]
+fn mid_game_position_is_solved_exactly() {
+    let state = connect4(&[3, 3, 3, 3, 2, 4, 4, 2, 2, 2, 4, 4, 3, 1]);
+    assert_eq!(solver::solve(&state), Some(Value::Win(23)));
+    let now = clock();
+    let (mv, value) = solver::best_move(&state, 60_000.0, &now).unwrap();
+    assert_eq!(value, Value::Win(23));
+    assert!(keeps_value(&state, mv, value));
+}
+
+#[test]
+fn opening_book_answers_the_empty_board() {
+    let now = clock();
+    assert_eq!(solver::best_move(&Connect4State::new(), 0.0, &now), Some((Connect4Move::Drop(3), Value::Win(41))));
+}
+
+#[test]
+fn gives_up_when_out_of_time() {
+    // Far too early in the game to solve within the budget
+    let now = clock();
+    assert_eq!(solver::best_move(&connect4(&[3, 3]), 10.0, &now), None);
+}
+
+#[test]
+fn early_positions_keep_to_the_expert_budget() {
+    // Expert's budget, after which it searches like hard instead
+    let budget = 1500.0;
+    let state = connect4(&[3, 3, 3, 3, 2, 4]);
+    let now = clock();
+    let found = solver::best_move(&state, budget, &now);
+    // The clock is only read every few thousand positions, so allow a little over
+    let took = now();
+    assert!(took < budget + 500.0, "took {} ms", took);
+    if let Some((mv, value)) = found {
+        assert!(keeps_value(&state, mv, value));
+    }
+}
+
+#[test]
+fn pop_out_is_not_solved() {
+    let state = Connect4State::new().with_variant(Variant::PopOut);
+    assert_eq!(solver::solve(&state), None);
+    assert_eq!(solver::best_move(&state, 60_000.0, &|| 0.0), None);
+}
+
+#[test]
+fn hard_level_keeps_a_solved_win() {
+    let state = connect4(&[3, 3, 3, 3, 2, 4, 4, 2, 2, 2, 4, 4, 3, 1]);
+    let now = clock();
+    let result = connect4_search::best_move(&state, Limits { time_budget: 60_000.0, max_depth: 12 }, &now).unwrap();
+    assert!(keeps_value(&state, result.best_move, Value::Win(23)));
+}
+
+// Compares every difficulty level with perfect play on random mid-game
+// positions. Slow, so run it on demand with `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn heuristic_levels_against_solver() {
+    let mut seed: u64 = 99;
+    let mut random = |n: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % n as u64) as usize
+    };
+
+    let levels = ["easy", "medium", "hard"];
+    let mut kept = [0; 3];
+    let mut positions = 0;
+    while positions < 50 {
+        let mut state = Connect4State::new();
+        for _ in 0..16 {
+            let moves = state.legal_moves();
+            if moves.is_empty() {
+                break;
+            }
+            state.play_move(moves[random(moves.len())]).unwrap();
+        }
+        if state.is_over() {
+            continue;
+        }
+        let value = solver::solve(&state).unwrap();
+        positions += 1;
+
+        let now = clock();
+        let choices = [
+            search::best_moves(&state, 2).1[0],
+            search::best_moves(&state, 4).1[0],
+            connect4_search::best_move(&state, Limits { time_budget: 2000.0, max_depth: 64 }, &now).unwrap().best_move,
+        ];
+        for (level, &mv) in choices.iter().enumerate() {
+            if keeps_value(&state, mv, value) {
+                kept[level] += 1;
+            }
+        }
+    }
+    for (level, kept) in levels.iter().zip(kept.iter()) {
+        println!("{}: kept the solved value in {} of {} positions", level, kept, positions);
+    }
+}
@@ -0,0 +1,71 @@
+use models::game::{Game, GameMove};
+use models::stats::{PlayerStats, Record};
+
+fn game(date: i64, player1: &str, player2: &str, winner: &str, columns: &[i32]) -> Game {
+    Game {
+        game_type: "Connect4".into(),
+        player1_name: player1.into(),
+        player2_name: player2.into(),
+        winner_name: winner.into(),
+        game_date: date,
+        moves: columns.iter().map(|&column| GameMove { column, letter: None, pop: false }).collect(),
+        ..Game::default()
+    }
+}
+
+fn record(wins: i32, losses: i32, draws: i32) -> Record {
+    Record { played: wins + losses + draws, wins, losses, draws }
+}
+
+#[test]
+fn counts_results_by_opponent_and_game_type() {
+    let games = vec![
+        game(1, "Ann", "Bob", "Ann", &[3, 2, 3, 2]),
+        game(2, "Bob", "Ann", "Bob", &[0, 3, 0]),
+        game(3, "Ann", "Cat", "Draw", &[]),
+        Game { game_type: "TOOT-OTTO".into(), ..game(4, "Ann", "Bob", "Ann", &[]) },
+        // Not Ann's game, and a game without a result
+        game(5, "Bob", "Cat", "Bob", &[]),
+        game(6, "Ann", "Bob", "", &[1]),
+    ];
+    let stats = PlayerStats::from_games("Ann", &games);
+    assert_eq!(stats.overall, record(2, 1, 1));
+    assert_eq!(stats.by_opponent, vec![("Bob".to_string(), record(2, 1, 0)), ("Cat".to_string(), record(0, 0, 1))]);
+    assert_eq!(stats.by_game_type, vec![("Connect4".to_string(), record(1, 1, 1)), ("TOOT-OTTO".to_string(), record(1, 0, 0))]);
+    // Only the games saved with their moves count towards the length
+    assert_eq!(stats.average_moves, 3.5);
+    // Ann opened in column 3 as player one and replied in column 3 as player two
+    assert_eq!(stats.opening_columns, vec![(3, 2)]);
+}
+
+#[test]
+fn finds_the_longest_win_streak_in_date_order() {
+    let games = vec![
+        game(4, "Ann", "Bob", "Ann", &[]),
+        game(1, "Ann", "Bob", "Ann", &[]),
+        game(3, "Ann", "Bob", "Ann", &[]),
+        game(2, "Ann", "Bob", "Bob", &[]),
+        game(5, "Ann", "Bob", "Draw", &[]),
+        game(6, "Ann", "Bob", "Ann", &[]),
+    ];
+    assert_eq!(PlayerStats::from_games("Ann", &games).longest_win_streak, 2);
+    assert_eq!(PlayerStats::from_games("Bob", &games).longest_win_streak, 1);
+}
+
+#[test]
+fn splits_computer_games_by_difficulty() {
+    let hard = |date, winner: &str| Game { difficulty: "hard".into(), ..game(date, "Ann", "Computer", winner, &[]) };
+    let easy = Game { difficulty: "easy".into(), ..game(3, "Ann", "Computer", "Ann", &[]) };
+    let games = vec![hard(1, "Computer"), hard(2, "Computer"), easy];
+    let stats = PlayerStats::from_games("Ann", &games);
+    assert_eq!(stats.by_difficulty, vec![("hard".to_string(), record(0, 2, 0)), ("easy".to_string(), record(1, 0, 0))]);
+    assert_eq!(stats.by_opponent, vec![("Computer".to_string(), record(1, 2, 0))]);
+}
+
+#[test]
+fn players_without_games_have_empty_stats() {
+    let stats = PlayerStats::from_games("Nobody", &[game(1, "Ann", "Bob", "Ann", &[])]);
+    assert_eq!(stats.overall, Record::default());
+    assert_eq!(stats.average_moves, 0.0);
+    assert!(stats.opening_columns.is_empty());
+}
@@ -0,0 +1,68 @@
+use models::player::Player;
+use models::toot_otto::{Letter, MoveError, MoveOutcome, TootOttoMove, TootOttoState, DISCS_PER_LETTER, ROWS};
+
+fn mv(column: usize, letter: char) -> TootOttoMove {
+    TootOttoMove { column, letter: Letter::from_char(letter).unwrap() }
+}
+
+fn play_all(state: &mut TootOttoState, moves: &[(usize, char)]) -> MoveOutcome {
+    let mut outcome = MoveOutcome::Continue;
+    for &(column, letter) in moves {
+        outcome = state.play(mv(column, letter)).unwrap();
+    }
+    outcome
+}
+
+#[test]
+fn letters_are_recorded_in_disc_map() {
+    let mut state = TootOttoState::new();
+    play_all(&mut state, &[(2, 'T'), (2, 'O')]);
+    assert_eq!(state.board().disc_map[ROWS - 1][2], 'T');
+    assert_eq!(state.board().disc_map[ROWS - 2][2], 'O');
+    assert_eq!(state.board().tokens[ROWS - 2][2], -1);
+    assert_eq!(state.discs_left(Player::One, Letter::T), DISCS_PER_LETTER - 1);
+    assert_eq!(state.discs_left(Player::Two, Letter::O), DISCS_PER_LETTER - 1);
+}
+
+#[test]
+fn toot_wins_for_player_one_whoever_places_the_last_disc() {
+    let mut state = TootOttoState::new();
+    let outcome = play_all(&mut state, &[(0, 'T'), (1, 'O'), (5, 'O'), (3, 'T'), (2, 'O')]);
+    assert_eq!(outcome, MoveOutcome::Win(Player::One));
+    assert_eq!(state.winner(), Some(Player::One));
+    assert_eq!(state.play(mv(4, 'T')), Err(MoveError::GameOver));
+}
+
+#[test]
+fn vertical_otto_wins_for_player_two() {
+    let mut state = TootOttoState::new();
+    let outcome = play_all(&mut state, &[(0, 'O'), (0, 'T'), (1, 'O'), (0, 'T'), (2, 'T'), (0, 'O')]);
+    assert_eq!(outcome, MoveOutcome::Win(Player::Two));
+}
+
+#[test]
+fn spelling_both_words_at_once_is_a_draw() {
+    let mut state = TootOttoState::new();
+    let outcome = play_all(&mut state, &[
+        (5, 'T'), (0, 'T'), (4, 'O'), (1, 'T'), (2, 'O'), (5, 'O'), (4, 'O'), (1, 'O'),
+        (4, 'T'), (0, 'O'), (1, 'T'), (2, 'O'), (1, 'T'), (4, 'O'), (3, 'O'), (2, 'T'),
+        (0, 'O'), (0, 'O'), (5, 'T'), (2, 'T'), (5, 'T'), (3, 'T'),
+    ]);
+    assert_eq!(outcome, MoveOutcome::Continue);
+    assert_eq!(state.play(mv(3, 'O')), Ok(MoveOutcome::Draw));
+    assert!(state.is_draw());
+    assert_eq!(state.winner(), None);
+}
+
+#[test]
+fn each_player_only_has_six_of_each_letter() {
+    let mut state = TootOttoState::new();
+    // Player one plays T everywhere on the bottom row, player two stacks O on top
+    for column in 0..6 {
+        state.play(mv(column, 'T')).unwrap();
+        state.play(mv(column, 'T')).unwrap();
+    }
+    assert_eq!(state.discs_left(Player::One, Letter::T), 0);
+    assert_eq!(state.play(mv(0, 'T')), Err(MoveError::OutOfDiscs(Letter::T)));
+    assert!(state.legal_moves().iter().all(|m| m.letter == Letter::O));
+}
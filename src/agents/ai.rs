@@ -0,0 +1,145 @@
+use serde::{Serialize, Deserialize};
+use yew::agent::{Agent, AgentLink, HandlerId, Public};
+use js_sys::{Date, Math};
+use crate::models::connect4::{Connect4Move, Connect4State, Variant};
+use crate::models::toot_otto::{TootOttoMove, TootOttoState};
+use crate::models::{connect4_search, mcts, search, solver};
+
+// Longest the hard AI may think about a move, in milliseconds
+const HARD_TIME_BUDGET: f64 = 2000.0;
+// Time the expert AI gives the solver before falling back to the hard search.
+// Most positions in the opening cannot be solved this quickly, so expert plays
+// like hard until the board has filled up; each response says which it did.
+const EXPERT_SOLVE_BUDGET: f64 = 1500.0;
+
+macro_rules! log {
+    ( $( $t:tt )* ) => {
+        web_sys::console::log_1(&format!( $( $t )* ).into());
+    }
+}
+
+/// Position the computer should move in. Games are sent as the moves played
+/// so far and replayed in the worker, along with how hard the computer should try.
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Request {
+    Connect4 {
+        id: u32,
+        columns: usize,
+        rows: usize,
+        connect: usize,
+        variant: Variant,
+        moves: Vec<Connect4Move>,
+        difficulty: String,
+        max_depth: u32
+    },
+    TootOtto {
+        id: u32,
+        moves: Vec<TootOttoMove>,
+        /// Random games to play out before choosing
+        playouts: u32
+    }
+}
+
+/// Move chosen for the request with the same id, or `None` if there was nothing to play
+#[derive(Serialize, Deserialize, Debug)]
+pub enum Response {
+    /// `solved` is set when the solver found the move, so it is perfect play
+    Connect4 { id: u32, choice: Option<Connect4Move>, solved: bool },
+    TootOtto { id: u32, choice: Option<TootOttoMove> }
+}
+
+/// Runs the computer players in a Web Worker, so the page stays responsive
+/// while they think.
+///
+/// A search cannot be interrupted once it has started, so components cancel a
+/// request by ignoring the response that carries its id.
+pub struct AiAgent {
+    link: AgentLink<AiAgent>
+}
+
+impl Agent for AiAgent {
+    type Reach = Public;
+    type Message = ();
+    type Input = Request;
+    type Output = Response;
+
+    fn create(link: AgentLink<Self>) -> Self {
+        AiAgent { link }
+    }
+
+    fn update(&mut self, _msg: Self::Message) {}
+
+    fn handle_input(&mut self, request: Self::Input, who: HandlerId) {
+        let response = match request {
+            Request::Connect4 { id, columns, rows, connect, variant, moves, difficulty, max_depth } => {
+                let found = Connect4State::with_size(columns, rows, connect).ok()
+                    .map(|state| state.with_variant(variant))
+                    .and_then(|mut state| {
+                        for &mv in moves.iter() {
+                            state.play_move(mv).ok()?;
+                        }
+                        Some(state)
+                    })
+                    .and_then(|state| connect4_move(&state, &difficulty, max_depth));
+                Response::Connect4 {
+                    id,
+                    choice: found.map(|(choice, _)| choice),
+                    solved: found.map_or(false, |(_, solved)| solved)
+                }
+            },
+            Request::TootOtto { id, moves, playouts } => {
+                let mut state = TootOttoState::new();
+                let replayed = moves.iter().all(|&mv| state.play(mv).is_ok());
+                let choice = if replayed { toot_otto_move(&state, playouts) } else { None };
+                Response::TootOtto { id, choice }
+            },
+        };
+        self.link.respond(who, response);
+    }
+
+    // Script built from the `worker` crate, served next to the page
+    fn name_of_resource() -> &'static str {
+        "worker.js"
+    }
+}
+
+// The move with whether the solver chose it
+fn connect4_move(state: &Connect4State, difficulty: &str, max_depth: u32) -> Option<(Connect4Move, bool)> {
+    if difficulty == "expert" {
+        // Play perfectly once the position can be solved in time
+        if let Some((choice, value)) = solver::best_move(state, EXPERT_SOLVE_BUDGET, &Date::now) {
+            log!("AI -1 choose move: {:?} (solved: {:?})", choice, value);
+            return Some((choice, true));
+        }
+    }
+    if difficulty == "hard" || difficulty == "expert" {
+        // Search deeper and deeper until the time budget is spent
+        let limits = connect4_search::Limits { time_budget: HARD_TIME_BUDGET, max_depth };
+        let result = connect4_search::best_move(state, limits, &Date::now)?;
+        log!("AI -1 choose move: {:?} (value: {}, depth: {}, positions: {})",
+            result.best_move, result.score, result.depth, result.nodes);
+        return Some((result.best_move, false));
+    }
+
+    // depth changes the difficulty (less depth = easier)
+    let (value, choices) = search::best_moves(state, max_depth + 1);
+    let choice = random_choice(&choices)?;
+    log!("AI -1 choose move: {:?} (value: {})", choice, value);
+    Some((choice, false))
+}
+
+fn toot_otto_move(state: &TootOttoState, playouts: u32) -> Option<TootOttoMove> {
+    // More playouts make a stronger and steadier player
+    let seed = (Math::random() * u32::MAX as f64) as u64;
+    let (choice, value) = mcts::best_move(state, playouts, seed)?;
+    log!("AI {} choose column: {} (won {:.0}% of playouts)", choice.letter.to_char(), choice.column, value * 100.0);
+    Some(choice)
+}
+
+// Use js_sys::Math to find random value (rand does not seem to work)
+fn random_choice<T: Copy>(choices: &[T]) -> Option<T> {
+    if choices.is_empty() {
+        return None;
+    }
+    Some(choices[(Math::random() * choices.len() as f64).floor() as usize])
+}
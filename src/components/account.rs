@@ -0,0 +1,210 @@
+use yew::prelude::*;
+use yew::services::fetch::{Request, Response, FetchService, FetchTask};
+use yew::format::{Json, Nothing};
+use anyhow::Error;
+use crate::models::account::{Credentials, Session};
+use super::utils::{api, session};
+
+/// Register, log in and log out. The session is kept in local storage, so the
+/// game tabs play under the account until it logs out.
+pub struct Account {
+    link: ComponentLink<Self>,
+    credentials: Credentials,
+    session: Option<Session>,
+    error: Option<String>,
+    task: Option<Result<FetchTask, Error>>
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {}
+
+pub enum Msg {
+    UsernameInput(String),
+    PasswordInput(String),
+    ClickedLogin,
+    ClickedRegister,
+    ClickedLogout,
+    LoggedIn(Session),
+    LoggedOut,
+    Failed(String)
+}
+
+impl Component for Account {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Account {
+            link,
+            credentials: Credentials::default(),
+            session: session::current(),
+            error: None,
+            task: None
+        }
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::UsernameInput(value) => self.credentials.username = value,
+            Msg::PasswordInput(value) => self.credentials.password = value,
+            Msg::ClickedLogin => self.send_credentials(api::url("/login")),
+            Msg::ClickedRegister => {
+                // Checked here too, so mistakes show without a trip to the server
+                match self.credentials.validate() {
+                    Ok(()) => self.send_credentials(api::url("/register")),
+                    Err(reason) => self.error = Some(reason),
+                }
+            },
+            Msg::ClickedLogout => self.logout(),
+            Msg::LoggedIn(new_session) => {
+                session::store(&new_session);
+                self.session = Some(new_session);
+                self.credentials = Credentials::default();
+                self.error = None;
+                self.task = None;
+            },
+            Msg::LoggedOut => {
+                session::clear();
+                self.session = None;
+                self.task = None;
+            },
+            Msg::Failed(reason) => {
+                self.error = Some(reason);
+                self.task = None;
+            },
+        }
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        html! {
+            <div class="w3-container">
+                <div>
+                    <h5 class="w3-xxxlarge w3-text-red"><b>{"Account"}</b></h5>
+                    <hr style="width:50px;border:5px solid red" class="w3-round"/>
+                </div>
+                {
+                    match &self.session {
+                        Some(session) => self.view_logged_in(session),
+                        None => self.view_login_form(),
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl Account {
+    fn view_logged_in(&self, session: &Session) -> Html {
+        html! {
+            <div class="w3-row-padding" style="padding:4px;">
+                <p>{"Logged in as "}<b>{&session.username}</b>{". Games you play are saved under this name."}</p>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.task.is_some()
+                    onclick=self.link.callback(|_| Msg::ClickedLogout)>
+                    {"Log Out"}
+                </button>
+            </div>
+        }
+    }
+
+    fn view_login_form(&self) -> Html {
+        let error = match &self.error {
+            Some(reason) => html! { <p class="w3-text-red">{reason}</p> },
+            None => html! {},
+        };
+        html! {
+            <form
+                onsubmit=self.link.callback(|_| Msg::ClickedLogin)
+                action="javascript:void(0);">
+                <p>{"Log in to have your games recorded under your account. Nobody else can save games under a registered name."}</p>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <div class="w3-third">
+                        <label for="accountUsername">{"Username:"}</label>
+                        <input
+                            class="w3-input w3-border w3-round"
+                            id="accountUsername"
+                            type="text"
+                            value=&self.credentials.username
+                            oninput=self.link.callback(|e: InputData| Msg::UsernameInput(e.value))
+                            placeholder="Enter username"/>
+                    </div>
+                    <div class="w3-third">
+                        <label for="accountPassword">{"Password:"}</label>
+                        <input
+                            class="w3-input w3-border w3-round"
+                            id="accountPassword"
+                            type="password"
+                            value=&self.credentials.password
+                            oninput=self.link.callback(|e: InputData| Msg::PasswordInput(e.value))
+                            placeholder="Enter password"/>
+                    </div>
+                </div>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <button class="w3-button w3-border w3-round" type="submit" disabled=self.task.is_some()>
+                        {"Log In"}
+                    </button>
+                    <button class="w3-button w3-border w3-round" type="button"
+                        disabled=self.task.is_some()
+                        onclick=self.link.callback(|_| Msg::ClickedRegister)>
+                        {"Register"}
+                    </button>
+                </div>
+                {error}
+            </form>
+        }
+    }
+
+    fn send_credentials(&mut self, url: String) {
+        let post_request = Request::post(url)
+            .header("Content-Type", "application/json")
+            .body(Json(&self.credentials))
+            .expect("Failed to build request.");
+
+        let task = FetchService::new().fetch(
+            post_request,
+            self.link.callback(|response: Response<Result<String, Error>>| {
+                let (meta, body) = response.into_parts();
+                let body = body.unwrap_or_default();
+                if meta.status.is_success() {
+                    match serde_json::from_str(&body) {
+                        Ok(session) => Msg::LoggedIn(session),
+                        Err(e) => Msg::Failed(e.to_string()),
+                    }
+                } else {
+                    Msg::Failed(error_message(&body, meta.status.as_str()))
+                }
+            }),
+        );
+        self.task = Some(task);
+    }
+
+    fn logout(&mut self) {
+        let token = match &self.session {
+            Some(session) => session.bearer(),
+            None => return,
+        };
+        let post_request = Request::post(api::url("/logout"))
+            .header("Authorization", token)
+            .body(Nothing)
+            .expect("Failed to build request.");
+
+        // The session is forgotten here even if the server has already ended it
+        let task = FetchService::new().fetch(
+            post_request,
+            self.link.callback(|_: Response<Result<String, Error>>| Msg::LoggedOut),
+        );
+        self.task = Some(task);
+    }
+}
+
+// The backend explains refusals with an {"error": ...} body
+fn error_message(body: &str, status: &str) -> String {
+    serde_json::from_str::<serde_json::Value>(body).ok()
+        .and_then(|value| value["error"].as_str().map(String::from))
+        .unwrap_or_else(|| format!("The server answered with status {}", status))
+}
@@ -3,42 +3,60 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window};
 use yew::services::fetch::{Request, Response, FetchService, FetchTask};
-use yew::format::{Json, Nothing};
+use yew::format::Json;
 use anyhow::Error;
 use serde_json::json;
-use js_sys::{Date, Math};
-use crate::models::game_boards::Connect4GameBoard;
+use js_sys::Date;
+use crate::models::notation;
+use crate::models::account::Session;
+use crate::models::difficulty;
+use crate::components::utils::{api, session};
+use crate::models::connect4::{self, Connect4Move, Connect4State, MoveOutcome, Variant};
+use crate::models::connect4_search;
+use crate::agents::ai::{self, AiAgent};
+use crate::models::player::Player;
 use std::f64;
-use std::cmp::{max, min};
+use std::cmp::max;
 use crate::alert;
 
 extern crate models;
 use models::game::Game;
 
+// Half the spacing between columns, so a click anywhere over the board picks the nearest column
+const CLICK_RADIUS: f64 = 37.5;
+
 pub struct Connect4Computer {
     link: ComponentLink<Self>,
     game: Game,
     game_started: bool,
     context: Option<CanvasRenderingContext2d>,
-    board: Connect4GameBoard,
-    move_num: u8,
+    state: Connect4State,
+    columns: usize,
+    rows: usize,
+    connect: usize,
+    variant: Variant,
     won: bool,
     paused: bool,
     save_task: Option<Result<FetchTask, Error>>,
+    // Account logged in on this browser, which plays as Player 1
+    account: Option<Session>,
     player1_color: String,
     computer_color: String,
     board_color: String,
     selected_difficulty: String,
-    max_depth: i64
-}
-
-pub struct GameBoard {
-    rows: u8,
-    columns: u8,
-    tokens: [[i8; 7];6]
+    max_depth: u32,
+    ai: Box<dyn Bridge<AiAgent>>,
+    // Id of the request the computer is thinking about, if any
+    thinking: Option<u32>,
+    requests: u32,
+    // Whether the expert's last move was solved, rather than searched for like hard
+    expert_solved: Option<bool>,
+    // Moves taken back by Undo, most recent last
+    redo: Vec<Connect4Move>,
+    // Notation of the game so far is shown under the board
+    show_notation: bool
 }
 
-
 macro_rules! log {
     ( $( $t:tt )* ) => {
         web_sys::console::log_1(&format!( $( $t )* ).into());
@@ -52,13 +70,21 @@ pub enum Msg {
     GotInput(String),
     ClickedStart,
     ClickedBoard(MouseEvent),
-    GetGamesList(Vec<Game>),
-    GameSaved,
+    GameSaved(Game),
     SaveError,
     Player1ColorChange(String),
     ComputerColorChange(String),
     BoardColorChange(String),
-    DifficultyLevelChange(String)
+    BoardWidthChange(String),
+    BoardHeightChange(String),
+    ConnectLengthChange(String),
+    VariantChange(String),
+    DifficultyLevelChange(String),
+    AiMoved(ai::Response),
+    ClickedCancel,
+    ClickedUndo,
+    ClickedRedo,
+    ClickedExport
 }
 
 impl Component for Connect4Computer {
@@ -66,32 +92,40 @@ impl Component for Connect4Computer {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let account = session::current();
         Connect4Computer {
-            link,
+            link: link.clone(),
             game: Game {
-                game_number: 0, // placeholder, when game is saved this can be set
+                game_number: 0, // placeholder, the backend numbers the game when it is saved
                 game_type: "Connect4".into(),
-                player1_name: "".into(),
+                player1_name: session::player_name(&account),
                 player2_name: "Computer".into(),
                 winner_name: "".into(),
-                game_date: 0 // placeholder, when game is saved this can be set
+                game_date: 0, // placeholder, when game is saved this can be set
+                ..Game::default()
             },
             game_started: false,
             context: None,
-            board: Connect4GameBoard {
-                rows: 6,
-                columns: 7,
-                tokens: [[0; 7]; 6],
-            },
-            move_num: 0,
+            state: Connect4State::new(),
+            columns: connect4::COLUMNS,
+            rows: connect4::ROWS,
+            connect: connect4::CONNECT,
+            variant: Variant::Standard,
             won: false,
             paused: false,
             save_task: None,
+            account,
             player1_color: "#ff4136".into(),
             computer_color: "#ffff00".into(),
             board_color: "#00bfff".into(),
             selected_difficulty: "easy".into(),
-            max_depth: 1
+            max_depth: 1,
+            ai: AiAgent::bridge(link.callback(Msg::AiMoved)),
+            thinking: None,
+            expert_solved: None,
+            requests: 0,
+            redo: Vec::new(),
+            show_notation: false
         }
     }
 
@@ -108,14 +142,24 @@ impl Component for Connect4Computer {
                     alert("Player name can't be blank!");
                 } else if self.game.player1_name == "Computer" {
                     alert("Computer is a reserved name!");
+                } else if let Err(e) = Connect4State::with_size(self.columns, self.rows, self.connect) {
+                    alert(&e.to_string());
                 } else {
+                    self.game.game_type = self.variant.game_type().into();
+                    // Settings are saved with the game so it can be replayed
+                    self.game.columns = self.columns as i32;
+                    self.game.rows = self.rows as i32;
+                    self.game.connect = self.connect as i32;
+                    self.game.player1_color = self.player1_color.clone();
+                    self.game.player2_color = self.computer_color.clone();
+                    self.game.difficulty = self.selected_difficulty.clone();
                     self.game_started = true;
                     self.draw_board();
                     self.print();
                 }
             },
             Msg::ClickedBoard(event) => {
-                if !self.game_started {
+                if !self.game_started || self.thinking.is_some() {
                     return false;
                 }
                 if self.won {
@@ -128,29 +172,30 @@ impl Component for Connect4Computer {
                 let y = event.client_y() as f64 - rect.top();
                 // log!("x: {} y: {}", x,y);
 
-                for i in 0..7 {
-                    if self.on_region([x, y], (75 * i + 100) as f64, 25.0){
+                // In PopOut, clicks below the board pop a disc instead of dropping one
+                let popping = self.state.variant() == Variant::PopOut && y > self.board_height() as f64;
+                for i in 0..self.state.columns() {
+                    if self.on_region([x, y], (75 * i + 100) as f64, CLICK_RADIUS){
                         // log!("Region {} clicked", i);
                         self.paused = false;
-                        let valid = self.action(i);
+                        let mv = if popping { Connect4Move::Pop(i) } else { Connect4Move::Drop(i) };
+                        let valid = self.action(mv);
                         if valid == 1 {
-                            // Perform AI action
-                            self.paused = false;
-                            self.ai();
+                            // A new move makes the undone ones unreachable
+                            self.redo.clear();
+                            if !self.won {
+                                // Perform AI action
+                                self.ai();
+                            }
                         }
                         break; //because there will be no 2 points that are clicked at a time
                     }
                     
                 }
             },
-            Msg::GetGamesList(games) => {
-                log!("Obtained games list");
-                self.game.game_number = games.len() as i32 + 1;
-                self.save_task = None;
-                self.save_game();
-            },
-            Msg::GameSaved => {
-                log!("Successfully saved");
+            Msg::GameSaved(saved) => {
+                log!("Successfully saved as game {}", saved.game_number);
+                self.game.game_number = saved.game_number;
                 self.save_task = None;
             },
             Msg::SaveError => log!("Game failed to save"),
@@ -163,16 +208,95 @@ impl Component for Connect4Computer {
             Msg::BoardColorChange(new_value) => {
                 self.board_color = new_value;
             },
+            Msg::BoardWidthChange(new_value) => {
+                if let Ok(columns) = new_value.parse() {
+                    self.columns = columns;
+                    self.resize();
+                }
+            },
+            Msg::BoardHeightChange(new_value) => {
+                if let Ok(rows) = new_value.parse() {
+                    self.rows = rows;
+                    self.resize();
+                }
+            },
+            Msg::ConnectLengthChange(new_value) => {
+                if let Ok(connect) = new_value.parse() {
+                    self.connect = connect;
+                    self.resize();
+                }
+            },
+            Msg::VariantChange(new_value) => {
+                if let Some(variant) = Variant::from_game_type(&new_value) {
+                    self.variant = variant;
+                    self.resize();
+                    // Expert needs the solver, which PopOut does not have
+                    if difficulty::offered(variant.game_type()).all(|level| level.name != self.selected_difficulty) {
+                        self.selected_difficulty = String::from("hard");
+                    }
+                }
+            },
             Msg::DifficultyLevelChange(new_value) => {
                 if new_value == "easy" {
                     self.max_depth = 1;
                 } else if new_value == "medium" {
                     self.max_depth = 3;
-                } else if new_value == "hard" {
-                    self.max_depth = 4;
+                } else if new_value == "hard" || new_value == "expert" {
+                    self.max_depth = connect4_search::MAX_DEPTH;
                 }
                 self.selected_difficulty = new_value;
             },
+            Msg::AiMoved(ai::Response::Connect4 { id, choice, solved }) => {
+                // Answers to cancelled requests come back too, and are dropped
+                if self.thinking != Some(id) {
+                    return false;
+                }
+                self.thinking = None;
+                if self.selected_difficulty == "expert" {
+                    self.expert_solved = Some(solved);
+                }
+                self.paused = false;
+                if let Some(choice) = choice {
+                    self.action(choice);
+                }
+            },
+            Msg::AiMoved(_) => return false,
+            Msg::ClickedCancel => {
+                if self.thinking.take().is_some() {
+                    // Take back the player's move so they can choose again
+                    self.state.undo();
+                    self.redraw();
+                    self.paused = false;
+                }
+            },
+            Msg::ClickedUndo => {
+                if self.won || self.thinking.is_some() {
+                    return false;
+                }
+                // Take back the computer's reply together with the player's move
+                while let Some(mv) = self.state.undo() {
+                    self.redo.push(mv);
+                    if self.state.current_player() == Player::One {
+                        break;
+                    }
+                }
+                self.paused = false;
+                self.redraw();
+            },
+            Msg::ClickedExport => self.show_notation = !self.show_notation,
+            Msg::ClickedRedo => {
+                if self.won || self.thinking.is_some() {
+                    return false;
+                }
+                // Replay the player's move and the computer's reply to it
+                while let Some(mv) = self.redo.pop() {
+                    self.paused = false;
+                    self.action(mv);
+                    if self.won || self.state.current_player() == Player::One {
+                        break;
+                    }
+                }
+            },
         }
         true
     }
@@ -228,7 +352,7 @@ impl Component for Connect4Computer {
                                     class="w3-input w3-border w3-round"
                                     id="nameInput"
                                     type="text"
-                                    disabled=self.game_started
+                                    disabled=self.game_started || self.account.is_some()
                                     value=&self.game.player1_name
                                     oninput=self.link.callback(|e: InputData| Msg::GotInput(e.value))
                                     placeholder="Enter name"
@@ -274,23 +398,59 @@ impl Component for Connect4Computer {
                             <div class="w3-threequarter">
                                 <label>{"Difficulty Level:"}</label>
                                 <div style="display:block;">
-                                    <input class="w3-radio" type="radio" name="difficulty"
-                                        value="easy" disabled=self.game_started checked={self.selected_difficulty == "easy"}
-                                        oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
-                                    <label style="padding:8px;">{"Easy"}</label>
-                                    
-                                    <input class="w3-radio" type="radio" name="difficulty"
-                                        value="medium" disabled=self.game_started checked={self.selected_difficulty == "medium"}
-                                        oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
-                                    <label style="padding:8px;">{"Medium"}</label>
-                                    
-                                    <input class="w3-radio" type="radio" name="difficulty"
-                                        value="hard" disabled=self.game_started checked={self.selected_difficulty == "hard"}
-                                        oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
-                                    <label style="padding:8px;">{"Hard"}</label>
+                                    { for difficulty::offered(self.variant.game_type()).map(|level| html! {
+                                        <>
+                                            <input class="w3-radio" type="radio" name="difficulty"
+                                                value=level.name disabled=self.game_started checked={self.selected_difficulty == level.name}
+                                                oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
+                                            <label style="padding:8px;">{level.label}</label>
+                                        </>
+                                    }) }
                                 </div>
                             </div>  
                         </div>
+                        <div class="w3-row-padding" style="padding:4px;">
+                            <div class="w3-quarter">
+                                <label for="board_width">{"Board Width:"}</label>
+                                <input
+                                    class="w3-input w3-border w3-round"
+                                    oninput=self.link.callback(|e: InputData| Msg::BoardWidthChange(e.value))
+                                    type="number"
+                                    id="board_width"
+                                    min=connect4::MIN_SIZE.to_string()
+                                    max=connect4::MAX_SIZE.to_string()
+                                    value=self.columns.to_string()
+                                    disabled=self.game_started
+                                />
+                            </div>
+                            <div class="w3-quarter">
+                                <label for="board_height">{"Board Height:"}</label>
+                                <input
+                                    class="w3-input w3-border w3-round"
+                                    oninput=self.link.callback(|e: InputData| Msg::BoardHeightChange(e.value))
+                                    type="number"
+                                    id="board_height"
+                                    min=connect4::MIN_SIZE.to_string()
+                                    max=connect4::MAX_SIZE.to_string()
+                                    value=self.rows.to_string()
+                                    disabled=self.game_started
+                                />
+                            </div>
+                            <div class="w3-quarter">
+                                <label for="connect_length">{"Connect:"}</label>
+                                <input
+                                    class="w3-input w3-border w3-round"
+                                    oninput=self.link.callback(|e: InputData| Msg::ConnectLengthChange(e.value))
+                                    type="number"
+                                    id="connect_length"
+                                    min=connect4::MIN_CONNECT.to_string()
+                                    max=connect4::MAX_SIZE.to_string()
+                                    value=self.connect.to_string()
+                                    disabled=self.game_started
+                                />
+                            </div>
+                        </div>
+                        {self.view_variant_options()}
                         <div class="w3-row-padding" style="padding:4px;">
                             <input
                                 class="w3-button w3-border w3-block"
@@ -304,12 +464,15 @@ impl Component for Connect4Computer {
                 </div>
 
                 {game_details}
+                {self.view_history_buttons()}
+                {self.view_thinking()}
+                {self.view_expert_play()}
 
                 <canvas
                     onclick=self.link.callback(|e| Msg::ClickedBoard(e))
                     id="connect4-comp-gameboard"
-                    height="480"
-                    width="640">
+                    height=self.canvas_height().to_string()
+                    width=self.canvas_width().to_string()>
                 </canvas>
                 
             </>
@@ -318,15 +481,107 @@ impl Component for Connect4Computer {
 }
 
 impl Connect4Computer {
+    // Radio buttons for the rule variant, kept apart from `view` to stay under the macro recursion limit
+    fn view_variant_options(&self) -> Html {
+        html! {
+            <div class="w3-row-padding" style="padding:4px;">
+                <div class="w3-threequarter">
+                    <label>{"Game Mode:"}</label>
+                    <div style="display:block;">
+                        <input class="w3-radio" type="radio" name="variant"
+                            value="Connect4" disabled=self.game_started checked={self.variant == Variant::Standard}
+                            oninput=self.link.callback(|e: InputData| Msg::VariantChange(e.value))/>
+                        <label style="padding:8px;">{"Standard"}</label>
+
+                        <input class="w3-radio" type="radio" name="variant"
+                            value="Connect4-PopOut" disabled=self.game_started checked={self.variant == Variant::PopOut}
+                            oninput=self.link.callback(|e: InputData| Msg::VariantChange(e.value))/>
+                        <label style="padding:8px;">{"PopOut"}</label>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    // Says whether expert solved its last move or searched for it like hard,
+    // which it does until the position can be solved in time
+    fn view_expert_play(&self) -> Html {
+        let message = match self.expert_solved {
+            Some(true) => "Expert solved this position and is playing perfectly",
+            Some(false) => "This position was too hard to solve in time, so Expert searched like Hard",
+            None => return html! {},
+        };
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <span style="padding:8px;">{message}</span>
+            </div>
+        }
+    }
+
+    // Shown while the worker searches for the computer's move
+    fn view_thinking(&self) -> Html {
+        if self.thinking.is_none() {
+            return html! {};
+        }
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <span style="padding:8px;">{"Computer is thinking\u{2026}"}</span>
+                <button class="w3-button w3-border w3-round"
+                    onclick=self.link.callback(|_| Msg::ClickedCancel)>
+                    {"Cancel"}
+                </button>
+            </div>
+        }
+    }
+
+    // Undo and Redo buttons, shown once the game has started
+    fn view_history_buttons(&self) -> Html {
+        if !self.game_started {
+            return html! {};
+        }
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.thinking.is_some() || self.state.moves().is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedUndo)>
+                    {"Undo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.thinking.is_some() || self.redo.is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedRedo)>
+                    {"Redo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    onclick=self.link.callback(|_| Msg::ClickedExport)>
+                    {if self.show_notation { "Hide Notation" } else { "Export" }}
+                </button>
+                {self.view_notation()}
+            </div>
+        }
+    }
+
+    // Text of the game so far, to copy into another tool
+    fn view_notation(&self) -> Html {
+        if !self.show_notation {
+            return html! {};
+        }
+        let mut game = self.game.clone();
+        game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
+        html! {
+            <textarea class="w3-input w3-border" style="margin-top:4px;" rows="10" readonly=true
+                value=notation::to_notation(&game) />
+        }
+    }
+
     fn draw_board(&self) {
         let context = self.context();
 
         context.save();
         context.set_fill_style(&JsValue::from_str(&self.board_color));
         context.begin_path();
-        for y in 0..6 {
+        for y in 0..self.state.rows() {
             let y = y as f64;
-            for x in 0..7 {
+            for x in 0..self.state.columns() {
                 let x = x as f64;
                 context.arc(75.0 * x + 100.0, 75.0 * y + 50.0, 25.0, 0.0, 2.0 * f64::consts::PI).unwrap();
                 context.rect(75.0 * x + 150.0, 75.0 * y, -100.0, 100.0);
@@ -334,16 +589,27 @@ impl Connect4Computer {
         }
         context.fill();
         context.restore();
+
+        if self.state.variant() == Variant::PopOut {
+            context.save();
+            context.set_font("12pt sans-serif");
+            context.set_fill_style(&JsValue::from_str("#111"));
+            for x in 0..self.state.columns() {
+                context.fill_text("Pop", 75.0 * x as f64 + 85.0, (self.board_height() + 30) as f64).unwrap();
+            }
+            context.restore();
+        }
     }
 
     fn draw(&self) {
+        let tokens = &self.state.board().tokens;
         let mut fg_color = "transparent".to_string();
-        for y in 0..6 {
-            for x in 0..7 {
+        for y in 0..self.state.rows() {
+            for x in 0..self.state.columns() {
                 fg_color = "transparent".to_string();
-                if self.board.tokens[y][x] >= 1 {
+                if tokens[y][x] >= 1 {
                     fg_color = self.player1_color.clone();
-                } else if self.board.tokens[y][x] <= -1_i8 {
+                } else if tokens[y][x] <= -1_i8 {
                     fg_color = self.computer_color.clone();
                 }
                 self.draw_circle((75 * x + 100) as f64, (75 * y + 50) as f64, 25.0, fg_color, "black".to_string());
@@ -371,6 +637,43 @@ impl Connect4Computer {
         return false;
     }
 
+    // Empty board with the chosen size and rules
+    fn new_state(&self) -> Option<Connect4State> {
+        Connect4State::with_size(self.columns, self.rows, self.connect)
+            .ok()
+            .map(|state| state.with_variant(self.variant))
+    }
+
+    // Rebuild the empty board when the size or rule settings change
+    fn resize(&mut self) {
+        if let Some(state) = self.new_state() {
+            self.state = state;
+        }
+    }
+
+    // Canvas fits the board, but stays wide enough for the result banner
+    fn canvas_width(&self) -> usize {
+        max(640, 75 * self.state.columns() + 115)
+    }
+
+    // PopOut leaves room under the board for the pop buttons
+    fn canvas_height(&self) -> usize {
+        let pop_row = if self.state.variant() == Variant::PopOut { 40 } else { 0 };
+        75 * self.state.rows() + 30 + pop_row
+    }
+
+    // Bottom edge of the drawn board
+    fn board_height(&self) -> usize {
+        75 * self.state.rows() + 25
+    }
+
+    // Repaint the board from scratch, after discs were taken off it
+    fn redraw(&self) {
+        self.clear();
+        self.draw_board();
+        self.draw();
+    }
+
     fn clear(&self) {
         let context = self.context();
         let canvas = self.canvas();
@@ -381,94 +684,39 @@ impl Connect4Computer {
 
     // }
 
-    fn action(&mut self, column: i64) -> i8{
+    fn action(&mut self, mv: Connect4Move) -> i8 {
         if self.paused || self.won {
             return 0;
         }
-        if self.board.tokens[0][column as usize] != 0 || column < 0 || column > 6 {
-            return -1;
-        }
-
-        let mut row = 0;
-        let mut done = false;
-        for i in 0..5 {
-            match self.board.tokens[i + 1][column as usize] {
-                0 => continue,
-                _=> {
-                    done = true;
-                    row = i;
-                    break;
-                }
+        let outcome = match self.state.play_move(mv) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                log!("{}", e);
+                return -1;
             }
+        };
+        if let Connect4Move::Pop(_) = mv {
+            // Every disc in the column moved, so repaint the board under them
+            self.clear();
+            self.draw_board();
         }
-        if !done {
-            row = 5;
-        }
-        // log!("Adding token to row {}", row);
-        self.board.tokens[row as usize][column as usize] = self.player_token();
-        self.move_num += 1;
         self.draw();
-        self.check();
+        match outcome {
+            MoveOutcome::Win(player) => self.win(player.token()),
+            MoveOutcome::Draw => self.win(0),
+            MoveOutcome::Continue => (),
+        }
         self.print();
-        // self.animate(column, self.moveNum, row as f64, 0.0);
-        
+
         // Set pause to true to do AI move
         self.paused = true;
         return 1;
     }
 
-    fn check(&mut self){
-        let (mut temp_r, mut temp_b, mut temp_br, mut temp_tr) = (0, 0, 0, 0);
-        for i in 0..6 {
-            for j in 0..7 {
-                temp_r = 0;
-                temp_b = 0;
-                temp_br = 0;
-                temp_tr = 0;
-                for k in 0..=3 {
-                    //from (i,j) to right
-                    if j + k < 7 {
-                        temp_r += self.board.tokens[i][j + k];
-                    }
-                    //from (i,j) to bottom
-                    if i + k < 6 {
-                        temp_b += self.board.tokens[i + k][j];
-                    }
-
-                    //from (i,j) to bottom-right
-                    if i + k < 6 && j + k < 7 {
-                        temp_br += self.board.tokens[i + k][j + k];
-                    }
-
-                    //from (i,j) to top-right
-                    if (i - k) as i8 >= 0 && j + k < 7 {
-                        temp_tr += self.board.tokens[i - k][j + k];
-                    }
-                }
-                if temp_r.abs() == 4 {
-                    self.win(temp_r);
-                } 
-                else if temp_b.abs() == 4 {
-                    self.win(temp_b);
-                } 
-                else if temp_br.abs() == 4 {
-                    self.win(temp_br);
-                } 
-                else if temp_tr.abs() == 4 {
-                    self.win(temp_tr);
-                }
-
-            }
-        }
-        // check if draw
-        if self.move_num == 42 && !self.won {
-            self.win(0);
-        }
-    }
-
     fn win(&mut self, player: i8) {
         self.paused = true;
         self.won = true;
+        self.game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
         self.game.game_date = Date::new_0().get_time() as i64; // Set date using js_sys::Date (chrono does not seem to work)
         let mut msg = "".to_string();
         if player > 0 {
@@ -496,258 +744,39 @@ impl Connect4Computer {
         log!("{}", msg);
 
         // Save game using API
-        self.get_games_list();
+        self.save_game();
     }
 
 
-    // Returns i if it is the player's token, else -1 (for computer)
-    fn player_token (&self) -> i8 {
-        if self.move_num %2 == 0 {
-            return 1;
-        } else {
-            return -1;
-        }
-    }
-
     // Print board and move number
     fn print(&self) {
         let mut msg = "".to_string();
         msg.push_str("\n");
-        msg.push_str(format!("Move: {}", self.move_num).as_str());
+        msg.push_str(format!("Move: {}", self.state.move_num()).as_str());
         msg.push_str("\n");
-        for i in 0..6 {
-            for j in 0..7 {
-                msg.push_str(format!(" {}", self.board.tokens[i][j]).as_str());
+        for i in 0..self.state.rows() {
+            for j in 0..self.state.columns() {
+                msg.push_str(format!(" {}", self.state.board().tokens[i][j]).as_str());
             }
             msg.push_str("\n");
         }
         log!("{}", msg);
     }
 
+    // Ask the worker for the computer's move; the answer arrives as `Msg::AiMoved`
     fn ai(&mut self) {
-        let curr_state = self.board.tokens.clone();
-
-        // On success, returns an array of size 2 -> index 0 gives filled map with char 'T', index 1 gives filled map with char 'O'
-        fn fill_map(state: [[i8; 7]; 6], column: i64, value: i8) -> Result<[[i8; 7]; 6], i8> {
-            if state[0][column as usize] != 0 || column < 0 || column > 6 {
-                return Err(-1);
-            }
-    
-            let mut done = false;
-            let mut row = 0;
-            for i in 0..5 {
-                if state[i + 1][column as usize] != 0 {
-                    done = true;
-                    row = i;
-                    break;
-                }
-            }
-    
-            if !done {
-                row = 5;
-            }
-    
-            let mut temp_map = state.clone();
-            temp_map[row][column as usize] = value;
-            return Ok(temp_map);
-        }
-
-        fn check_state(state: [[i8; 7]; 6]) -> [i64; 2] {
-            let mut win_val = 0;
-            let mut chain_val = 0;
-            let (mut temp_r, mut temp_b, mut temp_br, mut temp_tr) = (0, 0, 0, 0);
-            for i in 0..6 {
-                for j in 0..7 {
-                    temp_r = 0;
-                    temp_b = 0;
-                    temp_br = 0;
-                    temp_tr = 0;
-                    for k in 0..=3 {
-                        //from (i,j) to right
-                        if j + k < 7 {
-                            temp_r += state[i][j + k];
-                        }
-                        //from (i,j) to bottom
-                        if i + k < 6 {
-                            temp_b += state[i + k][j];
-                        }
-
-                        //from (i,j) to bottom-right
-                        if i + k < 6 && j + k < 7 {
-                            temp_br += state[i + k][j + k];
-                        }
-
-                        //from (i,j) to top-right
-                        if (i - k) as i8 >= 0 && j + k < 7 {
-                            temp_tr += state[i - k][j + k];
-                        }
-                    }
-
-                    chain_val += temp_r * temp_r * temp_r;
-                    chain_val += temp_b * temp_b * temp_b;
-                    chain_val += temp_br * temp_br * temp_br;
-                    chain_val += temp_tr * temp_tr * temp_tr; 
-
-                    if temp_r.abs() == 4 {
-                        win_val = temp_r;
-                    } 
-                    else if temp_b.abs() == 4 {
-                        win_val = temp_b;
-                    } 
-                    else if temp_br.abs() == 4 {
-                        win_val = temp_br;
-                    } 
-                    else if temp_tr.abs() == 4 {
-                        win_val = temp_tr;
-                    }
-
-                }
-            }
-            
-            [win_val.into(), chain_val.into()]
-        }
-        
-        fn value(state: [[i8; 7]; 6], depth: i64, max_depth: &i64, alpha: i64, beta: i64) -> [i64; 2] {
-            let val = check_state(state);
-
-            // depth changes the difficulty (less depth = easier)
-            if depth >= *max_depth {
-
-                let win_val = val[0];
-                let mut ret_value = val[1] * -1;
-
-                // If it lead to winning, then do it
-                if win_val == -4 { // AI win, AI wants to win of course
-                    ret_value = 999999; // Less value if it is later on in the game
-                } else if win_val == 4 { // AI lose, AI hates losing
-                    ret_value = -999999;
-                }
-
-                return [ret_value - depth * depth, -1];
-            }
-            
-            let win = val[0];
-            // if already won, then return the value right away
-            if win == -4 { // AI win, AI wants to win of course
-                return [999999 - depth * depth, -1];
-            } else if win == 4 { // AI lose, AI hates losing
-                return [-999999 - depth * depth, -1];
-            }
-
-            if depth % 2 == 0 {
-                return min_state(state, depth + 1, max_depth, alpha, beta);
-            }
-            return max_state(state, depth + 1, max_depth, alpha, beta);
-        }
-
-        fn choose(choice: Vec<i64>) -> i64 {
-            // Use js_sys::Math to find random value (rand does not seem to work)
-            return choice[(Math::random() * choice.len() as f64).floor() as usize]
-        }
-
-        // Returns [value, choice (column chosen)]
-        fn max_state(state: [[i8; 7]; 6], depth: i64, max_depth: &i64, alpha: i64, beta: i64) -> [i64; 2] {
-            let mut alpha = alpha;
-            let mut v = -100000000007;
-            let mut move_col = -1;
-            let mut temp_val: [i64; 2];
-            let mut move_queue = Vec::<i64>::new();
-            for j in 0..7 {
-                if let Ok(temp_state) = fill_map(state, j, -1) {
-                    temp_val = value(temp_state, depth, max_depth, alpha, beta);
-
-                    if temp_val[0] > v {
-                        v = temp_val[0];
-                        move_col = j;
-                        move_queue.clear();
-                        move_queue.push(j);
-                    } else if temp_val[0] == v {
-                        move_queue.push(j);
-                    }
-
-                    // alpha-beta pruning
-                    if v > beta {
-                        move_col = choose(move_queue);
-                        return [v, move_col];
-                    }
-                    alpha = max(alpha, v);
-
-                }
-            }
-
-            // Randomly choose from move queue
-            move_col = choose(move_queue);
-            // Return the move to make
-            [v, move_col]
-        }
-
-        // Returns [value, choice (column chosen)]
-        fn min_state(state: [[i8; 7]; 6], depth: i64, max_depth: &i64, alpha: i64, beta: i64) -> [i64; 2] {
-            let mut beta = beta;
-            let mut v = 100000000007;
-            let mut move_col = -1;
-            let mut temp_val: [i64; 2];
-            let mut move_queue = Vec::<i64>::new();
-            for j in 0..7 {
-                if let Ok(temp_state) = fill_map(state, j, 1) {
-                    temp_val = value(temp_state, depth, max_depth, alpha, beta);
-
-                    if temp_val[0] < v {
-                        v = temp_val[0];
-                        move_col = j;
-                        move_queue.clear();
-                        move_queue.push(j);
-                    } else if temp_val[0] == v {
-                        move_queue.push(j);
-                    }
-
-                    // alpha-beta pruning
-                    if v < alpha {
-                        move_col = choose(move_queue);
-                        return [v, move_col];
-                    }
-                    beta = min(beta, v);
-                }
-            }
-
-            // Randomly choose from move queue
-            move_col = choose(move_queue);
-            // Return the move to make
-            [v, move_col]
-        }
-
-        // Obtain choice and take action
-        let [val, choice] = max_state(curr_state, 0, &self.max_depth, -100000000007, 100000000007);
-        let [val2, choice2] = min_state(curr_state, 0, &self.max_depth, -100000000007, 100000000007);
-        self.action(choice);
-
-        // Print AI's move
-        log!("AI -1 choose column: {} (value: {})", choice, val);
-        log!("Max state val: {}, column: {}", val, choice);
-        log!("Min state val: {}, column: {}", val2, choice2);
-    }
-
-    fn get_games_list(&mut self) {
-        // Create GET request for list of games
-        let get_request = Request::get("http://localhost:8000/list-games")
-            .body(Nothing)
-            .unwrap();
-
-        // Create task for FetchService
-        let task = FetchService::new().fetch(
-            get_request,
-            self.link.callback(|response: Response<Json<Result<Vec<Game>, Error>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::GetGamesList(body);
-                    }
-                }
-                Msg::SaveError
-            }),
-        );
-
-        // Store reference to task
-        self.save_task = Some(task);
+        self.requests += 1;
+        self.thinking = Some(self.requests);
+        self.ai.send(ai::Request::Connect4 {
+            id: self.requests,
+            columns: self.state.columns(),
+            rows: self.state.rows(),
+            connect: self.state.connect(),
+            variant: self.state.variant(),
+            moves: self.state.moves().to_vec(),
+            difficulty: self.selected_difficulty.clone(),
+            max_depth: self.max_depth
+        });
     }
 
     fn save_game(&mut self) {
@@ -755,21 +784,27 @@ impl Connect4Computer {
         let json_game = json!{self.game};
 
         // Create POST request to save game
-        let post_request = Request::post("http://localhost:8000/insert-game")
-            .header("Content-Type", "application/json")
+        let mut post_request = Request::post(api::url("/insert-game"))
+            .header("Content-Type", "application/json");
+        // Games played under an account are only accepted from that account
+        if let Some(account) = &self.account {
+            post_request = post_request.header("Authorization", account.bearer());
+        }
+        let post_request = post_request
             .body(Json(&json_game))
             .expect("Failed to build request.");
 
         // Create save task
         let task = FetchService::new().fetch(
             post_request,
-            self.link.callback(|response: Response<Result<String, Error>>| {
+            self.link.callback(|response: Response<Json<Result<Game, Error>>>| {
                 log!("In callback function");
-                if response.status().is_success() {
-                    Msg::GameSaved
-                } else {
-                    Msg::SaveError
+                if let (meta, Json(Ok(saved))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::GameSaved(saved);
+                    }
                 }
+                Msg::SaveError
             }),
         );
 
@@ -780,22 +815,26 @@ impl Connect4Computer {
     fn reset(&mut self) {
         self.clear();
         self.game = Game {
-            game_number: 0, // placeholder, when game is saved this can be set
+            game_number: 0, // placeholder, the backend numbers the game when it is saved
             game_type: "Connect4".into(),
-            player1_name: "".into(),
+            player1_name: session::player_name(&self.account),
             player2_name: "Computer".into(),
             winner_name: "".into(),
-            game_date: 0 // placeholder, when game is saved this can be set
+            game_date: 0, // placeholder, when game is saved this can be set
+            ..Game::default()
         };
         // self.selected_difficulty = "easy".into();
         // self.board_color = "#00bfff".into();
         // self.player1_color = "#ff4136".into();
         // self.computer_color = "#ffff00".into();
         self.game_started = false;
-        self.board.tokens = [[0; 7]; 6];
-        self.move_num = 0;
+        self.state = self.new_state().unwrap_or_default();
         self.won = false;
         self.paused = false;
+        self.redo.clear();
+        self.expert_solved = None;
+        self.thinking = None;
+        self.show_notation = false;
     }
 
     fn context(&self) -> &CanvasRenderingContext2d {
@@ -3,30 +3,55 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window};
 use yew::services::fetch::{Request, Response, FetchService, FetchTask};
-use yew::format::{Json, Nothing};
+use yew::services::websocket::WebSocketStatus;
+use yew::format::Json;
 use anyhow::Error;
 use serde_json::json;
 use js_sys::Date;
-use crate::models::game_boards::Connect4GameBoard;
+use crate::models::notation;
+use crate::models::account::Session;
+use crate::components::utils::{api, session};
+use crate::models::online::{ClientMessage, ServerMessage};
+use crate::models::player::Player;
+use crate::components::utils::online::RoomConnection;
+use crate::models::connect4::{self, Connect4Move, Connect4State, MoveOutcome, Variant};
 use std::f64;
+use std::cmp::max;
 use crate::alert;
 
 extern crate models;
 use models::game::Game;
 
+// Half the spacing between columns, so a click anywhere over the board picks the nearest column
+const CLICK_RADIUS: f64 = 37.5;
+
 pub struct Connect4Human {
     link: ComponentLink<Self>,
     game: Game,
     game_started: bool,
     context: Option<CanvasRenderingContext2d>,
-    board: Connect4GameBoard,
-    move_num: u8,
+    state: Connect4State,
+    columns: usize,
+    rows: usize,
+    connect: usize,
+    variant: Variant,
     won: bool,
     paused: bool,
     save_task: Option<Result<FetchTask, Error>>,
+    // Account logged in on this browser, which plays as Player 1
+    account: Option<Session>,
     player1_color: String,
     player2_color: String,
-    board_color: String
+    board_color: String,
+    // Moves taken back by Undo, most recent last
+    redo: Vec<Connect4Move>,
+    // Notation of the game so far is shown under the board
+    show_notation: bool,
+    // Connection to the game room while playing online, and the side played here
+    room: Option<RoomConnection>,
+    online_player: Option<Player>,
+    room_code: String,
+    online_status: String
 }
 
 macro_rules! log {
@@ -43,12 +68,24 @@ pub enum Msg {
     GotPlayer2Input(String),
     ClickedStart,
     ClickedBoard(MouseEvent),
-    GetGamesList(Vec<Game>),
-    GameSaved,
+    GameSaved(Game),
     SaveError,
     Player1ColorChange(String),
     Player2ColorChange(String),
-    BoardColorChange(String)
+    BoardColorChange(String),
+    BoardWidthChange(String),
+    BoardHeightChange(String),
+    ConnectLengthChange(String),
+    VariantChange(String),
+    ClickedUndo,
+    ClickedRedo,
+    ClickedExport,
+    RoomCodeInput(String),
+    ClickedCreateRoom,
+    ClickedJoinRoom,
+    RoomStatus(WebSocketStatus),
+    RoomMessage(Result<ServerMessage, Error>),
+    Redraw
 }
 
 impl Component for Connect4Human {
@@ -56,30 +93,38 @@ impl Component for Connect4Human {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let account = session::current();
         Connect4Human {
             link,
             game: Game {
-                game_number: 0, // placeholder, when game is saved this can be set
+                game_number: 0, // placeholder, the backend numbers the game when it is saved
                 game_type: "Connect4".into(),
-                player1_name: "".into(),
+                player1_name: session::player_name(&account),
                 player2_name: "".into(),
                 winner_name: "".into(),
-                game_date: 0 // placeholder, when game is saved this can be set
+                game_date: 0, // placeholder, when game is saved this can be set
+                ..Game::default()
             },
             game_started: false,
             context: None,
-            board: Connect4GameBoard {
-                rows: 6,
-                columns: 7,
-                tokens: [[0; 7]; 6],
-            },
-            move_num: 0,
+            state: Connect4State::new(),
+            columns: connect4::COLUMNS,
+            rows: connect4::ROWS,
+            connect: connect4::CONNECT,
+            variant: Variant::Standard,
             won: false,
             paused: false,
             save_task: None,
+            account,
             player1_color: "#ff4136".into(),
             player2_color: "#ffff00".into(),
             board_color: "#00bfff".into(),
+            redo: Vec::new(),
+            show_notation: false,
+            room: None,
+            online_player: None,
+            room_code: String::new(),
+            online_status: String::new()
         }
     }
 
@@ -103,7 +148,16 @@ impl Component for Connect4Human {
                     alert("Computer is a reserved name!");
                 } else if self.game.player1_name == self.game.player2_name {
                     alert("Player names must be different!");
+                } else if let Err(e) = Connect4State::with_size(self.columns, self.rows, self.connect) {
+                    alert(&e.to_string());
                 } else {
+                    self.game.game_type = self.variant.game_type().into();
+                    // Settings are saved with the game so it can be replayed
+                    self.game.columns = self.columns as i32;
+                    self.game.rows = self.rows as i32;
+                    self.game.connect = self.connect as i32;
+                    self.game.player1_color = self.player1_color.clone();
+                    self.game.player2_color = self.player2_color.clone();
                     self.game_started = true;
                     self.draw_board();
                     self.print();
@@ -123,27 +177,35 @@ impl Component for Connect4Human {
                 let y = event.client_y() as f64 - rect.top();
                 // log!("x: {} y: {}", x,y);
 
-                for i in 0..7 {
-                    if self.on_region([x, y], (75 * i + 100) as f64, 25.0){
+                // In PopOut, clicks below the board pop a disc instead of dropping one
+                let popping = self.state.variant() == Variant::PopOut && y > self.board_height() as f64;
+                for i in 0..self.state.columns() {
+                    if self.on_region([x, y], (75 * i + 100) as f64, CLICK_RADIUS){
                         // log!("Region {} clicked", i);
                         self.paused = false;
-                        let valid = self.action(i as f64);
+                        let mv = if popping { Connect4Move::Pop(i) } else { Connect4Move::Drop(i) };
+                        if let Some(player) = self.online_player {
+                            // Online moves are played once the server sends them back
+                            if self.state.current_player() == player {
+                                if let Some(room) = self.room.as_mut() {
+                                    room.send(&ClientMessage::Move(mv.into()));
+                                }
+                            }
+                            break;
+                        }
+                        let valid = self.action(mv);
                         if valid == 1 {
-                            //Reject Click
+                            // A new move makes the undone ones unreachable
+                            self.redo.clear();
                         }
                         break; //because there will be no 2 points that are clicked at a time
                     }
                     
                 }
             },
-            Msg::GetGamesList(games) => {
-                log!("Obtained games list");
-                self.game.game_number = games.len() as i32 + 1;
-                self.save_task = None;
-                self.save_game();
-            },
-            Msg::GameSaved => {
-                log!("Successfully saved");
+            Msg::GameSaved(saved) => {
+                log!("Successfully saved as game {}", saved.game_number);
+                self.game.game_number = saved.game_number;
                 self.save_task = None;
             },
             Msg::SaveError => log!("Game failed to save"),
@@ -156,6 +218,101 @@ impl Component for Connect4Human {
             Msg::BoardColorChange(new_value) => {
                 self.board_color = new_value;
             },
+            Msg::BoardWidthChange(new_value) => {
+                if let Ok(columns) = new_value.parse() {
+                    self.columns = columns;
+                    self.resize();
+                }
+            },
+            Msg::BoardHeightChange(new_value) => {
+                if let Ok(rows) = new_value.parse() {
+                    self.rows = rows;
+                    self.resize();
+                }
+            },
+            Msg::ConnectLengthChange(new_value) => {
+                if let Ok(connect) = new_value.parse() {
+                    self.connect = connect;
+                    self.resize();
+                }
+            },
+            Msg::VariantChange(new_value) => {
+                if let Some(variant) = Variant::from_game_type(&new_value) {
+                    self.variant = variant;
+                    self.resize();
+                }
+            },
+            Msg::ClickedUndo => {
+                if self.won {
+                    return false;
+                }
+                if let Some(mv) = self.state.undo() {
+                    self.redo.push(mv);
+                    self.paused = false;
+                    self.redraw();
+                }
+            },
+            Msg::ClickedExport => self.show_notation = !self.show_notation,
+            Msg::RoomCodeInput(new_value) => {
+                self.room_code = new_value;
+            },
+            Msg::ClickedCreateRoom => {
+                if self.game.player1_name.is_empty() {
+                    alert("Player names can't be blank!");
+                } else if self.game.player1_name == "Computer" {
+                    alert("Computer is a reserved name!");
+                } else if let Err(e) = Connect4State::with_size(self.columns, self.rows, self.connect) {
+                    alert(&e.to_string());
+                } else {
+                    self.open_room(ClientMessage::Create {
+                        name: self.game.player1_name.clone(),
+                        game_type: self.variant.game_type().into(),
+                        columns: self.columns as i32,
+                        rows: self.rows as i32,
+                        connect: self.connect as i32,
+                        token: self.account.as_ref().map(|account| account.token.clone())
+                    });
+                }
+            },
+            Msg::ClickedJoinRoom => {
+                if self.game.player1_name.is_empty() {
+                    alert("Player names can't be blank!");
+                } else if self.game.player1_name == "Computer" {
+                    alert("Computer is a reserved name!");
+                } else if self.room_code.trim().is_empty() {
+                    alert("Enter the code of the room to join!");
+                } else {
+                    self.open_room(ClientMessage::Join {
+                        code: self.room_code.clone(),
+                        name: self.game.player1_name.clone(),
+                        token: self.account.as_ref().map(|account| account.token.clone())
+                    });
+                }
+            },
+            Msg::RoomStatus(status) => match status {
+                WebSocketStatus::Opened => {
+                    if let Some(room) = self.room.as_mut() {
+                        room.opened();
+                    }
+                },
+                WebSocketStatus::Closed | WebSocketStatus::Error => {
+                    if self.room.take().is_some() && !self.won {
+                        alert("Lost the connection to the game server");
+                        self.reset();
+                    }
+                },
+            },
+            Msg::RoomMessage(message) => self.room_message(message),
+            Msg::Redraw => self.redraw(),
+            Msg::ClickedRedo => {
+                if self.won {
+                    return false;
+                }
+                if let Some(mv) = self.redo.pop() {
+                    self.paused = false;
+                    self.action(mv);
+                }
+            },
         }
         true
     }
@@ -211,7 +368,7 @@ impl Component for Connect4Human {
                                     class="w3-input w3-border w3-round"
                                     id="nameInput1"
                                     type="text"
-                                    disabled=self.game_started
+                                    disabled=self.game_started || self.account.is_some()
                                     value=&self.game.player1_name
                                     oninput=self.link.callback(|e: InputData| Msg::GotPlayer1Input(e.value))
                                     placeholder="Enter name"
@@ -267,11 +424,53 @@ impl Component for Connect4Human {
                                 />
                             </div>  
                         </div>
+                        <div class="w3-row-padding" style="padding:4px;">
+                            <div class="w3-quarter">
+                                <label for="board_width">{"Board Width:"}</label>
+                                <input
+                                    class="w3-input w3-border w3-round"
+                                    oninput=self.link.callback(|e: InputData| Msg::BoardWidthChange(e.value))
+                                    type="number"
+                                    id="board_width"
+                                    min=connect4::MIN_SIZE.to_string()
+                                    max=connect4::MAX_SIZE.to_string()
+                                    value=self.columns.to_string()
+                                    disabled=self.game_started
+                                />
+                            </div>
+                            <div class="w3-quarter">
+                                <label for="board_height">{"Board Height:"}</label>
+                                <input
+                                    class="w3-input w3-border w3-round"
+                                    oninput=self.link.callback(|e: InputData| Msg::BoardHeightChange(e.value))
+                                    type="number"
+                                    id="board_height"
+                                    min=connect4::MIN_SIZE.to_string()
+                                    max=connect4::MAX_SIZE.to_string()
+                                    value=self.rows.to_string()
+                                    disabled=self.game_started
+                                />
+                            </div>
+                            <div class="w3-quarter">
+                                <label for="connect_length">{"Connect:"}</label>
+                                <input
+                                    class="w3-input w3-border w3-round"
+                                    oninput=self.link.callback(|e: InputData| Msg::ConnectLengthChange(e.value))
+                                    type="number"
+                                    id="connect_length"
+                                    min=connect4::MIN_CONNECT.to_string()
+                                    max=connect4::MAX_SIZE.to_string()
+                                    value=self.connect.to_string()
+                                    disabled=self.game_started
+                                />
+                            </div>
+                        </div>
+                        {self.view_variant_options()}
                         <div class="w3-row-padding" style="padding:4px;">
                             <input
                                 class="w3-button w3-border w3-block"
                                 id="startButton"
-                                disabled=self.game_started
+                                disabled=self.game_started || self.room.is_some()
                                 type="submit"
                                 value="Start Game"
                             />
@@ -279,13 +478,15 @@ impl Component for Connect4Human {
                     </form>
                 </div>
 
+                {self.view_online_options()}
                 {game_details}
+                {self.view_history_buttons()}
 
                 <canvas
                     onclick=self.link.callback(|e| Msg::ClickedBoard(e))
                     id="connect4-human-gameboard"
-                    height="480"
-                    width="640">
+                    height=self.canvas_height().to_string()
+                    width=self.canvas_width().to_string()>
                 </canvas>
                 
             </>
@@ -294,15 +495,107 @@ impl Component for Connect4Human {
 }
 
 impl Connect4Human {
+    // Radio buttons for the rule variant, kept apart from `view` to stay under the macro recursion limit
+    fn view_variant_options(&self) -> Html {
+        html! {
+            <div class="w3-row-padding" style="padding:4px;">
+                <div class="w3-threequarter">
+                    <label>{"Game Mode:"}</label>
+                    <div style="display:block;">
+                        <input class="w3-radio" type="radio" name="variant"
+                            value="Connect4" disabled=self.game_started checked={self.variant == Variant::Standard}
+                            oninput=self.link.callback(|e: InputData| Msg::VariantChange(e.value))/>
+                        <label style="padding:8px;">{"Standard"}</label>
+
+                        <input class="w3-radio" type="radio" name="variant"
+                            value="Connect4-PopOut" disabled=self.game_started checked={self.variant == Variant::PopOut}
+                            oninput=self.link.callback(|e: InputData| Msg::VariantChange(e.value))/>
+                        <label style="padding:8px;">{"PopOut"}</label>
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    // Create or join a room to play someone on another browser
+    fn view_online_options(&self) -> Html {
+        let busy = self.game_started || self.room.is_some();
+        html! {
+            <div class="w3-container">
+                <h5 class="w3-xlarge w3-text-red"><b>{"Play Online"}</b></h5>
+                <p>{"Create a room with the settings above and share its code, or join a room with the code you were given. You play under the Player 1 name."}</p>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <div class="w3-quarter">
+                        <button class="w3-button w3-border w3-block" disabled=busy
+                            onclick=self.link.callback(|_| Msg::ClickedCreateRoom)>
+                            {"Create Room"}
+                        </button>
+                    </div>
+                    <div class="w3-quarter">
+                        <input class="w3-input w3-border w3-round" type="text" placeholder="Room code"
+                            value=&self.room_code disabled=busy
+                            oninput=self.link.callback(|e: InputData| Msg::RoomCodeInput(e.value))/>
+                    </div>
+                    <div class="w3-quarter">
+                        <button class="w3-button w3-border w3-block" disabled=busy
+                            onclick=self.link.callback(|_| Msg::ClickedJoinRoom)>
+                            {"Join Room"}
+                        </button>
+                    </div>
+                </div>
+                <p>{&self.online_status}</p>
+            </div>
+        }
+    }
+
+    // Undo and Redo buttons, shown once the game has started
+    fn view_history_buttons(&self) -> Html {
+        if !self.game_started {
+            return html! {};
+        }
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.online_player.is_some() || self.state.moves().is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedUndo)>
+                    {"Undo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.online_player.is_some() || self.redo.is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedRedo)>
+                    {"Redo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    onclick=self.link.callback(|_| Msg::ClickedExport)>
+                    {if self.show_notation { "Hide Notation" } else { "Export" }}
+                </button>
+                {self.view_notation()}
+            </div>
+        }
+    }
+
+    // Text of the game so far, to copy into another tool
+    fn view_notation(&self) -> Html {
+        if !self.show_notation {
+            return html! {};
+        }
+        let mut game = self.game.clone();
+        game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
+        html! {
+            <textarea class="w3-input w3-border" style="margin-top:4px;" rows="10" readonly=true
+                value=notation::to_notation(&game) />
+        }
+    }
+
     fn draw_board(&self) {
         let context = self.context();
 
         context.save();
         context.set_fill_style(&JsValue::from_str(&self.board_color));
         context.begin_path();
-        for y in 0..6 {
+        for y in 0..self.state.rows() {
             let y = y as f64;
-            for x in 0..7 {
+            for x in 0..self.state.columns() {
                 let x = x as f64;
                 context.arc(75.0 * x + 100.0, 75.0 * y + 50.0, 25.0, 0.0, 2.0 * f64::consts::PI).unwrap();
                 context.rect(75.0 * x + 150.0, 75.0 * y, -100.0, 100.0);
@@ -310,16 +603,27 @@ impl Connect4Human {
         }
         context.fill();
         context.restore();
+
+        if self.state.variant() == Variant::PopOut {
+            context.save();
+            context.set_font("12pt sans-serif");
+            context.set_fill_style(&JsValue::from_str("#111"));
+            for x in 0..self.state.columns() {
+                context.fill_text("Pop", 75.0 * x as f64 + 85.0, (self.board_height() + 30) as f64).unwrap();
+            }
+            context.restore();
+        }
     }
 
     fn draw(&self) {
+        let tokens = &self.state.board().tokens;
         let mut fg_color = "transparent".to_string();
-        for y in 0..6 {
-            for x in 0..7 {
+        for y in 0..self.state.rows() {
+            for x in 0..self.state.columns() {
                 fg_color = "transparent".to_string();
-                if self.board.tokens[y][x] >= 1 {
+                if tokens[y][x] >= 1 {
                     fg_color = self.player1_color.clone();
-                } else if self.board.tokens[y][x] <= -1_i8 {
+                } else if tokens[y][x] <= -1_i8 {
                     fg_color = self.player2_color.clone();
                 }
                 self.draw_circle((75 * x + 100) as f64, (75 * y + 50) as f64, 25.0, fg_color, "black".to_string());
@@ -349,8 +653,8 @@ impl Connect4Human {
         context.save();
         context.set_fill_style(&JsValue::from_str(&"#00bfff"));
         context.begin_path();
-        for y in 0..6 {
-            for x in 0..7 {
+        for y in 0..self.state.rows() {
+            for x in 0..self.state.columns() {
                 context.arc(75.0 * x as f64 + 100.0, 75.0 * y as f64 + 50.0, 25.0, 0.0, 2.0 * f64::consts::PI);
                 context.rect(75.0 * x as f64 + 150.0, 75.0 * y as f64, -100.0, 100.0);
             }
@@ -366,6 +670,43 @@ impl Connect4Human {
         return false;
     }
 
+    // Empty board with the chosen size and rules
+    fn new_state(&self) -> Option<Connect4State> {
+        Connect4State::with_size(self.columns, self.rows, self.connect)
+            .ok()
+            .map(|state| state.with_variant(self.variant))
+    }
+
+    // Rebuild the empty board when the size or rule settings change
+    fn resize(&mut self) {
+        if let Some(state) = self.new_state() {
+            self.state = state;
+        }
+    }
+
+    // Canvas fits the board, but stays wide enough for the result banner
+    fn canvas_width(&self) -> usize {
+        max(640, 75 * self.state.columns() + 115)
+    }
+
+    // PopOut leaves room under the board for the pop buttons
+    fn canvas_height(&self) -> usize {
+        let pop_row = if self.state.variant() == Variant::PopOut { 40 } else { 0 };
+        75 * self.state.rows() + 30 + pop_row
+    }
+
+    // Bottom edge of the drawn board
+    fn board_height(&self) -> usize {
+        75 * self.state.rows() + 25
+    }
+
+    // Repaint the board from scratch, after discs were taken off it
+    fn redraw(&self) {
+        self.clear();
+        self.draw_board();
+        self.draw();
+    }
+
     fn clear(&self) {
         let context = self.context();
         let canvas = self.canvas();
@@ -395,93 +736,38 @@ impl Connect4Human {
     //     }
     // }
 
-    fn action(&mut self, column: f64) -> i8{
+    fn action(&mut self, mv: Connect4Move) -> i8 {
         if self.paused || self.won {
             return 0;
         }
-        if self.board.tokens[0][column as usize] != 0 || column < 0.0 || column > 6.0 {
-            return -1;
-        }
-
-        let mut row = 0;
-        let mut done = false;
-        for i in 0..5 {
-            match self.board.tokens[i + 1][column as usize] {
-                0 => continue,
-                _=> {
-                    done = true;
-                    row = i;
-                    break;
-                }
+        let outcome = match self.state.play_move(mv) {
+            Ok(outcome) => outcome,
+            Err(e) => {
+                log!("{}", e);
+                return -1;
             }
+        };
+        if let Connect4Move::Pop(_) = mv {
+            // Every disc in the column moved, so repaint the board under them
+            self.clear();
+            self.draw_board();
         }
-        if !done {
-            row = 5;
-        }
-        // log!("Adding token to row {}", row);
-        self.board.tokens[row as usize][column as usize] = self.player_token();
-        self.move_num += 1;
         self.draw();
-        self.check();
+        match outcome {
+            MoveOutcome::Win(player) => self.win(player.token()),
+            MoveOutcome::Draw => self.win(0),
+            MoveOutcome::Continue => (),
+        }
         self.print();
-        // self.animate(column, row as u8);
-        
+
         self.paused = true;
         return 1;
     }
 
-    fn check(&mut self) {
-        let (mut temp_r, mut temp_b, mut temp_br, mut temp_tr) = (0, 0, 0, 0);
-        for i in 0..6 {
-            for j in 0..7 {
-                temp_r = 0;
-                temp_b = 0;
-                temp_br = 0;
-                temp_tr = 0;
-                for k in 0..=3 {
-                    //from (i,j) to right
-                    if j + k < 7 {
-                        temp_r += self.board.tokens[i][j + k];
-                    }
-                    //from (i,j) to bottom
-                    if i + k < 6 {
-                        temp_b += self.board.tokens[i + k][j];
-                    }
-
-                    //from (i,j) to bottom-right
-                    if i + k < 6 && j + k < 7 {
-                        temp_br += self.board.tokens[i + k][j + k];
-                    }
-
-                    //from (i,j) to top-right
-                    if (i - k) as i8 >= 0 && j + k < 7 {
-                        temp_tr += self.board.tokens[i - k][j + k];
-                    }
-                }
-                if temp_r.abs() == 4 {
-                    self.win(temp_r);
-                } 
-                else if temp_b.abs() == 4 {
-                    self.win(temp_b);
-                } 
-                else if temp_br.abs() == 4 {
-                    self.win(temp_br);
-                } 
-                else if temp_tr.abs() == 4 {
-                    self.win(temp_tr);
-                }
-
-            }
-        }
-        // check if draw
-        if self.move_num == 42 && !self.won {
-            self.win(0);
-        }
-    }
-
     fn win(&mut self, player: i8) {
         self.paused = true;
         self.won = true;
+        self.game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
         self.game.game_date = Date::new_0().get_time() as i64; // Set date using js_sys::Date (chrono does not seem to work)
         let mut msg = "".to_string();
         if player > 0 {
@@ -508,16 +794,9 @@ impl Connect4Human {
         // Print final state
         log!("{}", msg);
 
-        // Save game using API
-        self.get_games_list();
-    }
-
-    // Returns i if it is the player's token, else -1 (for computer)
-    fn player_token(&self) -> i8 {
-        if self.move_num %2 == 0 {
-            return 1;
-        } else {
-            return -1;
+        // Save game using API, online games are saved by the room server
+        if self.online_player.is_none() {
+            self.save_game();
         }
     }
 
@@ -525,60 +804,43 @@ impl Connect4Human {
     fn print(&self) {
         let mut msg = "".to_string();
         msg.push_str("\n");
-        msg.push_str(format!("Move: {}", self.move_num).as_str());
+        msg.push_str(format!("Move: {}", self.state.move_num()).as_str());
         msg.push_str("\n");
-        for i in 0..6 {
-            for j in 0..7 {
-                msg.push_str(format!(" {}", self.board.tokens[i][j]).as_str());
+        for i in 0..self.state.rows() {
+            for j in 0..self.state.columns() {
+                msg.push_str(format!(" {}", self.state.board().tokens[i][j]).as_str());
             }
             msg.push_str("\n");
         }
         log!("{}", msg);
     }
 
-    fn get_games_list(&mut self) {
-        // Create GET request for list of games
-        let get_request = Request::get("http://localhost:8000/list-games")
-            .body(Nothing)
-            .unwrap();
-
-        // Create task for FetchService
-        let task = FetchService::new().fetch(
-            get_request,
-            self.link.callback(|response: Response<Json<Result<Vec<Game>, Error>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::GetGamesList(body);
-                    }
-                }
-                Msg::SaveError
-            }),
-        );
-
-        // Store reference to task
-        self.save_task = Some(task);
-    }
-
     fn save_game(&mut self) {
         // Create JSON representation of game to save
         let json_game = json!{self.game};
 
         // Create POST request to save game
-        let post_request = Request::post("http://localhost:8000/insert-game")
-            .header("Content-Type", "application/json")
+        let mut post_request = Request::post(api::url("/insert-game"))
+            .header("Content-Type", "application/json");
+        // Games played under an account are only accepted from that account
+        if let Some(account) = &self.account {
+            post_request = post_request.header("Authorization", account.bearer());
+        }
+        let post_request = post_request
             .body(Json(&json_game))
             .expect("Failed to build request.");
 
         // Create save task
         let task = FetchService::new().fetch(
             post_request,
-            self.link.callback(|response: Response<Result<String, Error>>| {
+            self.link.callback(|response: Response<Json<Result<Game, Error>>>| {
                 log!("In callback function");
-                if response.status().is_success() {
-                    Msg::GameSaved
-                } else {
-                    Msg::SaveError
+                if let (meta, Json(Ok(saved))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::GameSaved(saved);
+                    }
                 }
+                Msg::SaveError
             }),
         );
 
@@ -586,21 +848,93 @@ impl Connect4Human {
         self.save_task = Some(task);
     }
 
+    fn open_room(&mut self, message: ClientMessage) {
+        let received = self.link.callback(Msg::RoomMessage);
+        let status = self.link.callback(Msg::RoomStatus);
+        match RoomConnection::open(message, received, status) {
+            Ok(room) => {
+                self.room = Some(room);
+                self.online_status = "Connecting to the game server...".into();
+            },
+            Err(e) => alert(&format!("Cannot reach the game server: {}", e)),
+        }
+    }
+
+    fn room_message(&mut self, message: Result<ServerMessage, Error>) {
+        match message {
+            Ok(ServerMessage::Created { code }) => {
+                self.online_status = format!("Room {} is open, share the code with your opponent", code);
+                self.room_code = code;
+            },
+            Ok(ServerMessage::Started { game, you }) => self.start_online(game, you),
+            Ok(ServerMessage::Moved(mv)) => {
+                let column = mv.column as usize;
+                let mv = if mv.pop { Connect4Move::Pop(column) } else { Connect4Move::Drop(column) };
+                self.paused = false;
+                self.action(mv);
+            },
+            Ok(ServerMessage::Rejected { reason }) => {
+                alert(&reason);
+                // A room that could not be opened or joined is no use
+                if !self.game_started {
+                    self.room = None;
+                    self.online_status.clear();
+                }
+            },
+            Ok(ServerMessage::OpponentLeft) => {
+                self.room = None;
+                if !self.won {
+                    alert("Your opponent left the game");
+                    self.reset();
+                }
+            },
+            Err(e) => log!("Unreadable message from the game server: {}", e),
+        }
+    }
+
+    // Both players are in the room, so take its settings and start
+    fn start_online(&mut self, game: Game, you: Player) {
+        self.variant = Variant::from_game_type(&game.game_type).unwrap_or(Variant::Standard);
+        self.columns = game.columns as usize;
+        self.rows = game.rows as usize;
+        self.connect = game.connect as usize;
+        self.resize();
+        self.game = Game {
+            player1_color: self.player1_color.clone(),
+            player2_color: self.player2_color.clone(),
+            ..game
+        };
+        self.online_player = Some(you);
+        self.online_status = match you {
+            Player::One => "Playing online as Player 1, you move first".into(),
+            Player::Two => "Playing online as Player 2".into(),
+        };
+        self.game_started = true;
+        // Resizing the canvas on render clears it, so draw once that is done
+        self.link.send_message(Msg::Redraw);
+    }
+
     fn reset(&mut self) {
         self.clear();
         self.game = Game {
-            game_number: 0, // placeholder, when game is saved this can be set
+            game_number: 0, // placeholder, the backend numbers the game when it is saved
             game_type: "Connect4".into(),
-            player1_name: "".into(),
+            player1_name: session::player_name(&self.account),
             player2_name: "".into(),
             winner_name: "".into(),
-            game_date: 0 // placeholder, when game is saved this can be set
+            game_date: 0, // placeholder, when game is saved this can be set
+            ..Game::default()
         };
         self.game_started = false;
-        self.board.tokens = [[0; 7]; 6];
-        self.move_num = 0;
+        self.state = self.new_state().unwrap_or_default();
         self.won = false;
         self.paused = false;
+        self.redo.clear();
+        self.show_notation = false;
+        self.room = None;
+        self.online_player = None;
+        self.room_code.clear();
+        self.online_status.clear();
     }
 
     fn context(&self) -> &CanvasRenderingContext2d {
@@ -1,6 +1,8 @@
 use yew::prelude::*;
+use super::account::Account;
 use super::game_history::GameHistory;
 use super::score_board::ScoreBoard;
+use super::player_stats::PlayerStats;
 use super::connect4::{computer::Connect4Computer, human::Connect4Human};
 use super::toot_otto::{human::TootOttoHuman, computer::TootOttoComputer};
 
@@ -15,6 +17,8 @@ pub enum Tab {
     TootOttoHuman,
     ScoreBoard,
     Scores,
+    PlayerStats,
+    Account,
 }
 
 pub struct Content {
@@ -91,7 +95,9 @@ fn get_tab_html(tab: &Tab) -> Html {
         Tab::TootOttoComputer => html!{ <TootOttoComputer /> },
         Tab::TootOttoHuman => html!{ <TootOttoHuman /> },
         Tab::ScoreBoard => html!{<GameHistory />},
-        Tab::Scores => html!{<ScoreBoard />}
+        Tab::Scores => html!{<ScoreBoard />},
+        Tab::PlayerStats => html!{<PlayerStats />},
+        Tab::Account => html!{<Account />}
     }
 }
 
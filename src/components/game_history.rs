@@ -4,20 +4,56 @@ use yew::format::{Nothing, Json};
 // use yew::services::console::ConsoleService;
 use anyhow::Error;
 use crate::models::game::Game;
-use super::utils::table_builder;
+use crate::models::listing::{GamePage, GameQuery, SortOrder};
+use crate::models::notation;
+use super::utils::{api, table_builder};
+use super::replay::Replay;
+
+// Games on each page of the table
+const PAGE_SIZE: i64 = 10;
+// A day in milliseconds, so the last day of a date range counts in full
+const DAY: f64 = 86_400_000.0;
 
 pub struct GameHistory {
+    // Games on the page shown, and the search they came from
     games: Vec<Game>,
+    listing: GamePage,
+    query: GameQuery,
+    // Filters being typed, applied by Search
+    game_type_filter: String,
+    player_filter: String,
+    winner_filter: String,
+    from_date: String,
+    to_date: String,
+    sort: SortOrder,
     link: ComponentLink<Self>,
     get_games_task: Result<FetchTask, Error>, // Important to keep in scope!!
+    // Game being replayed, from the table or pasted in
+    replaying: Option<Game>,
+    import_text: String,
+    import_error: Option<String>
 }
 
 #[derive(Properties, Clone, PartialEq)]
 pub struct Props {}
 
 pub enum Msg {
-    FetchResourceComplete(Vec<Game>),
-    FetchResourceFailed
+    FetchResourceComplete(GamePage),
+    FetchResourceFailed,
+    GameTypeFilter(String),
+    PlayerFilter(String),
+    WinnerFilter(String),
+    FromDate(String),
+    ToDate(String),
+    SortChange(String),
+    ClickedSearch,
+    ClickedClearFilters,
+    ClickedPreviousPage,
+    ClickedNextPage,
+    SelectedGame(usize),
+    ClosedReplay,
+    ImportInput(String),
+    ClickedImport
 }
 
 impl Component for GameHistory {
@@ -25,41 +61,77 @@ impl Component for GameHistory {
     type Properties = Props;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
-        // Create GET request for list of games
-        let get_request = Request::get("http://localhost:8000/list-games")
-            .body(Nothing)
-            .unwrap();
-
-        // Create task for FetchService
-        let task = FetchService::new().fetch(
-            get_request,
-            link.callback(|response: Response<Json<Result<Vec<Game>, Error>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::FetchResourceComplete(body);
-                    }
-                }
-                Msg::FetchResourceFailed
-            }),
-        );
+        let query = GameQuery { page_size: Some(PAGE_SIZE), ..GameQuery::default() };
+        let task = fetch_games(&link, &query);
 
         GameHistory {
             games: Vec::<Game>::new(),
+            listing: GamePage::default(),
+            query,
+            game_type_filter: String::new(),
+            player_filter: String::new(),
+            winner_filter: String::new(),
+            from_date: String::new(),
+            to_date: String::new(),
+            sort: SortOrder::Newest,
             link: link,
-            get_games_task: task // Note: Reference to task needs to be stored for the duration of the request (https://github.com/yewstack/yew/issues/388)
+            get_games_task: task, // Note: Reference to task needs to be stored for the duration of the request (https://github.com/yewstack/yew/issues/388)
+            replaying: None,
+            import_text: String::new(),
+            import_error: None
         }
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::FetchResourceComplete(games) => {
-                for game in games {
-                    self.games.push(game);
-                }
+            Msg::FetchResourceComplete(mut listing) => {
+                self.games = std::mem::take(&mut listing.games);
+                self.listing = listing;
                 return true;
             },
             Msg::FetchResourceFailed => return false,
+            Msg::GameTypeFilter(value) => self.game_type_filter = value,
+            Msg::PlayerFilter(value) => self.player_filter = value,
+            Msg::WinnerFilter(value) => self.winner_filter = value,
+            Msg::FromDate(value) => self.from_date = value,
+            Msg::ToDate(value) => self.to_date = value,
+            Msg::SortChange(value) => self.sort = SortOrder::from_name(&value).unwrap_or_default(),
+            Msg::ClickedSearch => self.search(),
+            Msg::ClickedClearFilters => {
+                self.game_type_filter.clear();
+                self.player_filter.clear();
+                self.winner_filter.clear();
+                self.from_date.clear();
+                self.to_date.clear();
+                self.sort = SortOrder::Newest;
+                self.search();
+            },
+            Msg::ClickedPreviousPage => self.show_page(self.query.page() - 1),
+            Msg::ClickedNextPage => self.show_page(self.query.page() + 1),
+            Msg::SelectedGame(index) => {
+                self.replaying = self.games.get(index).cloned();
+                return true;
+            },
+            Msg::ClosedReplay => {
+                self.replaying = None;
+                return true;
+            },
+            Msg::ImportInput(text) => {
+                self.import_text = text;
+                return false;
+            },
+            Msg::ClickedImport => {
+                match notation::from_notation(&self.import_text) {
+                    Ok(game) => {
+                        self.replaying = Some(game);
+                        self.import_error = None;
+                    },
+                    Err(error) => self.import_error = Some(error.to_string()),
+                }
+                return true;
+            },
         }
+        true
     }
 
     fn view(&self) -> Html {
@@ -68,6 +140,9 @@ impl Component for GameHistory {
                 <h5 class="w3-xxxlarge w3-text-red"><b>{"Game History"}</b></h5>
                 <hr style="width:50px;border:5px solid red" class="w3-round" />
                 
+                {self.view_replay()}
+                {self.view_import()}
+                {self.view_filters()}
                 <div id="game-stream">
                     <table>
                         <tr>
@@ -78,10 +153,190 @@ impl Component for GameHistory {
                             <th>{"Winner"}</th>
                             <th>{"When Played"}</th>
                         </tr>
-                        { table_builder::render_gh_table(&self.games) }
+                        { table_builder::render_gh_table(&self.games, self.query.skip() as usize, &self.link.callback(Msg::SelectedGame)) }
                     </table>
                 </div>
+                {self.view_pager()}
+            </div>
+        }
+    }
+}
+
+impl GameHistory {
+    // Replay of the clicked game, above the table
+    fn view_replay(&self) -> Html {
+        match &self.replaying {
+            Some(game) => html! {
+                <div class="w3-container" style="margin-bottom:20px;">
+                    <Replay game=game.clone() />
+                    <button class="w3-button w3-border w3-round" onclick=self.link.callback(|_| Msg::ClosedReplay)>
+                        {"Close Replay"}
+                    </button>
+                </div>
+            },
+            None => html! {
+                <p>{"Click a game to replay it, or paste one in below."}</p>
+            },
+        }
+    }
+
+    // Paste a game written in notation to replay it
+    fn view_import(&self) -> Html {
+        let error = match &self.import_error {
+            Some(error) => html! { <p class="w3-text-red">{format!("Cannot load game: {}", error)}</p> },
+            None => html! {},
+        };
+        html! {
+            <div class="w3-container" style="margin-bottom:20px;">
+                <textarea class="w3-input w3-border" rows="6" placeholder="[Game \"Connect4\"] ..."
+                    value=self.import_text.clone()
+                    oninput=self.link.callback(|e: InputData| Msg::ImportInput(e.value)) />
+                <button class="w3-button w3-border w3-round" style="margin-top:4px;"
+                    onclick=self.link.callback(|_| Msg::ClickedImport)>
+                    {"Load Game"}
+                </button>
+                {error}
+            </div>
+        }
+    }
+
+    fn view_filters(&self) -> Html {
+        let game_type_option = |game_type: &str, label: &str| html! {
+            <>
+                <input class="w3-radio" type="radio" name="history_game_type"
+                    value=game_type checked={self.game_type_filter == game_type}
+                    oninput=self.link.callback(|e: InputData| Msg::GameTypeFilter(e.value))/>
+                <label style="padding:8px;">{label}</label>
+            </>
+        };
+        let sort_option = |order: SortOrder, label: &str| html! {
+            <>
+                <input class="w3-radio" type="radio" name="history_sort"
+                    value=order.name() checked={self.sort == order}
+                    oninput=self.link.callback(|e: InputData| Msg::SortChange(e.value))/>
+                <label style="padding:8px;">{label}</label>
+            </>
+        };
+        html! {
+            <form class="w3-container" style="margin-bottom:20px;"
+                onsubmit=self.link.callback(|_| Msg::ClickedSearch)
+                action="javascript:void(0);">
+                <div class="w3-row-padding" style="padding:4px;">
+                    <label>{"Game:"}</label>
+                    {game_type_option("", "All")}
+                    {game_type_option("Connect4", "Connect 4")}
+                    {game_type_option("Connect4-PopOut", "PopOut")}
+                    {game_type_option("TOOT-OTTO", "TOOT-OTTO")}
+                </div>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <div class="w3-quarter">
+                        <label for="historyPlayer">{"Player:"}</label>
+                        <input class="w3-input w3-border w3-round" id="historyPlayer" type="text"
+                            value=&self.player_filter placeholder="Anyone"
+                            oninput=self.link.callback(|e: InputData| Msg::PlayerFilter(e.value))/>
+                    </div>
+                    <div class="w3-quarter">
+                        <label for="historyWinner">{"Winner:"}</label>
+                        <input class="w3-input w3-border w3-round" id="historyWinner" type="text"
+                            value=&self.winner_filter placeholder="Anyone, or Draw"
+                            oninput=self.link.callback(|e: InputData| Msg::WinnerFilter(e.value))/>
+                    </div>
+                    <div class="w3-quarter">
+                        <label for="historyFrom">{"Played From:"}</label>
+                        <input class="w3-input w3-border w3-round" id="historyFrom" type="date"
+                            value=&self.from_date
+                            oninput=self.link.callback(|e: InputData| Msg::FromDate(e.value))/>
+                    </div>
+                    <div class="w3-quarter">
+                        <label for="historyTo">{"Played To:"}</label>
+                        <input class="w3-input w3-border w3-round" id="historyTo" type="date"
+                            value=&self.to_date
+                            oninput=self.link.callback(|e: InputData| Msg::ToDate(e.value))/>
+                    </div>
+                </div>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <label>{"Sort By:"}</label>
+                    {sort_option(SortOrder::Newest, "Newest")}
+                    {sort_option(SortOrder::Oldest, "Oldest")}
+                    {sort_option(SortOrder::GameType, "Game Type")}
+                    {sort_option(SortOrder::Winner, "Winner")}
+                </div>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <button class="w3-button w3-border w3-round" type="submit">{"Search"}</button>
+                    <button class="w3-button w3-border w3-round" type="button"
+                        onclick=self.link.callback(|_| Msg::ClickedClearFilters)>
+                        {"Clear Filters"}
+                    </button>
+                </div>
+            </form>
+        }
+    }
+
+    fn view_pager(&self) -> Html {
+        let page = self.query.page();
+        let page_count = self.listing.page_count();
+        html! {
+            <div class="w3-row-padding" style="padding:4px;">
+                <button class="w3-button w3-border w3-round"
+                    disabled={page <= 1}
+                    onclick=self.link.callback(|_| Msg::ClickedPreviousPage)>
+                    {"< Previous"}
+                </button>
+                <span style="padding:8px;">
+                    {format!("Page {} of {} ({} games)", page, page_count, self.listing.total)}
+                </span>
+                <button class="w3-button w3-border w3-round"
+                    disabled={page >= page_count}
+                    onclick=self.link.callback(|_| Msg::ClickedNextPage)>
+                    {"Next >"}
+                </button>
             </div>
         }
     }
-}
\ No newline at end of file
+
+    // Apply the filters being typed, from the first page
+    fn search(&mut self) {
+        let text = |value: &String| Some(value.trim().to_string()).filter(|value| !value.is_empty());
+        // Dates are taken as local midnight, and the range includes the whole last day
+        let date = |value: &String| Some(js_sys::Date::parse(&format!("{}T00:00:00", value)))
+            .filter(|time| !time.is_nan());
+        self.query = GameQuery {
+            page: Some(1),
+            page_size: Some(PAGE_SIZE),
+            game_type: text(&self.game_type_filter),
+            player: text(&self.player_filter),
+            winner: text(&self.winner_filter),
+            from: date(&self.from_date).map(|time| time as i64),
+            to: date(&self.to_date).map(|time| (time + DAY) as i64 - 1),
+            sort: Some(self.sort),
+        };
+        self.replaying = None;
+        self.get_games_task = fetch_games(&self.link, &self.query);
+    }
+
+    fn show_page(&mut self, page: i64) {
+        self.query.page = Some(page.max(1));
+        self.get_games_task = fetch_games(&self.link, &self.query);
+    }
+}
+
+// Ask the backend for the page of games `query` describes
+fn fetch_games(link: &ComponentLink<GameHistory>, query: &GameQuery) -> Result<FetchTask, Error> {
+    // Create GET request for the page of games
+    let get_request = Request::get(api::url(&format!("/list-games?{}", query.to_query_string())))
+        .body(Nothing)
+        .unwrap();
+
+    // Create task for FetchService
+    FetchService::new().fetch(
+        get_request,
+        link.callback(|response: Response<Json<Result<GamePage, Error>>>| {
+            if let (meta, Json(Ok(body))) = response.into_parts() {
+                if meta.status.is_success() {
+                    return Msg::FetchResourceComplete(body);
+                }
+            }
+            Msg::FetchResourceFailed
+        }),
+    )
+}
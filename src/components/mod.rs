@@ -1,7 +1,10 @@
 pub mod sidebar;
+pub mod account;
 pub mod content;
 pub mod game_history;
+pub mod replay;
 pub mod score_board;
+pub mod player_stats;
 pub mod utils;
 pub mod connect4;
 pub mod toot_otto;
@@ -0,0 +1,221 @@
+use yew::prelude::*;
+use yew::services::fetch::{Request, Response, FetchService, FetchTask};
+use yew::format::{Nothing, Json};
+use anyhow::Error;
+use crate::models::stats::{PlayerStats as Stats, Record};
+use super::utils::{api, session};
+
+/// Results of one player, looked up by name. Opens on the logged in account.
+pub struct PlayerStats {
+    link: ComponentLink<Self>,
+    name: String,
+    stats: Option<Stats>,
+    error: Option<String>,
+    get_stats_task: Option<Result<FetchTask, Error>>
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {}
+
+pub enum Msg {
+    NameInput(String),
+    ClickedShow,
+    FetchStatsComplete(Stats),
+    FetchStatsFailed
+}
+
+impl Component for PlayerStats {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let mut player_stats = PlayerStats {
+            link,
+            name: session::player_name(&session::current()),
+            stats: None,
+            error: None,
+            get_stats_task: None
+        };
+        if !player_stats.name.is_empty() {
+            player_stats.get_stats();
+        }
+        player_stats
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::NameInput(value) => self.name = value,
+            Msg::ClickedShow => {
+                if self.name.trim().is_empty() {
+                    self.error = Some("Enter the name of a player".into());
+                } else {
+                    self.get_stats();
+                }
+            },
+            Msg::FetchStatsComplete(stats) => {
+                self.error = None;
+                self.stats = Some(stats);
+            },
+            Msg::FetchStatsFailed => self.error = Some("Could not load the statistics".into()),
+        }
+        true
+    }
+
+    fn change(&mut self, _: Self::Properties) -> ShouldRender {
+        false
+    }
+
+    fn view(&self) -> Html {
+        let error = match &self.error {
+            Some(reason) => html! { <p class="w3-text-red">{reason}</p> },
+            None => html! {},
+        };
+        html! {
+            <div class="w3-container" style="margin-top:75px;margin-bottom:75px;">
+                <h5 class="w3-xxxlarge w3-text-red"><b>{"Player Stats"}</b></h5>
+                <hr style="width:50px;border:5px solid red" class="w3-round" />
+                <form
+                    onsubmit=self.link.callback(|_| Msg::ClickedShow)
+                    action="javascript:void(0);">
+                    <div class="w3-row-padding" style="padding:4px;">
+                        <div class="w3-third">
+                            <label for="statsName">{"Player Name:"}</label>
+                            <input
+                                class="w3-input w3-border w3-round"
+                                id="statsName"
+                                type="text"
+                                value=&self.name
+                                oninput=self.link.callback(|e: InputData| Msg::NameInput(e.value))
+                                placeholder="Enter name"/>
+                        </div>
+                    </div>
+                    <div class="w3-row-padding" style="padding:4px;">
+                        <button class="w3-button w3-border w3-round" type="submit">{"Show Stats"}</button>
+                    </div>
+                </form>
+                {error}
+                {
+                    match &self.stats {
+                        Some(stats) => self.view_stats(stats),
+                        None => html! {},
+                    }
+                }
+            </div>
+        }
+    }
+}
+
+impl PlayerStats {
+    fn view_stats(&self, stats: &Stats) -> Html {
+        if stats.overall.played == 0 {
+            return html! { <p>{format!("{} has not finished any games yet", stats.player)}</p> };
+        }
+        let average = if stats.average_moves > 0.0 {
+            format!("{:.1}", stats.average_moves)
+        } else {
+            "-".to_string()
+        };
+        html! {
+            <>
+                <div><h4>{format!("Summary for {}", stats.player)}</h4></div>
+                <table>
+                    <tr>
+                        <th>{"Played"}</th>
+                        <th>{"Won"}</th>
+                        <th>{"Lost"}</th>
+                        <th>{"Drawn"}</th>
+                        <th>{"Longest Win Streak"}</th>
+                        <th>{"Average Moves per Game"}</th>
+                    </tr>
+                    <tr>
+                        <td>{stats.overall.played}</td>
+                        <td>{stats.overall.wins}</td>
+                        <td>{stats.overall.losses}</td>
+                        <td>{stats.overall.draws}</td>
+                        <td>{stats.longest_win_streak}</td>
+                        <td>{average}</td>
+                    </tr>
+                </table>
+                {view_records("By Game Type", "Game Type", &stats.by_game_type)}
+                {view_records("By Opponent", "Opponent", &stats.by_opponent)}
+                {view_records("Against the Computer", "Difficulty", &stats.by_difficulty)}
+                {view_openings(&stats.opening_columns)}
+            </>
+        }
+    }
+
+    fn get_stats(&mut self) {
+        let name = String::from(js_sys::encode_uri_component(self.name.trim()));
+        let get_request = Request::get(api::url(&format!("/players/{}/stats", name)))
+            .body(Nothing)
+            .unwrap();
+
+        let task = FetchService::new().fetch(
+            get_request,
+            self.link.callback(|response: Response<Json<Result<Stats, Error>>>| {
+                if let (meta, Json(Ok(body))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::FetchStatsComplete(body);
+                    }
+                }
+                Msg::FetchStatsFailed
+            }),
+        );
+        self.get_stats_task = Some(task);
+    }
+}
+
+fn view_records(title: &str, first_column: &str, records: &[(String, Record)]) -> Html {
+    if records.is_empty() {
+        return html! {};
+    }
+    html! {
+        <>
+            <br></br>
+            <div><h4>{title}</h4></div>
+            <table>
+                <tr>
+                    <th>{first_column}</th>
+                    <th>{"Played"}</th>
+                    <th>{"Won"}</th>
+                    <th>{"Lost"}</th>
+                    <th>{"Drawn"}</th>
+                </tr>
+                { for records.iter().map(|(name, record)| html! {
+                    <tr>
+                        <td>{name}</td>
+                        <td>{record.played}</td>
+                        <td>{record.wins}</td>
+                        <td>{record.losses}</td>
+                        <td>{record.draws}</td>
+                    </tr>
+                }) }
+            </table>
+        </>
+    }
+}
+
+fn view_openings(openings: &[(i32, i32)]) -> Html {
+    if openings.is_empty() {
+        return html! {};
+    }
+    html! {
+        <>
+            <br></br>
+            <div><h4>{"Favourite Opening Columns"}</h4></div>
+            <table>
+                <tr>
+                    <th>{"Column"}</th>
+                    <th>{"Games"}</th>
+                </tr>
+                // Columns are shown from 1, as on the board
+                { for openings.iter().map(|(column, games)| html! {
+                    <tr>
+                        <td>{column + 1}</td>
+                        <td>{games}</td>
+                    </tr>
+                }) }
+            </table>
+        </>
+    }
+}
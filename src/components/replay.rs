@@ -0,0 +1,326 @@
+use yew::prelude::*;
+use yew::services::interval::{IntervalService, IntervalTask};
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement};
+use std::f64;
+use std::cmp::max;
+use std::time::Duration;
+use crate::models::game::Game;
+use crate::models::player::Player;
+use crate::models::replay::{Position, Replay as GameReplay, ReplayError};
+
+// Colours used when a game was saved before they were recorded
+const PLAYER1_COLOR: &str = "#ff4136";
+const PLAYER2_COLOR: &str = "#ffff00";
+const BOARD_COLOR: &str = "#00bfff";
+
+// Milliseconds between moves at each autoplay speed
+const SLOW: u64 = 1500;
+const NORMAL: u64 = 800;
+const FAST: u64 = 300;
+
+/// Redraws a saved game one move at a time
+pub struct Replay {
+    link: ComponentLink<Self>,
+    game: Game,
+    replay: Result<GameReplay, ReplayError>,
+    // Moves shown so far
+    step: usize,
+    context: Option<CanvasRenderingContext2d>,
+    // Running while the game plays itself
+    autoplay: Option<IntervalTask>,
+    speed: u64
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct Props {
+    pub game: Game
+}
+
+pub enum Msg {
+    ClickedStart,
+    ClickedBack,
+    ClickedForward,
+    ClickedLastMove,
+    ClickedPlay,
+    Tick,
+    SpeedChange(String)
+}
+
+impl Component for Replay {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        Replay {
+            link,
+            replay: GameReplay::new(&props.game),
+            game: props.game,
+            step: 0,
+            context: None,
+            autoplay: None,
+            speed: NORMAL
+        }
+    }
+
+    fn mounted(&mut self) -> ShouldRender {
+        let document = web_sys::window().unwrap().document().unwrap();
+        let canvas = document.get_element_by_id("replay-gameboard").unwrap();
+        let canvas: HtmlCanvasElement = canvas
+            .dyn_into::<HtmlCanvasElement>()
+            .map_err(|_| ())
+            .unwrap();
+
+        self.context = Some(canvas
+            .get_context("2d")
+            .unwrap()
+            .unwrap()
+            .dyn_into::<CanvasRenderingContext2d>()
+            .unwrap());
+        self.redraw();
+
+        false
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        if self.game == props.game {
+            return false;
+        }
+        self.replay = GameReplay::new(&props.game);
+        self.game = props.game;
+        self.step = 0;
+        self.autoplay = None;
+        self.redraw();
+        true
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        let last = self.moves();
+        match msg {
+            Msg::ClickedStart => self.step = 0,
+            Msg::ClickedBack => self.step = self.step.saturating_sub(1),
+            Msg::ClickedForward => self.step = (self.step + 1).min(last),
+            Msg::ClickedLastMove => self.step = last,
+            Msg::ClickedPlay => {
+                if self.autoplay.is_some() {
+                    self.autoplay = None;
+                } else {
+                    // Playing from the end starts the game over
+                    if self.step == last {
+                        self.step = 0;
+                    }
+                    self.start_autoplay();
+                }
+            },
+            Msg::Tick => {
+                self.step = (self.step + 1).min(last);
+                if self.step == last {
+                    self.autoplay = None;
+                }
+            },
+            Msg::SpeedChange(value) => {
+                self.speed = value.parse().unwrap_or(NORMAL);
+                if self.autoplay.is_some() {
+                    self.start_autoplay();
+                }
+            },
+        }
+        self.redraw();
+        true
+    }
+
+    fn view(&self) -> Html {
+        // The canvas size is set when drawing, so re-rendering never clears it
+        html! {
+            <div class="w3-container">
+                <h4>{format!("Replay: {} vs {}", self.game.player1_name, self.game.player2_name)}</h4>
+                {self.view_status()}
+                <canvas id="replay-gameboard" style="margin:10px 0;"></canvas>
+                {self.view_controls()}
+                {self.view_speed_options()}
+            </div>
+        }
+    }
+}
+
+impl Replay {
+    fn view_status(&self) -> Html {
+        let text = match &self.replay {
+            Err(error) => format!("This game cannot be replayed: {}", error),
+            Ok(replay) if replay.is_empty() => "No moves were saved with this game".to_string(),
+            Ok(replay) => match replay.position(self.step).winner() {
+                Some(player) if self.step == replay.len() =>
+                    format!("Move {} of {}: {} wins!", self.step, replay.len(), self.player_name(player)),
+                _ => format!("Move {} of {}", self.step, replay.len()),
+            },
+        };
+        html! { <p>{text}</p> }
+    }
+
+    fn view_controls(&self) -> Html {
+        let last = self.moves();
+        let won = match &self.replay {
+            Ok(replay) => replay.winning_step().is_some(),
+            Err(_) => false,
+        };
+        html! {
+            <div class="w3-row-padding" style="padding:4px;">
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.step == 0
+                    onclick=self.link.callback(|_| Msg::ClickedStart)>
+                    {"|<"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.step == 0
+                    onclick=self.link.callback(|_| Msg::ClickedBack)>
+                    {"< Back"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=last == 0
+                    onclick=self.link.callback(|_| Msg::ClickedPlay)>
+                    {if self.autoplay.is_some() { "Pause" } else { "Play" }}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.step == last
+                    onclick=self.link.callback(|_| Msg::ClickedForward)>
+                    {"Forward >"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.step == last
+                    onclick=self.link.callback(|_| Msg::ClickedLastMove)>
+                    {if won { "Winning Move >|" } else { "Last Move >|" }}
+                </button>
+            </div>
+        }
+    }
+
+    fn view_speed_options(&self) -> Html {
+        html! {
+            <div class="w3-row-padding" style="padding:4px;">
+                <label>{"Autoplay Speed:"}</label>
+                <input class="w3-radio" type="radio" name="replay_speed"
+                    value=SLOW.to_string() checked={self.speed == SLOW}
+                    oninput=self.link.callback(|e: InputData| Msg::SpeedChange(e.value))/>
+                <label style="padding:8px;">{"Slow"}</label>
+
+                <input class="w3-radio" type="radio" name="replay_speed"
+                    value=NORMAL.to_string() checked={self.speed == NORMAL}
+                    oninput=self.link.callback(|e: InputData| Msg::SpeedChange(e.value))/>
+                <label style="padding:8px;">{"Normal"}</label>
+
+                <input class="w3-radio" type="radio" name="replay_speed"
+                    value=FAST.to_string() checked={self.speed == FAST}
+                    oninput=self.link.callback(|e: InputData| Msg::SpeedChange(e.value))/>
+                <label style="padding:8px;">{"Fast"}</label>
+            </div>
+        }
+    }
+
+    fn start_autoplay(&mut self) {
+        let callback = self.link.callback(|_| Msg::Tick);
+        self.autoplay = Some(IntervalService::new().spawn(Duration::from_millis(self.speed), callback));
+    }
+
+    // Moves in the game, or 0 if it cannot be replayed
+    fn moves(&self) -> usize {
+        self.replay.as_ref().map(|replay| replay.len()).unwrap_or(0)
+    }
+
+    fn player_name(&self, player: Player) -> String {
+        match player {
+            Player::One => self.game.player1_name.clone(),
+            Player::Two => self.game.player2_name.clone(),
+        }
+    }
+
+    fn player_color(&self, player: Player) -> String {
+        let (stored, default) = match player {
+            Player::One => (&self.game.player1_color, PLAYER1_COLOR),
+            Player::Two => (&self.game.player2_color, PLAYER2_COLOR),
+        };
+        if stored.is_empty() { default.to_string() } else { stored.clone() }
+    }
+
+    fn redraw(&self) {
+        let position = match (&self.replay, &self.context) {
+            (Ok(replay), Some(_)) => replay.position(self.step),
+            _ => return,
+        };
+        let (rows, columns) = match position {
+            Position::Connect4(state) => (state.rows(), state.columns()),
+            Position::TootOtto(state) => (state.board().tokens.len(), state.board().tokens[0].len()),
+        };
+        // Resizing the canvas also clears it
+        let canvas = self.context().canvas().unwrap();
+        canvas.set_width(max(640, 75 * columns + 115) as u32);
+        canvas.set_height((75 * rows + 30) as u32);
+
+        self.draw_board(rows, columns);
+        for y in 0..rows {
+            for x in 0..columns {
+                let (token, letter) = match position {
+                    Position::Connect4(state) => (state.board().tokens[y][x], None),
+                    Position::TootOtto(state) => (state.board().tokens[y][x], Some(state.board().disc_map[y][x])),
+                };
+                let fill = match token {
+                    token if token >= 1 => self.player_color(Player::One),
+                    token if token <= -1 => self.player_color(Player::Two),
+                    _ => "transparent".to_string(),
+                };
+                self.draw_circle((75 * x + 100) as f64, (75 * y + 50) as f64, fill, letter.filter(|_| token != 0));
+            }
+        }
+        // Only the final position can hold a winning line
+        for (y, x) in position.winning_line().unwrap_or_default() {
+            self.draw_ring((75 * x + 100) as f64, (75 * y + 50) as f64);
+        }
+    }
+
+    fn draw_board(&self, rows: usize, columns: usize) {
+        let context = self.context();
+        context.save();
+        context.set_fill_style(&JsValue::from_str(BOARD_COLOR));
+        context.begin_path();
+        for y in 0..rows {
+            let y = y as f64;
+            for x in 0..columns {
+                let x = x as f64;
+                context.arc(75.0 * x + 100.0, 75.0 * y + 50.0, 25.0, 0.0, 2.0 * f64::consts::PI).unwrap();
+                context.rect(75.0 * x + 150.0, 75.0 * y, -100.0, 100.0);
+            }
+        }
+        context.fill();
+        context.restore();
+    }
+
+    fn draw_circle(&self, x: f64, y: f64, fill: String, letter: Option<char>) {
+        let context = self.context();
+        context.save();
+        context.set_fill_style(&JsValue::from_str(&fill));
+        context.begin_path();
+        context.arc(x, y, 25.0, 0.0, 2.0 * f64::consts::PI).unwrap();
+        context.fill();
+        context.restore();
+        if let Some(letter) = letter {
+            context.save();
+            context.set_font("bold 25px serif");
+            context.fill_text(&letter.to_string(), x - 8.5, y + 8.0).unwrap();
+            context.restore();
+        }
+    }
+
+    fn draw_ring(&self, x: f64, y: f64) {
+        let context = self.context();
+        context.save();
+        context.set_stroke_style(&JsValue::from_str("#111"));
+        context.set_line_width(6.0);
+        context.begin_path();
+        context.arc(x, y, 28.0, 0.0, 2.0 * f64::consts::PI).unwrap();
+        context.stroke();
+        context.restore();
+    }
+
+    fn context(&self) -> &CanvasRenderingContext2d {
+        self.context.as_ref().unwrap()
+    }
+}
@@ -3,20 +3,28 @@ use yew::services::fetch::{Request, Response, FetchService, FetchTask};
 use yew::format::{Nothing, Json};
 use anyhow::Error;
 use crate::models::game::Game;
-use super::utils::table_builder;
+use crate::models::listing::{GamePage, GameQuery, SortOrder, MAX_PAGE_SIZE};
+use crate::models::rating::Rating;
+use super::utils::{api, table_builder};
 
 pub struct ScoreBoard {
     games: Vec<Game>,
     link: ComponentLink<Self>,
     get_games_task: Result<FetchTask, Error>, // Important to keep in scope!!
+    // Leaderboard of the game type picked above the ratings table
+    game_type: String,
+    ratings: Vec<Rating>,
+    get_ratings_task: Option<Result<FetchTask, Error>>
 }
 
 #[derive(Properties, Clone, PartialEq)]
 pub struct Props {}
 
 pub enum Msg {
-    FetchResourceComplete(Vec<Game>),
-    FetchResourceFailed
+    FetchResourceComplete(GamePage),
+    FetchResourceFailed,
+    GameTypeChange(String),
+    FetchRatingsComplete(Vec<Rating>)
 }
 
 impl Component for ScoreBoard {
@@ -24,41 +32,43 @@ impl Component for ScoreBoard {
     type Properties = Props;
 
     fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        // The tables count every game, which are fetched a page at a time from the first
+        let task = fetch_games(&link, 1);
 
-        // Create GET request for list of games
-        let get_request = Request::get("http://localhost:8000/list-games")
-            .body(Nothing)
-            .unwrap();
-
-        // Create task for FetchService
-        let task = FetchService::new().fetch(
-            get_request,
-            link.callback(|response: Response<Json<Result<Vec<Game>, Error>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::FetchResourceComplete(body);
-                    }
-                }
-                Msg::FetchResourceFailed
-            }),
-        );
-
-        ScoreBoard {
+        let mut score_board = ScoreBoard {
             games: Vec::<Game>::new(),
             link: link,
-            get_games_task: task // Note: Reference to task needs to be stored for the duration of the request (https://github.com/yewstack/yew/issues/388)
-        }
+            get_games_task: task, // Note: Reference to task needs to be stored for the duration of the request (https://github.com/yewstack/yew/issues/388)
+            game_type: "Connect4".into(),
+            ratings: Vec::new(),
+            get_ratings_task: None
+        };
+        score_board.get_ratings();
+        score_board
     }
 
     fn update(&mut self, msg: Self::Message) -> ShouldRender {
         match msg {
-            Msg::FetchResourceComplete(games) => {
-                for game in games {
+            Msg::FetchResourceComplete(listing) => {
+                if listing.page < listing.page_count() {
+                    self.get_games_task = fetch_games(&self.link, listing.page + 1);
+                }
+                for game in listing.games {
                     self.games.push(game);
                 }
                 return true;
             },
             Msg::FetchResourceFailed => return false,
+            Msg::GameTypeChange(game_type) => {
+                self.game_type = game_type;
+                self.ratings.clear();
+                self.get_ratings();
+                return true;
+            },
+            Msg::FetchRatingsComplete(ratings) => {
+                self.ratings = ratings;
+                return true;
+            },
         }
     }
 
@@ -67,6 +77,10 @@ impl Component for ScoreBoard {
             <div class="w3-container" id="services" style="margin-top:75px;margin-bottom:75px;">
                 <h5 class="w3-xxxlarge w3-text-red"><b>{"Score Board"}</b></h5>
                 <hr style="width:50px;border:5px solid red" class="w3-round" />
+                {self.view_ratings()}
+
+                <br></br>
+
                 <div><h4>{"Games Won by Computer"}</h4></div>
                 <table>
                         <tr>
@@ -109,4 +123,81 @@ impl Component for ScoreBoard {
             </div>
         }
     }
-}
\ No newline at end of file
+}
+
+impl ScoreBoard {
+    fn view_ratings(&self) -> Html {
+        let game_type_option = |game_type: &str, label: &str| html! {
+            <>
+                <input class="w3-radio" type="radio" name="rating_game_type"
+                    value=game_type checked={self.game_type == game_type}
+                    oninput=self.link.callback(|e: InputData| Msg::GameTypeChange(e.value))/>
+                <label style="padding:8px;">{label}</label>
+            </>
+        };
+        html! {
+            <>
+                <div><h4>{"Player Ratings"}</h4></div>
+                <p>{"Ratings go up for a win and down for a loss, by more against stronger opponents. The computer keeps a fixed rating at each difficulty."}</p>
+                <div class="w3-row-padding" style="padding:4px;">
+                    {game_type_option("Connect4", "Connect 4")}
+                    {game_type_option("Connect4-PopOut", "PopOut")}
+                    {game_type_option("TOOT-OTTO", "TOOT-OTTO")}
+                </div>
+                <table>
+                    <tr>
+                        <th>{"Rank"}</th>
+                        <th>{"Player"}</th>
+                        <th>{"Rating"}</th>
+                        <th>{"Won"}</th>
+                        <th>{"Lost"}</th>
+                        <th>{"Drawn"}</th>
+                        <th>{"Recent Trend"}</th>
+                    </tr>
+                    { table_builder::render_ratings_table(&self.ratings) }
+                </table>
+            </>
+        }
+    }
+
+    fn get_ratings(&mut self) {
+        let get_request = Request::get(api::url(&format!("/leaderboard?game_type={}", self.game_type)))
+            .body(Nothing)
+            .unwrap();
+
+        let task = FetchService::new().fetch(
+            get_request,
+            self.link.callback(|response: Response<Json<Result<Vec<Rating>, Error>>>| {
+                if let (meta, Json(Ok(body))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::FetchRatingsComplete(body);
+                    }
+                }
+                Msg::FetchResourceFailed
+            }),
+        );
+        self.get_ratings_task = Some(task);
+    }
+}
+
+// Ask the backend for one page of every game, oldest first so that games
+// saved in the meantime only add to the last page
+fn fetch_games(link: &ComponentLink<ScoreBoard>, page: i64) -> Result<FetchTask, Error> {
+    let query = GameQuery { page: Some(page), page_size: Some(MAX_PAGE_SIZE), sort: Some(SortOrder::Oldest), ..GameQuery::default() };
+    let get_request = Request::get(api::url(&format!("/list-games?{}", query.to_query_string())))
+        .body(Nothing)
+        .unwrap();
+
+    // Create task for FetchService
+    FetchService::new().fetch(
+        get_request,
+        link.callback(|response: Response<Json<Result<GamePage, Error>>>| {
+            if let (meta, Json(Ok(body))) = response.into_parts() {
+                if meta.status.is_success() {
+                    return Msg::FetchResourceComplete(body);
+                }
+            }
+            Msg::FetchResourceFailed
+        }),
+    )
+}
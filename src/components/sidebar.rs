@@ -94,6 +94,17 @@ impl Component for Sidebar {
                     onclick=self.link.callback(|_| Msg::TabClicked(Tab::Scores))
                     class="w3-bar-item w3-button w3-hover-white">{"Score Board"}
                 </button>
+                <button
+                    onclick=self.link.callback(|_| Msg::TabClicked(Tab::PlayerStats))
+                    class="w3-bar-item w3-button w3-hover-white">
+                    {"Player Stats"}
+                </button>
+                <h5 class="w3-bar-item"></h5>
+                <button
+                    onclick=self.link.callback(|_| Msg::TabClicked(Tab::Account))
+                    class="w3-bar-item w3-button w3-hover-white">
+                    {"Account"}
+                </button>
             </nav>
         }
     }
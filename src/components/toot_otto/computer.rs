@@ -3,35 +3,58 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window};
 use yew::services::fetch::{Request, Response, FetchService, FetchTask};
-use yew::format::{Json, Nothing};
+use yew::format::Json;
 use anyhow::Error;
 use serde_json::json;
-use js_sys::{Date, Math};
-use crate::models::game_boards::TootOttoGameBoard;
+use js_sys::Date;
+use crate::models::notation;
+use crate::models::account::Session;
+use crate::models::difficulty;
+use crate::models::replay::TOOT_OTTO;
+use crate::components::utils::{api, session};
+use crate::models::toot_otto::{self, TootOttoState, TootOttoMove, Letter, MoveOutcome, MoveError};
+use crate::agents::ai::{self, AiAgent};
+use crate::models::player::Player;
+
 use std::f64;
-use std::cmp::{max, min};
 use crate::alert;
 
 extern crate models;
 use models::game::Game;
 
+// Random games the computer plays out before each move, by difficulty
+const EASY_PLAYOUTS: u32 = 100;
+const MEDIUM_PLAYOUTS: u32 = 2000;
+const HARD_PLAYOUTS: u32 = 20000;
+
+// Half the spacing between columns, so a click anywhere over the board picks the nearest column
+const CLICK_RADIUS: f64 = 37.5;
+
 pub struct TootOttoComputer {
     link: ComponentLink<Self>,
     game: Game,
-    selected_disc: char,
-    computer_disc: char,
+    selected_disc: Letter,
     game_started: bool,
     context: Option<CanvasRenderingContext2d>,
-    board: TootOttoGameBoard,
-    move_num: u8,
+    state: TootOttoState,
     won: bool,
     paused: bool,
     save_task: Option<Result<FetchTask, Error>>,
+    // Account logged in on this browser, which plays as Player 1
+    account: Option<Session>,
     player1_color: String,
     computer_color: String,
     board_color: String,
     selected_difficulty: String,
-    max_depth: i64
+    playouts: u32,
+    ai: Box<dyn Bridge<AiAgent>>,
+    // Id of the request the computer is thinking about, if any
+    thinking: Option<u32>,
+    requests: u32,
+    // Moves taken back by Undo, most recent last
+    redo: Vec<TootOttoMove>,
+    // Notation of the game so far is shown under the board
+    show_notation: bool
 }
 
 macro_rules! log {
@@ -49,13 +72,17 @@ pub enum Msg {
     DiscTSelected,
     DiscOSelected,
     ClickedBoard(MouseEvent),
-    GetGamesList(Vec<Game>),
-    GameSaved,
+    GameSaved(Game),
     SaveError,
     Player1ColorChange(String),
     ComputerColorChange(String),
     BoardColorChange(String),
-    DifficultyLevelChange(String)
+    DifficultyLevelChange(String),
+    AiMoved(ai::Response),
+    ClickedCancel,
+    ClickedUndo,
+    ClickedRedo,
+    ClickedExport
 }
 
 impl Component for TootOttoComputer {
@@ -63,35 +90,36 @@ impl Component for TootOttoComputer {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let account = session::current();
         TootOttoComputer {
-            link,
+            link: link.clone(),
             game: Game {
-                game_number: 0, // placeholder, when game is saved this can be set
+                game_number: 0, // placeholder, the backend numbers the game when it is saved
                 game_type: "TOOT-OTTO".into(),
-                player1_name: "".into(),
+                player1_name: session::player_name(&account),
                 player2_name: "Computer".into(),
                 winner_name: "".into(),
-                game_date: 0 // placeholder, when game is saved this can be set
+                game_date: 0, // placeholder, when game is saved this can be set
+                ..Game::default()
             },
-            selected_disc: 'T',
-            computer_disc: 'T',
+            selected_disc: Letter::T,
             game_started: false,
             context: None,
-            board: TootOttoGameBoard {
-                rows: 4,
-                columns: 6,
-                tokens: [[0; 6]; 4],
-                disc_map: [['0'; 6]; 4]
-            },
-            move_num: 0,
+            state: TootOttoState::new(),
             won: false,
             paused: false,
             save_task: None,
+            account,
             player1_color: "#99ffcc".into(),
             computer_color: "#ffff99".into(),
             board_color: "#00bfff".into(),
             selected_difficulty: "easy".into(),
-            max_depth: 1
+            playouts: EASY_PLAYOUTS,
+            ai: AiAgent::bridge(link.callback(Msg::AiMoved)),
+            thinking: None,
+            requests: 0,
+            redo: Vec::new(),
+            show_notation: false
         }
     }
 
@@ -109,19 +137,25 @@ impl Component for TootOttoComputer {
                 } else if self.game.player1_name == "Computer" {
                     alert("Computer is a reserved name!");
                 } else {
+                    // Settings are saved with the game so it can be replayed
+                    self.game.columns = toot_otto::COLUMNS as i32;
+                    self.game.rows = toot_otto::ROWS as i32;
+                    self.game.player1_color = self.player1_color.clone();
+                    self.game.player2_color = self.computer_color.clone();
+                    self.game.difficulty = self.selected_difficulty.clone();
                     self.game_started = true;
                     self.draw_board();
                     self.print();
                 }
             },
             Msg::DiscTSelected => {
-                self.selected_disc = 'T';
+                self.selected_disc = Letter::T;
             },
             Msg::DiscOSelected => {
-                self.selected_disc = 'O';
+                self.selected_disc = Letter::O;
             },
             Msg::ClickedBoard(event) => {
-                if !self.game_started {
+                if !self.game_started || self.thinking.is_some() {
                     return false;
                 }
                 if self.won {
@@ -135,28 +169,26 @@ impl Component for TootOttoComputer {
                 // log!("x: {} y: {}", x,y);
 
                 for i in 0..7 {
-                    if self.on_region([x, y], (75 * i + 100) as f64, 25.0){
+                    if self.on_region([x, y], (75 * i + 100) as f64, CLICK_RADIUS){
                         // log!("Region {} clicked", i);
                         self.paused = false;
-                        let valid = self.action(i);
+                        let valid = self.action(i, self.selected_disc);
                         if valid == 1 {
-                            // Perform AI action
-                            self.paused = false;
-                            self.ai();
+                            // A new move makes the undone ones unreachable
+                            self.redo.clear();
+                            if !self.won {
+                                // Perform AI action
+                                self.ai();
+                            }
                         }
                         break; //because there will be no 2 points that are clicked at a time
                     }
                     
                 }
             },
-            Msg::GetGamesList(games) => {
-                log!("Obtained games list");
-                self.game.game_number = games.len() as i32 + 1;
-                self.save_task = None;
-                self.save_game();
-            },
-            Msg::GameSaved => {
-                log!("Successfully saved");
+            Msg::GameSaved(saved) => {
+                log!("Successfully saved as game {}", saved.game_number);
+                self.game.game_number = saved.game_number;
                 self.save_task = None;
             },
             Msg::SaveError => log!("Game failed to save"),
@@ -171,14 +203,62 @@ impl Component for TootOttoComputer {
             },
             Msg::DifficultyLevelChange(new_value) => {
                 if new_value == "easy" {
-                    self.max_depth = 1;
+                    self.playouts = EASY_PLAYOUTS;
                 } else if new_value == "medium" {
-                    self.max_depth = 3;
+                    self.playouts = MEDIUM_PLAYOUTS;
                 } else if new_value == "hard" {
-                    self.max_depth = 4;
+                    self.playouts = HARD_PLAYOUTS;
                 }
                 self.selected_difficulty = new_value;
             },
+            Msg::AiMoved(ai::Response::TootOtto { id, choice }) => {
+                // Answers to cancelled requests come back too, and are dropped
+                if self.thinking != Some(id) {
+                    return false;
+                }
+                self.thinking = None;
+                self.paused = false;
+                if let Some(choice) = choice {
+                    self.action(choice.column, choice.letter);
+                }
+            },
+            Msg::AiMoved(_) => return false,
+            Msg::ClickedCancel => {
+                if self.thinking.take().is_some() {
+                    // Take back the player's move so they can choose again
+                    self.state.undo();
+                    self.redraw();
+                    self.paused = false;
+                }
+            },
+            Msg::ClickedUndo => {
+                if self.won || self.thinking.is_some() {
+                    return false;
+                }
+                // Take back the computer's reply together with the player's move
+                while let Some(mv) = self.state.undo() {
+                    self.redo.push(mv);
+                    if self.state.current_player() == Player::One {
+                        break;
+                    }
+                }
+                self.paused = false;
+                self.redraw();
+            },
+            Msg::ClickedExport => self.show_notation = !self.show_notation,
+            Msg::ClickedRedo => {
+                if self.won || self.thinking.is_some() {
+                    return false;
+                }
+                // Replay the player's move and the computer's reply to it
+                while let Some(mv) = self.redo.pop() {
+                    self.paused = false;
+                    self.action(mv.column, mv.letter);
+                    if self.won || self.state.current_player() == Player::One {
+                        break;
+                    }
+                }
+            },
         }
         true
     }
@@ -215,23 +295,23 @@ impl Component for TootOttoComputer {
                     </div>
                     <form class="w3-row-padding">
                         <label>{"Select a Disc Type: "}</label>
-                        <label for="discT" style="padding:8px;">{"T"}</label>
+                        <label for="discT" style="padding:8px;">{format!("T ({} left)", self.state.discs_left(self.state.current_player(), Letter::T))}</label>
                         <input
                             class="w3-radio"
                             id="discT"
                             type="radio"
                             name="choice"
                             value="T"
-                            checked=(self.selected_disc == 'T')
+                            checked=(self.selected_disc == Letter::T)
                             onclick=self.link.callback(|_| Msg::DiscTSelected) />
-                        <label for="discO" style="padding:8px;">{"O"}</label>
+                        <label for="discO" style="padding:8px;">{format!("O ({} left)", self.state.discs_left(self.state.current_player(), Letter::O))}</label>
                         <input
                             class="w3-radio"
                             id="discO"
                             type="radio"
                             name="choice"
                             value="O"
-                            checked=(self.selected_disc == 'O')
+                            checked=(self.selected_disc == Letter::O)
                             onclick=self.link.callback(|_| Msg::DiscOSelected) />
                     </form>
                     <br/>
@@ -258,7 +338,7 @@ impl Component for TootOttoComputer {
                                     class="w3-input w3-border w3-round"
                                     id="nameInput"
                                     type="text"
-                                    disabled=self.game_started
+                                    disabled=self.game_started || self.account.is_some()
                                     value=&self.game.player1_name
                                     oninput=self.link.callback(|e: InputData| Msg::GotPlayer1Input(e.value))
                                     placeholder="Enter name"
@@ -304,20 +384,14 @@ impl Component for TootOttoComputer {
                             <div class="w3-threequarter">
                                 <label>{"Difficulty Level:"}</label>
                                 <div style="display:block;">
-                                    <input class="w3-radio" type="radio" name="difficulty"
-                                        value="easy" disabled=self.game_started checked={self.selected_difficulty == "easy"}
-                                        oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
-                                    <label style="padding:8px;">{"Easy"}</label>
-                                    
-                                    <input class="w3-radio" type="radio" name="difficulty"
-                                        value="medium" disabled=self.game_started checked={self.selected_difficulty == "medium"}
-                                        oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
-                                    <label style="padding:8px;">{"Medium"}</label>
-                                    
-                                    <input class="w3-radio" type="radio" name="difficulty"
-                                        value="hard" disabled=self.game_started checked={self.selected_difficulty == "hard"}
-                                        oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
-                                    <label style="padding:8px;">{"Hard"}</label>
+                                    { for difficulty::offered(TOOT_OTTO).map(|level| html! {
+                                        <>
+                                            <input class="w3-radio" type="radio" name="difficulty"
+                                                value=level.name disabled=self.game_started checked={self.selected_difficulty == level.name}
+                                                oninput=self.link.callback(|e: InputData| Msg::DifficultyLevelChange(e.value))/>
+                                            <label style="padding:8px;">{level.label}</label>
+                                        </>
+                                    }) }
                                 </div>
                             </div>  
                         </div>
@@ -334,6 +408,8 @@ impl Component for TootOttoComputer {
                 </div>
 
                 {game_details}
+                {self.view_history_buttons()}
+                {self.view_thinking()}
 
                 <canvas
                     onclick=self.link.callback(|e| Msg::ClickedBoard(e))
@@ -347,6 +423,61 @@ impl Component for TootOttoComputer {
 }
 
 impl TootOttoComputer {
+    // Shown while the worker searches for the computer's move
+    fn view_thinking(&self) -> Html {
+        if self.thinking.is_none() {
+            return html! {};
+        }
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <span style="padding:8px;">{"Computer is thinking\u{2026}"}</span>
+                <button class="w3-button w3-border w3-round"
+                    onclick=self.link.callback(|_| Msg::ClickedCancel)>
+                    {"Cancel"}
+                </button>
+            </div>
+        }
+    }
+
+    // Undo and Redo buttons, shown once the game has started
+    fn view_history_buttons(&self) -> Html {
+        if !self.game_started {
+            return html! {};
+        }
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.thinking.is_some() || self.state.moves().is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedUndo)>
+                    {"Undo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.thinking.is_some() || self.redo.is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedRedo)>
+                    {"Redo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    onclick=self.link.callback(|_| Msg::ClickedExport)>
+                    {if self.show_notation { "Hide Notation" } else { "Export" }}
+                </button>
+                {self.view_notation()}
+            </div>
+        }
+    }
+
+    // Text of the game so far, to copy into another tool
+    fn view_notation(&self) -> Html {
+        if !self.show_notation {
+            return html! {};
+        }
+        let mut game = self.game.clone();
+        game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
+        html! {
+            <textarea class="w3-input w3-border" style="margin-top:4px;" rows="10" readonly=true
+                value=notation::to_notation(&game) />
+        }
+    }
+
     fn draw_board(&self) {
         let context = self.context();
 
@@ -366,21 +497,22 @@ impl TootOttoComputer {
     }
 
     fn draw(&self){
+        let board = self.state.board();
         let mut fg_color = "transparent".to_string();
         for y in 0..4 {
             for x in 0..6 {
                 let mut text = ' ';
                 fg_color = "transparent".to_string();
-                if self.board.tokens[y][x] >= 1 && self.board.disc_map[y][x] == 'T' {
+                if board.tokens[y][x] >= 1 && board.disc_map[y][x] == 'T' {
                     fg_color = self.player1_color.clone();
                     text = 'T';
-                } else if self.board.tokens[y][x] >= 1 && self.board.disc_map[y][x] == 'O' {
+                } else if board.tokens[y][x] >= 1 && board.disc_map[y][x] == 'O' {
                     fg_color = self.player1_color.clone();
                     text = 'O';
-                } else if self.board.tokens[y][x] <= -1_i8 && self.board.disc_map[y][x] == 'T' {
+                } else if board.tokens[y][x] <= -1_i8 && board.disc_map[y][x] == 'T' {
                     fg_color = self.computer_color.clone();
                     text = 'T';
-                } else if self.board.tokens[y][x] <= -1_i8 && self.board.disc_map[y][x] == 'O' {
+                } else if board.tokens[y][x] <= -1_i8 && board.disc_map[y][x] == 'O' {
                     fg_color = self.computer_color.clone();
                     text = 'O';
                 }
@@ -412,6 +544,13 @@ impl TootOttoComputer {
         return false;
     }
 
+    // Repaint the board from scratch, after discs were taken off it
+    fn redraw(&self) {
+        self.clear();
+        self.draw_board();
+        self.draw();
+    }
+
     fn clear(&self) {
         let context = self.context();
         let canvas = self.canvas();
@@ -441,112 +580,37 @@ impl TootOttoComputer {
     //     }
     // }
 
-    fn action(&mut self, column: i64) -> i8 {
+    fn action(&mut self, column: usize, letter: Letter) -> i8 {
         if self.paused || self.won {
             return 0;
         }
-        if self.board.tokens[0][column as usize] != 0 || column < 0 || column > 5 {
-            return -1;
-        }
-
-        let mut row = 0;
-        let mut done = false;
-        for i in 0..3 {
-            match self.board.tokens[i + 1][column as usize] {
-                0 => continue,
-                _=> {
-                    done = true;
-                    row = i;
-                    break;
-                }
+        let outcome = match self.state.play(TootOttoMove { column, letter }) {
+            Ok(outcome) => outcome,
+            Err(MoveError::OutOfDiscs(letter)) => {
+                alert(&format!("No {} discs left, select the other disc type!", letter.to_char()));
+                return -1;
+            },
+            Err(e) => {
+                log!("{}", e);
+                return -1;
             }
-        }
-        if !done {
-            row = 3;
-        }
-        // log!("Adding token to row {}", row);
-        let token = self.player_token();
-        self.board.tokens[row as usize][column as usize] = token;
-        if token == 1 {
-            self.board.disc_map[row as usize][column as usize] = self.selected_disc;
-        } else {
-            self.board.disc_map[row as usize][column as usize] = self.computer_disc;
-        }
-        self.move_num += 1;
+        };
         self.draw();
-        self.check();
+        match outcome {
+            MoveOutcome::Win(player) => self.win(player.token()),
+            MoveOutcome::Draw => self.win(0),
+            MoveOutcome::Continue => (),
+        }
         self.print();
-        // self.animate(column, row as u8);
-        
+
         self.paused = true;
         return 1;
     }
 
-    fn check (&mut self) {
-        let (mut temp_r, mut temp_b, mut temp_br, mut temp_tr) = (['0'; 4], ['0'; 4], ['0'; 4], ['0'; 4]);
-        for i in 0..4 {
-            for j in 0..6 {
-                temp_r = ['0'; 4];
-                temp_b = ['0'; 4];
-                temp_br = ['0'; 4];
-                temp_tr = ['0'; 4];
-                for k in 0..=3 {
-                    //from (i,j) to right
-                    if j + k < 6 {
-                        temp_r[k] = self.board.disc_map[i][j + k];
-                    }
-                    //from (i,j) to bottom
-                    if i + k < 4 {
-                        temp_b[k] = self.board.disc_map[i + k][j];
-                    }
-
-                    //from (i,j) to bottom-right
-                    if i + k < 4 && j + k < 6 {
-                        temp_br[k] = self.board.disc_map[i + k][j + k];
-                    }
-
-                    //from (i,j) to top-right
-                    if (i - k) as i8 >= 0 && j + k < 6 {
-                        temp_tr[k] = self.board.disc_map[i - k][j + k];
-                    }
-                }
-
-                if temp_r[0] == 'T' && temp_r[1] == 'O' && temp_r[2] == 'O' && temp_r[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_r[0] == 'O' && temp_r[1] == 'T' && temp_r[2] == 'T' && temp_r[3] == 'O' {
-                    self.win(-1);
-                }
-                else if temp_b[0] == 'T' && temp_b[1] == 'O' && temp_b[2] == 'O' && temp_b[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_b[0] == 'O' && temp_b[1] == 'T' && temp_b[2] == 'T' && temp_b[3] == 'O' {
-                    self.win(-1);
-                }
-                else if temp_br[0] == 'T' && temp_br[1] == 'O' && temp_br[2] == 'O' && temp_br[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_br[0] == 'O' && temp_br[1] == 'T' && temp_br[2] == 'T' && temp_br[3] == 'O' {
-                    self.win(-1);
-                }
-                else if temp_tr[0] == 'T' && temp_tr[1] == 'O' && temp_tr[2] == 'O' && temp_tr[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_tr[0] == 'O' && temp_tr[1] == 'T' && temp_tr[2] == 'T' && temp_tr[3] == 'O' {
-                    self.win(-1);
-                }
-
-            }
-        }
-        // check if draw
-        if self.move_num == 24 && !self.won {
-            self.win(0);
-        }
-    }
-
     fn win(&mut self, player: i8) {
         self.paused = true;
         self.won = true;
+        self.game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
         self.game.game_date = Date::new_0().get_time() as i64; // Set date using js_sys::Date (chrono does not seem to work)
         let mut msg = "".to_string();
         if player > 0 {
@@ -575,28 +639,19 @@ impl TootOttoComputer {
         log!("{}", msg);
 
         // Save game using API
-        self.get_games_list();
-    }
-
-    // Returns i if it is the player's token, else -1 (for computer)
-    fn player_token (&self) -> i8 {
-        if self.move_num %2 == 0 {
-            return 1;
-        } else {
-            return -1;
-        }
+        self.save_game();
     }
 
     // Print board, disc map, and move number
     fn print(&self) {
         let (mut msg, mut disc_msg) = ("".to_string(), "".to_string());
         msg.push_str("\n");
-        msg.push_str(format!("Move: {}", self.move_num).as_str());
+        msg.push_str(format!("Move: {}", self.state.move_num()).as_str());
         msg.push_str("\n");
         for i in 0..4 {
             for j in 0..6 {
-                msg.push_str(format!(" {}", self.board.tokens[i][j]).as_str());
-                disc_msg.push_str(format!(" {}", self.board.disc_map[i][j]).as_str());
+                msg.push_str(format!(" {}", self.state.board().tokens[i][j]).as_str());
+                disc_msg.push_str(format!(" {}", self.state.board().disc_map[i][j]).as_str());
             }
             msg.push_str("\n");
             disc_msg.push_str("\n");
@@ -605,392 +660,15 @@ impl TootOttoComputer {
         log!("{}", disc_msg);
     }
 
-    fn get_games_list(&mut self) {
-        // Create GET request for list of games
-        let get_request = Request::get("http://localhost:8000/list-games")
-            .body(Nothing)
-            .unwrap();
-
-        // Create task for FetchService
-        let task = FetchService::new().fetch(
-            get_request,
-            self.link.callback(|response: Response<Json<Result<Vec<Game>, Error>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::GetGamesList(body);
-                    }
-                }
-                Msg::SaveError
-            }),
-        );
-
-        // Store reference to task
-        self.save_task = Some(task);
-    }
-
+    // Ask the worker for the computer's move; the answer arrives as `Msg::AiMoved`
     fn ai(&mut self) {
-        let curr_state = self.board.disc_map.clone();
-
-        // On success, returns an array of size 2 -> index 0 gives filled map with char 'T', index 1 gives filled map with char 'O'
-        fn fill_map(state: [[char; 6]; 4], column: i64) -> Result<[[[char; 6]; 4]; 2], i8> {
-            if state[0][column as usize] != '0' || column < 0 || column > 5 {
-                return Err(-1);
-            }
-    
-            let mut done = false;
-            let mut row = 0;
-            for i in 0..3 {
-                if state[i + 1][column as usize] != '0' {
-                    done = true;
-                    row = i;
-                    break;
-                }
-            }
-    
-            if !done {
-                row = 3;
-            }
-    
-            let mut temp_map_T = state.clone();
-            let mut temp_map_O = state.clone();
-
-            temp_map_T[row][column as usize] = 'T';
-            temp_map_O[row][column as usize] = 'O';
-
-            return Ok([temp_map_T, temp_map_O]);
-        }
-
-        // Fill map but with choice option
-        fn fill_map_choice(state: [[char; 6]; 4], column: i64, choice: char) -> Result<[[char; 6]; 4], i8> {
-            if state[0][column as usize] != '0' || column < 0 || column > 5 {
-                return Err(-1);
-            }
-    
-            let mut done = false;
-            let mut row = 0;
-            for i in 0..3 {
-                if state[i + 1][column as usize] != '0' {
-                    done = true;
-                    row = i;
-                    break;
-                }
-            }
-    
-            if !done {
-                row = 3;
-            }
-    
-            let mut temp_map = state.clone();
-
-            temp_map[row][column as usize] = choice;
-
-            return Ok(temp_map);
-        }
-
-        fn check_state(state: [[char; 6]; 4]) -> i8 {
-            let (mut temp_r, mut temp_b, mut temp_br, mut temp_tr) = (['0'; 4], ['0'; 4], ['0'; 4], ['0'; 4]);
-            for i in 0..4 {
-                for j in 0..6 {
-                    temp_r = ['0'; 4];
-                    temp_b = ['0'; 4];
-                    temp_br = ['0'; 4];
-                    temp_tr = ['0'; 4];
-                    for k in 0..=3 {
-                        //from (i,j) to right
-                        if j + k < 6 {
-                            temp_r[k] = state[i][j + k];
-                        }
-                        //from (i,j) to bottom
-                        if i + k < 4 {
-                            temp_b[k] = state[i + k][j];
-                        }
-
-                        //from (i,j) to bottom-right
-                        if i + k < 4 && j + k < 6 {
-                            temp_br[k] = state[i + k][j + k];
-                        }
-
-                        //from (i,j) to top-right
-                        if (i - k) as i8 >= 0 && j + k < 6 {
-                            temp_tr[k] = state[i - k][j + k];
-                        }
-                    }
-
-                    // Computer wins if spell "OTTO"
-                    if temp_r[0] == 'T' && temp_r[1] == 'O' && temp_r[2] == 'O' && temp_r[3] == 'T' {
-                        return -1;
-                    }
-                    else if temp_r[0] == 'O' && temp_r[1] == 'T' && temp_r[2] == 'T' && temp_r[3] == 'O' {
-                        return 1;
-                    }
-                    else if temp_b[0] == 'T' && temp_b[1] == 'O' && temp_b[2] == 'O' && temp_b[3] == 'T' {
-                        return -1;
-                    }
-                    else if temp_b[0] == 'O' && temp_b[1] == 'T' && temp_b[2] == 'T' && temp_b[3] == 'O' {
-                        return 1;
-                    }
-                    else if temp_br[0] == 'T' && temp_br[1] == 'O' && temp_br[2] == 'O' && temp_br[3] == 'T' {
-                        return -1;
-                    }
-                    else if temp_br[0] == 'O' && temp_br[1] == 'T' && temp_br[2] == 'T' && temp_br[3] == 'O' {
-                        return 1;
-                    }
-                    else if temp_tr[0] == 'T' && temp_tr[1] == 'O' && temp_tr[2] == 'O' && temp_tr[3] == 'T' {
-                        return -1;
-                    }
-                    else if temp_tr[0] == 'O' && temp_tr[1] == 'T' && temp_tr[2] == 'T' && temp_tr[3] == 'O' {
-                        return 1;
-                    }
-
-                }
-            }
-            
-            // Return 0 (no win/lose)
-            0
-        }
-        
-        fn value(state: [[char; 6]; 4], depth: i64, max_depth: &i64, choice: Option<char>, alpha: i64, beta: i64) -> i64 {
-            let val = check_state(state);
-
-            // depth changes the difficulty (less depth = easier)
-            if depth >= *max_depth {
-                // If it lead to winning, then do it
-                if val == 1 { // AI win, AI wants to win of course
-                    return 999999 - depth * depth; // Less value if it is later on in the game
-                } else if val == -1 { // AI lose, AI hates losing
-                    return -999999 + depth * depth;
-                }
-                return 0;
-            }
-            
-            // if already won, then return the value right away
-            if val == 1 { // AI win, AI wants to win of course
-                return 999999; // Less value if it is later on in the game
-            } else if val == -1 { // AI lose, AI hates losing
-                return -999999;
-            }
-
-            if depth % 2 == 0 {
-                return min_state(state, depth + 1, max_depth, choice, alpha, beta)[0];
-            }
-            return max_state(state, depth + 1, max_depth, choice, alpha, beta)[0];
-        }
-
-        fn value_states(states: [[[char; 6]; 4]; 2], depth: i64, max_depth: &i64, choice: Option<char>, alpha: i64, beta: i64) -> i64 {
-            let val_1 = check_state(states[0]);
-            let val_2 = check_state(states[1]);
-
-            // depth changes the difficulty (less depth = easier)
-            if depth >= *max_depth {
-                // If it lead to winning, then do it
-                if val_1 == 1 || val_2 == 1 { // AI win, AI wants to win of course
-                    return 999999 - depth * depth; // Less value if it is later on in the game
-                } else if val_1 == -1 || val_2 == -1 { // AI lose, AI hates losing
-                    return -999999 + depth * depth;
-                }
-                return 0;
-            }
-            
-            // if already won, then return the value right away
-            if val_1 == 1 || val_2 == 1 { // AI win, AI wants to win of course
-                return 999999; // Less value if it is later on in the game
-            } else if val_1 == -1 || val_2 == -1 { // AI lose, AI hates losing
-                return -999999;
-            }
-
-            if depth % 2 == 0 {
-                return min(min_state(states[0], depth + 1, max_depth, choice, alpha, beta)[0], min_state(states[1], depth + 1, max_depth, choice, alpha, beta)[0]);
-            }
-            return max(max_state(states[0], depth + 1, max_depth, choice, alpha, beta)[0], max_state(states[1], depth + 1, max_depth, choice, alpha, beta)[0]);
-        }
-
-        fn choose(choice: Vec<i64>) -> i64 {
-            // Use js_sys::Math to find random value (rand does not seem to work)
-            return choice[(Math::random() * choice.len() as f64).floor() as usize]
-        }
-
-        // Returns [value, choice (column chosen)]
-        fn max_state(state: [[char; 6]; 4], depth: i64, max_depth: &i64, choice: Option<char>, alpha: i64, beta: i64) -> [i64; 2] {
-            let mut alpha = alpha;
-            let mut v = -100000000007;
-            let mut move_col = -1;
-            let mut temp_val: i64;
-            let mut move_queue = Vec::<i64>::new();
-            for j in 0..6 {
-                match choice {
-                    Some(choice) => {
-                        if let Ok(temp_state) = fill_map_choice(state, j, choice) {
-                            temp_val = value(temp_state, depth, max_depth, None, alpha, beta);
-        
-                            if temp_val > v {
-                                v = temp_val;
-                                move_col = j;
-                                move_queue.clear();
-                                move_queue.push(j);
-                            } else if temp_val == v {
-                                move_queue.push(j);
-                            }
-        
-                            // alpha-beta pruning
-                            if v > beta {
-                                move_col = choose(move_queue);
-                                return [v - depth * depth, move_col];
-                            }
-                            alpha = max(alpha, v);
-        
-                        }
-                    },
-                    None => {
-                        if let Ok(temp_states) = fill_map(state, j) {
-                            temp_val = value_states(temp_states, depth, max_depth, None, alpha, beta);
-
-                            if temp_val > v {
-                                v = temp_val;
-                                move_col = j;
-                                move_queue.clear();
-                                move_queue.push(j);
-                            } else if temp_val == v {
-                                move_queue.push(j);
-                            }
-        
-                            // alpha-beta pruning
-                            if v > beta {
-                                move_col = choose(move_queue);
-                                return [v - depth * depth, move_col];
-                            }
-                            alpha = max(alpha, v);
-        
-                        }
-                    }
-                }
-            }
-
-            // Randomly choose from move queue
-            move_col = choose(move_queue);
-            // Return the move to make
-            [v - depth * depth, move_col]
-        }
-
-        // Returns [value, choice (column chosen)]
-        fn min_state(state: [[char; 6]; 4], depth: i64, max_depth: &i64, choice: Option<char>, alpha: i64, beta: i64) -> [i64; 2] {
-            let mut beta = beta;
-            let mut v = 100000000007;
-            let mut move_col = -1;
-            let mut temp_val: i64;
-            let mut move_queue = Vec::<i64>::new();
-            for j in 0..6 {
-                match choice {
-                    Some(choice) => {
-                        if let Ok(temp_states) = fill_map_choice(state, j, choice) {
-                            temp_val = value(temp_states, depth, max_depth, None, alpha, beta);
-        
-                            if temp_val < v {
-                                v = temp_val;
-                                move_col = j;
-                                move_queue.clear();
-                                move_queue.push(j);
-                            } else if temp_val == v {
-                                move_queue.push(j);
-                            }
-        
-                            // alpha-beta pruning
-                            if v < alpha {
-                                move_col = choose(move_queue);
-                                return [v + depth * depth, move_col];
-                            }
-                            beta = min(beta, v);
-                        }
-                    },
-                    None => {
-                        if let Ok(temp_states) = fill_map(state, j) {
-                            temp_val = value_states(temp_states, depth, max_depth, None, alpha, beta);
-        
-                            if temp_val < v {
-                                v = temp_val;
-                                move_col = j;
-                                move_queue.clear();
-                                move_queue.push(j);
-                            } else if temp_val == v {
-                                move_queue.push(j);
-                            }
-        
-                            // alpha-beta pruning
-                            if v < alpha {
-                                move_col = choose(move_queue);
-                                return [v + depth * depth, move_col];
-                            }
-                            beta = min(beta, v);
-                        }
-                    }
-                }
-            }
-
-            // Randomly choose from move queue
-            move_col = choose(move_queue);
-            // Return the move to make
-            [v + depth * depth, move_col]
-        }
-
-        // Calculate max/min states
-        let [value_T_max, column_T_max] = max_state(curr_state, 0, &self.max_depth, Some('T'), -100000000007, 100000000007);
-        let [value_T_min, column_T_min] = min_state(curr_state, 0, &self.max_depth, Some('T'), -100000000007, 100000000007);
-        let [value_O_max, column_O_max] = max_state(curr_state, 0, &self.max_depth, Some('O'), -100000000007, 100000000007);
-        let [value_O_min, column_O_min] = min_state(curr_state, 0, &self.max_depth, Some('O'), -100000000007, 100000000007);
-        
-        // log!("Max state: AI Disc type: T chosen column: {} (value: {})", column_T_max, value_T_max);
-        // log!("Min state: AI Disc type: T chosen column: {} (value: {})", column_T_min, value_T_min);
-        // log!("Max state: AI Disc type: O chosen column: {} (value: {})", column_O_max, value_O_max);
-        // log!("Min state: AI Disc type: O chosen column: {} (value: {})", column_O_min, value_O_min);
-
-        let mut msg = "".to_string();
-
-        if value_T_max == 999999 || value_O_max == 999999 { // If there is a win condition, play it immediately
-            // Will win in the next move if play these discs...
-            if value_T_max > value_O_max {
-                // Choosing action T will result in win
-                self.computer_disc = 'T';
-                self.action(column_T_max);
-                msg.push_str(format!("AI T choose column: {} (value: {})", column_T_max, value_T_max).as_str());
-            } else {
-                // Choosing action O will result in win
-                self.computer_disc = 'O';
-                self.action(column_O_max);
-                msg.push_str(format!("AI T choose column: {} (value: {})", column_O_max, value_O_max).as_str());
-            }
-        } else if value_T_min == -999999 || value_O_min == -999999 { // If there is a lose condition, block it immediately
-            // Will lose in next two moves if play those discs...
-            if value_T_min < value_O_min {
-                // Choosing action O will result in block
-                self.computer_disc = 'O';
-                self.action(column_T_min);
-                msg.push_str(format!("AI O choose column: {} (value: {})", column_T_min, value_T_min).as_str());
-            } else {
-                // Choosing action T will result in block
-                self.computer_disc = 'T';
-                self.action(column_O_min);
-                msg.push_str(format!("AI T choose column: {} (value: {})", column_O_min, value_O_min).as_str());
-            }
-        } else if value_T_max == value_O_max {
-            // Choose a random action if they are both equal
-            let choices = [('T', column_T_max), ('O', column_O_max)];
-            let random_choice = (Math::random() * 2.0).floor();
-            self.computer_disc = choices[random_choice as usize].0;
-            self.action(choices[random_choice as usize].1);
-            msg.push_str(format!("AI {} choose column: {} (value: {})", choices[random_choice as usize].0, choices[random_choice as usize].1, value_T_max).as_str());
-        } else if value_T_max > value_O_max {
-            // Choose action T with higher value
-            self.computer_disc = 'T';
-            self.action(column_T_max);
-            msg.push_str(format!("AI T choose column: {} (value: {})", column_T_max, value_T_max).as_str());
-        } else {
-            // Choose action O with higher value
-            self.computer_disc = 'O';
-            self.action(column_O_max);
-            msg.push_str(format!("AI T choose column: {} (value: {})", column_O_max, value_O_max).as_str());
-        }
-
-        // Print AI's move
-        log!("{}", msg);
+        self.requests += 1;
+        self.thinking = Some(self.requests);
+        self.ai.send(ai::Request::TootOtto {
+            id: self.requests,
+            moves: self.state.moves().to_vec(),
+            playouts: self.playouts
+        });
     }
 
     fn save_game(&mut self) {
@@ -998,21 +676,27 @@ impl TootOttoComputer {
         let json_game = json!{self.game};
 
         // Create POST request to save game
-        let post_request = Request::post("http://localhost:8000/insert-game")
-            .header("Content-Type", "application/json")
+        let mut post_request = Request::post(api::url("/insert-game"))
+            .header("Content-Type", "application/json");
+        // Games played under an account are only accepted from that account
+        if let Some(account) = &self.account {
+            post_request = post_request.header("Authorization", account.bearer());
+        }
+        let post_request = post_request
             .body(Json(&json_game))
             .expect("Failed to build request.");
 
         // Create save task
         let task = FetchService::new().fetch(
             post_request,
-            self.link.callback(|response: Response<Result<String, Error>>| {
+            self.link.callback(|response: Response<Json<Result<Game, Error>>>| {
                 log!("In callback function");
-                if response.status().is_success() {
-                    Msg::GameSaved
-                } else {
-                    Msg::SaveError
+                if let (meta, Json(Ok(saved))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::GameSaved(saved);
+                    }
                 }
+                Msg::SaveError
             }),
         );
 
@@ -1023,20 +707,22 @@ impl TootOttoComputer {
     fn reset(&mut self) {
         self.clear();
         self.game = Game {
-            game_number: 0, // placeholder, when game is saved this can be set
+            game_number: 0, // placeholder, the backend numbers the game when it is saved
             game_type: "TOOT-OTTO".into(),
-            player1_name: "".into(),
+            player1_name: session::player_name(&self.account),
             player2_name: "Computer".into(),
             winner_name: "".into(),
-            game_date: 0 // placeholder, when game is saved this can be set
+            game_date: 0, // placeholder, when game is saved this can be set
+            ..Game::default()
         };
-        self.selected_disc = 'T';
+        self.selected_disc = Letter::T;
         self.game_started = false;
-        self.board.tokens = [[0; 6]; 4];
-        self.board.disc_map = [['0'; 6]; 4];
-        self.move_num = 0;
+        self.state = TootOttoState::new();
         self.won = false;
         self.paused = false;
+        self.redo.clear();
+        self.thinking = None;
+        self.show_notation = false;
     }
 
     fn context(&self) -> &CanvasRenderingContext2d {
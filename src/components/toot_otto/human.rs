@@ -3,31 +3,51 @@ use wasm_bindgen::prelude::*;
 use wasm_bindgen::JsCast;
 use web_sys::{CanvasRenderingContext2d, HtmlCanvasElement, Window};
 use yew::services::fetch::{Request, Response, FetchService, FetchTask};
-use yew::format::{Json, Nothing};
+use yew::services::websocket::WebSocketStatus;
+use yew::format::Json;
 use anyhow::Error;
 use serde_json::json;
 use js_sys::Date;
-use crate::models::game_boards::TootOttoGameBoard;
+use crate::models::notation;
+use crate::models::account::Session;
+use crate::components::utils::{api, session};
+use crate::models::online::{ClientMessage, ServerMessage};
+use crate::models::player::Player;
+use crate::components::utils::online::RoomConnection;
+use crate::models::toot_otto::{self, TootOttoState, TootOttoMove, Letter, MoveOutcome, MoveError};
 use std::f64;
 use crate::alert;
 
 extern crate models;
 use models::game::Game;
 
+// Half the spacing between columns, so a click anywhere over the board picks the nearest column
+const CLICK_RADIUS: f64 = 37.5;
+
 pub struct TootOttoHuman {
     link: ComponentLink<Self>,
     game: Game,
-    selected_disc: char,
+    selected_disc: Letter,
     game_started: bool,
     context: Option<CanvasRenderingContext2d>,
-    board: TootOttoGameBoard,
-    move_num: u8,
+    state: TootOttoState,
     won: bool,
     paused: bool,
     save_task: Option<Result<FetchTask, Error>>,
+    // Account logged in on this browser, which plays as Player 1
+    account: Option<Session>,
     player1_color: String,
     player2_color: String,
-    board_color: String
+    board_color: String,
+    // Moves taken back by Undo, most recent last
+    redo: Vec<TootOttoMove>,
+    // Notation of the game so far is shown under the board
+    show_notation: bool,
+    // Connection to the game room while playing online, and the side played here
+    room: Option<RoomConnection>,
+    online_player: Option<Player>,
+    room_code: String,
+    online_status: String
 }
 
 macro_rules! log {
@@ -46,12 +66,19 @@ pub enum Msg {
     DiscTSelected,
     DiscOSelected,
     ClickedBoard(MouseEvent),
-    GetGamesList(Vec<Game>),
-    GameSaved,
+    GameSaved(Game),
     SaveError,
     Player1ColorChange(String),
     Player2ColorChange(String),
-    BoardColorChange(String)
+    BoardColorChange(String),
+    ClickedUndo,
+    ClickedRedo,
+    ClickedExport,
+    RoomCodeInput(String),
+    ClickedCreateRoom,
+    ClickedJoinRoom,
+    RoomStatus(WebSocketStatus),
+    RoomMessage(Result<ServerMessage, Error>)
 }
 
 impl Component for TootOttoHuman {
@@ -59,32 +86,35 @@ impl Component for TootOttoHuman {
     type Properties = ();
 
     fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let account = session::current();
         TootOttoHuman {
             link,
             game: Game {
-                game_number: 0, // placeholder, when game is saved this can be set
+                game_number: 0, // placeholder, the backend numbers the game when it is saved
                 game_type: "TOOT-OTTO".into(),
-                player1_name: "".into(),
+                player1_name: session::player_name(&account),
                 player2_name: "".into(),
                 winner_name: "".into(),
-                game_date: 0 // placeholder, when game is saved this can be set
+                game_date: 0, // placeholder, when game is saved this can be set
+                ..Game::default()
             },
-            selected_disc: 'T',
+            selected_disc: Letter::T,
             game_started: false,
             context: None,
-            board: TootOttoGameBoard {
-                rows: 4,
-                columns: 6,
-                tokens: [[0; 6]; 4],
-                disc_map: [['0'; 6]; 4]
-            },
-            move_num: 0,
+            state: TootOttoState::new(),
             won: false,
             paused: false,
             save_task: None,
+            account,
             player1_color: "#99ffcc".into(),
             player2_color: "#ffff99".into(),
             board_color: "#00bfff".into(),
+            redo: Vec::new(),
+            show_notation: false,
+            room: None,
+            online_player: None,
+            room_code: String::new(),
+            online_status: String::new()
         }
     }
 
@@ -109,16 +139,21 @@ impl Component for TootOttoHuman {
                 } else if self.game.player1_name == self.game.player2_name {
                     alert("Player names must be different!");
                 } else {
+                    // Settings are saved with the game so it can be replayed
+                    self.game.columns = toot_otto::COLUMNS as i32;
+                    self.game.rows = toot_otto::ROWS as i32;
+                    self.game.player1_color = self.player1_color.clone();
+                    self.game.player2_color = self.player2_color.clone();
                     self.game_started = true;
                     self.draw_board();
                     self.print();
                 }
             },
             Msg::DiscTSelected => {
-                self.selected_disc = 'T';
+                self.selected_disc = Letter::T;
             },
             Msg::DiscOSelected => {
-                self.selected_disc = 'O';
+                self.selected_disc = Letter::O;
             },
             Msg::ClickedBoard(event) => {
                 if !self.game_started {
@@ -135,26 +170,32 @@ impl Component for TootOttoHuman {
                 // log!("x: {} y: {}", x,y);
 
                 for i in 0..7 {
-                    if self.on_region([x, y], (75 * i + 100) as f64, 25.0){
+                    if self.on_region([x, y], (75 * i + 100) as f64, CLICK_RADIUS){
                         // log!("Region {} clicked", i);
                         self.paused = false;
-                        let valid = self.action(i as f64);
+                        if let Some(player) = self.online_player {
+                            // Online moves are played once the server sends them back
+                            if self.state.current_player() == player {
+                                let mv = TootOttoMove { column: i, letter: self.selected_disc };
+                                if let Some(room) = self.room.as_mut() {
+                                    room.send(&ClientMessage::Move(mv.into()));
+                                }
+                            }
+                            break;
+                        }
+                        let valid = self.action(i, self.selected_disc);
                         if valid == 1 {
-                            //Reject Click
+                            // A new move makes the undone ones unreachable
+                            self.redo.clear();
                         }
                         break; //because there will be no 2 points that are clicked at a time
                     }
                     
                 }
             },
-            Msg::GetGamesList(games) => {
-                log!("Obtained games list");
-                self.game.game_number = games.len() as i32 + 1;
-                self.save_task = None;
-                self.save_game();
-            },
-            Msg::GameSaved => {
-                log!("Successfully saved");
+            Msg::GameSaved(saved) => {
+                log!("Successfully saved as game {}", saved.game_number);
+                self.game.game_number = saved.game_number;
                 self.save_task = None;
             },
             Msg::SaveError => log!("Game failed to save"),
@@ -167,6 +208,74 @@ impl Component for TootOttoHuman {
             Msg::BoardColorChange(new_value) => {
                 self.board_color = new_value;
             },
+            Msg::ClickedUndo => {
+                if self.won {
+                    return false;
+                }
+                if let Some(mv) = self.state.undo() {
+                    self.redo.push(mv);
+                    self.paused = false;
+                    self.redraw();
+                }
+            },
+            Msg::ClickedExport => self.show_notation = !self.show_notation,
+            Msg::RoomCodeInput(new_value) => {
+                self.room_code = new_value;
+            },
+            Msg::ClickedCreateRoom => {
+                if self.game.player1_name.is_empty() {
+                    alert("Player names can't be blank!");
+                } else if self.game.player1_name == "Computer" {
+                    alert("Computer is a reserved name!");
+                } else {
+                    self.open_room(ClientMessage::Create {
+                        name: self.game.player1_name.clone(),
+                        game_type: self.game.game_type.clone(),
+                        columns: toot_otto::COLUMNS as i32,
+                        rows: toot_otto::ROWS as i32,
+                        connect: 0,
+                        token: self.account.as_ref().map(|account| account.token.clone())
+                    });
+                }
+            },
+            Msg::ClickedJoinRoom => {
+                if self.game.player1_name.is_empty() {
+                    alert("Player names can't be blank!");
+                } else if self.game.player1_name == "Computer" {
+                    alert("Computer is a reserved name!");
+                } else if self.room_code.trim().is_empty() {
+                    alert("Enter the code of the room to join!");
+                } else {
+                    self.open_room(ClientMessage::Join {
+                        code: self.room_code.clone(),
+                        name: self.game.player1_name.clone(),
+                        token: self.account.as_ref().map(|account| account.token.clone())
+                    });
+                }
+            },
+            Msg::RoomStatus(status) => match status {
+                WebSocketStatus::Opened => {
+                    if let Some(room) = self.room.as_mut() {
+                        room.opened();
+                    }
+                },
+                WebSocketStatus::Closed | WebSocketStatus::Error => {
+                    if self.room.take().is_some() && !self.won {
+                        alert("Lost the connection to the game server");
+                        self.reset();
+                    }
+                },
+            },
+            Msg::RoomMessage(message) => self.room_message(message),
+            Msg::ClickedRedo => {
+                if self.won {
+                    return false;
+                }
+                if let Some(mv) = self.redo.pop() {
+                    self.paused = false;
+                    self.action(mv.column, mv.letter);
+                }
+            },
         }
         true
     }
@@ -203,23 +312,23 @@ impl Component for TootOttoHuman {
                     </div>
                     <form class="w3-row-padding">
                         <label>{"Select a Disc Type: "}</label>
-                        <label for="discT" style="padding:8px;">{"T"}</label>
+                        <label for="discT" style="padding:8px;">{format!("T ({} left)", self.state.discs_left(self.state.current_player(), Letter::T))}</label>
                         <input
                             class="w3-radio"
                             id="discT"
                             type="radio"
                             name="choice"
                             value="T"
-                            checked=(self.selected_disc == 'T')
+                            checked=(self.selected_disc == Letter::T)
                             onclick=self.link.callback(|_| Msg::DiscTSelected) />
-                        <label for="discO" style="padding:8px;">{"O"}</label>
+                        <label for="discO" style="padding:8px;">{format!("O ({} left)", self.state.discs_left(self.state.current_player(), Letter::O))}</label>
                         <input
                             class="w3-radio"
                             id="discO"
                             type="radio"
                             name="choice"
                             value="O"
-                            checked=(self.selected_disc == 'O')
+                            checked=(self.selected_disc == Letter::O)
                             onclick=self.link.callback(|_| Msg::DiscOSelected) />
                     </form>
                     <br/>
@@ -246,7 +355,7 @@ impl Component for TootOttoHuman {
                                     class="w3-input w3-border w3-round"
                                     id="nameInput1"
                                     type="text"
-                                    disabled=self.game_started
+                                    disabled=self.game_started || self.account.is_some()
                                     value=&self.game.player1_name
                                     oninput=self.link.callback(|e: InputData| Msg::GotPlayer1Input(e.value))
                                     placeholder="Enter name"
@@ -306,7 +415,7 @@ impl Component for TootOttoHuman {
                             <input
                                 class="w3-button w3-border w3-block"
                                 id="startButton"
-                                disabled=self.game_started
+                                disabled=self.game_started || self.room.is_some()
                                 type="submit"
                                 value="Start Game"
                             />
@@ -314,7 +423,9 @@ impl Component for TootOttoHuman {
                     </form>
                 </div>
 
+                {self.view_online_options()}
                 {game_details}
+                {self.view_history_buttons()}
 
                 <canvas
                     onclick=self.link.callback(|e| Msg::ClickedBoard(e))
@@ -328,6 +439,76 @@ impl Component for TootOttoHuman {
 }
 
 impl TootOttoHuman {
+    // Undo and Redo buttons, shown once the game has started
+    // Create or join a room to play someone on another browser
+    fn view_online_options(&self) -> Html {
+        let busy = self.game_started || self.room.is_some();
+        html! {
+            <div class="w3-container">
+                <h5 class="w3-xlarge w3-text-red"><b>{"Play Online"}</b></h5>
+                <p>{"Create a room and share its code, or join a room with the code you were given. You play under the Player 1 name."}</p>
+                <div class="w3-row-padding" style="padding:4px;">
+                    <div class="w3-quarter">
+                        <button class="w3-button w3-border w3-block" disabled=busy
+                            onclick=self.link.callback(|_| Msg::ClickedCreateRoom)>
+                            {"Create Room"}
+                        </button>
+                    </div>
+                    <div class="w3-quarter">
+                        <input class="w3-input w3-border w3-round" type="text" placeholder="Room code"
+                            value=&self.room_code disabled=busy
+                            oninput=self.link.callback(|e: InputData| Msg::RoomCodeInput(e.value))/>
+                    </div>
+                    <div class="w3-quarter">
+                        <button class="w3-button w3-border w3-block" disabled=busy
+                            onclick=self.link.callback(|_| Msg::ClickedJoinRoom)>
+                            {"Join Room"}
+                        </button>
+                    </div>
+                </div>
+                <p>{&self.online_status}</p>
+            </div>
+        }
+    }
+
+    fn view_history_buttons(&self) -> Html {
+        if !self.game_started {
+            return html! {};
+        }
+        html! {
+            <div class="w3-container w3-row-padding" style="padding:4px;">
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.online_player.is_some() || self.state.moves().is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedUndo)>
+                    {"Undo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    disabled=self.won || self.online_player.is_some() || self.redo.is_empty()
+                    onclick=self.link.callback(|_| Msg::ClickedRedo)>
+                    {"Redo"}
+                </button>
+                <button class="w3-button w3-border w3-round"
+                    onclick=self.link.callback(|_| Msg::ClickedExport)>
+                    {if self.show_notation { "Hide Notation" } else { "Export" }}
+                </button>
+                {self.view_notation()}
+            </div>
+        }
+    }
+
+    // Text of the game so far, to copy into another tool
+    fn view_notation(&self) -> Html {
+        if !self.show_notation {
+            return html! {};
+        }
+        let mut game = self.game.clone();
+        game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
+        html! {
+            <textarea class="w3-input w3-border" style="margin-top:4px;" rows="10" readonly=true
+                value=notation::to_notation(&game) />
+        }
+    }
+
     fn draw_board(&self) {
         let context = self.context();
 
@@ -347,21 +528,22 @@ impl TootOttoHuman {
     }
 
     fn draw(&self){
+        let board = self.state.board();
         let mut fg_color = "transparent".to_string();
         for y in 0..4 {
             for x in 0..6 {
                 let mut text = ' ';
                 fg_color = "transparent".to_string();
-                if self.board.tokens[y][x] >= 1 && self.board.disc_map[y][x] == 'T' {
+                if board.tokens[y][x] >= 1 && board.disc_map[y][x] == 'T' {
                     fg_color = self.player1_color.clone();
                     text = 'T';
-                } else if self.board.tokens[y][x] >= 1 && self.board.disc_map[y][x] == 'O' {
+                } else if board.tokens[y][x] >= 1 && board.disc_map[y][x] == 'O' {
                     fg_color = self.player1_color.clone();
                     text = 'O';
-                } else if self.board.tokens[y][x] <= -1_i8 && self.board.disc_map[y][x] == 'T' {
+                } else if board.tokens[y][x] <= -1_i8 && board.disc_map[y][x] == 'T' {
                     fg_color = self.player2_color.clone();
                     text = 'T';
-                } else if self.board.tokens[y][x] <= -1_i8 && self.board.disc_map[y][x] == 'O' {
+                } else if board.tokens[y][x] <= -1_i8 && board.disc_map[y][x] == 'O' {
                     fg_color = self.player2_color.clone();
                     text = 'O';
                 }
@@ -412,6 +594,13 @@ impl TootOttoHuman {
         return false;
     }
 
+    // Repaint the board from scratch, after discs were taken off it
+    fn redraw(&self) {
+        self.clear();
+        self.draw_board();
+        self.draw();
+    }
+
     fn clear(&self) {
         let context = self.context();
         let canvas = self.canvas();
@@ -441,107 +630,37 @@ impl TootOttoHuman {
     //     }
     // }
 
-    fn action(&mut self, column: f64) -> i8{
+    fn action(&mut self, column: usize, letter: Letter) -> i8 {
         if self.paused || self.won {
             return 0;
         }
-        if self.board.tokens[0][column as usize] != 0 || column < 0.0 || column > 6.0 {
-            return -1;
-        }
-
-        let mut row = 0;
-        let mut done = false;
-        for i in 0..3 {
-            match self.board.tokens[i + 1][column as usize] {
-                0 => continue,
-                _=> {
-                    done = true;
-                    row = i;
-                    break;
-                }
+        let outcome = match self.state.play(TootOttoMove { column, letter }) {
+            Ok(outcome) => outcome,
+            Err(MoveError::OutOfDiscs(letter)) => {
+                alert(&format!("No {} discs left, select the other disc type!", letter.to_char()));
+                return -1;
+            },
+            Err(e) => {
+                log!("{}", e);
+                return -1;
             }
-        }
-        if !done {
-            row = 3;
-        }
-        // log!("Adding token to row {}", row);
-        self.board.tokens[row as usize][column as usize] = self.player_token();
-        self.board.disc_map[row as usize][column as usize] = self.selected_disc;
-        self.move_num += 1;
+        };
         self.draw();
-        self.check();
+        match outcome {
+            MoveOutcome::Win(player) => self.win(player.token()),
+            MoveOutcome::Draw => self.win(0),
+            MoveOutcome::Continue => (),
+        }
         self.print();
-        // self.animate(column, row as u8);
-        
+
         self.paused = true;
         return 1;
     }
 
-    fn check (&mut self) {
-        let (mut temp_r, mut temp_b, mut temp_br, mut temp_tr) = (['0'; 4], ['0'; 4], ['0'; 4], ['0'; 4]);
-        for i in 0..4 {
-            for j in 0..6 {
-                temp_r = ['0'; 4];
-                temp_b = ['0'; 4];
-                temp_br = ['0'; 4];
-                temp_tr = ['0'; 4];
-                for k in 0..=3 {
-                    //from (i,j) to right
-                    if j + k < 6 {
-                        temp_r[k] = self.board.disc_map[i][j + k];
-                    }
-                    //from (i,j) to bottom
-                    if i + k < 4 {
-                        temp_b[k] = self.board.disc_map[i + k][j];
-                    }
-
-                    //from (i,j) to bottom-right
-                    if i + k < 4 && j + k < 6 {
-                        temp_br[k] = self.board.disc_map[i + k][j + k];
-                    }
-
-                    //from (i,j) to top-right
-                    if (i - k) as i8 >= 0 && j + k < 6 {
-                        temp_tr[k] = self.board.disc_map[i - k][j + k];
-                    }
-                }
-
-                if temp_r[0] == 'T' && temp_r[1] == 'O' && temp_r[2] == 'O' && temp_r[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_r[0] == 'O' && temp_r[1] == 'T' && temp_r[2] == 'T' && temp_r[3] == 'O' {
-                    self.win(-1);
-                }
-                else if temp_b[0] == 'T' && temp_b[1] == 'O' && temp_b[2] == 'O' && temp_b[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_b[0] == 'O' && temp_b[1] == 'T' && temp_b[2] == 'T' && temp_b[3] == 'O' {
-                    self.win(-1);
-                }
-                else if temp_br[0] == 'T' && temp_br[1] == 'O' && temp_br[2] == 'O' && temp_br[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_br[0] == 'O' && temp_br[1] == 'T' && temp_br[2] == 'T' && temp_br[3] == 'O' {
-                    self.win(-1);
-                }
-                else if temp_tr[0] == 'T' && temp_tr[1] == 'O' && temp_tr[2] == 'O' && temp_tr[3] == 'T' {
-                    self.win(1);
-                }
-                else if temp_tr[0] == 'O' && temp_tr[1] == 'T' && temp_tr[2] == 'T' && temp_tr[3] == 'O' {
-                    self.win(-1);
-                }
-
-            }
-        }
-        // check if draw
-        if self.move_num == 24 && !self.won {
-            self.win(0);
-        }
-    }
-
     fn win(&mut self, player: i8) {
         self.paused = true;
         self.won = true;
+        self.game.moves = self.state.moves().iter().map(|&mv| mv.into()).collect();
         self.game.game_date = Date::new_0().get_time() as i64; // Set date using js_sys::Date (chrono does not seem to work)
         let mut msg = "".to_string();
         if player > 0 {
@@ -568,16 +687,9 @@ impl TootOttoHuman {
         // Print final state
         log!("{}", msg);
 
-        // Save game using API
-        self.get_games_list();
-    }
-
-    // Returns i if it is the player's token, else -1 (for computer)
-    fn player_token (&self) -> i8 {
-        if self.move_num %2 == 0 {
-            return 1;
-        } else {
-            return -1;
+        // Save game using API, online games are saved by the room server
+        if self.online_player.is_none() {
+            self.save_game();
         }
     }
 
@@ -585,12 +697,12 @@ impl TootOttoHuman {
     fn print(&self) {
         let (mut msg, mut disc_msg) = ("".to_string(), "".to_string());
         msg.push_str("\n");
-        msg.push_str(format!("Move: {}", self.move_num).as_str());
+        msg.push_str(format!("Move: {}", self.state.move_num()).as_str());
         msg.push_str("\n");
         for i in 0..4 {
             for j in 0..6 {
-                msg.push_str(format!(" {}", self.board.tokens[i][j]).as_str());
-                disc_msg.push_str(format!(" {}", self.board.disc_map[i][j]).as_str());
+                msg.push_str(format!(" {}", self.state.board().tokens[i][j]).as_str());
+                disc_msg.push_str(format!(" {}", self.state.board().disc_map[i][j]).as_str());
             }
             msg.push_str("\n");
             disc_msg.push_str("\n");
@@ -599,49 +711,32 @@ impl TootOttoHuman {
         log!("{}", disc_msg);
     }
 
-    fn get_games_list(&mut self) {
-        // Create GET request for list of games
-        let get_request = Request::get("http://localhost:8000/list-games")
-            .body(Nothing)
-            .unwrap();
-
-        // Create task for FetchService
-        let task = FetchService::new().fetch(
-            get_request,
-            self.link.callback(|response: Response<Json<Result<Vec<Game>, Error>>>| {
-                if let (meta, Json(Ok(body))) = response.into_parts() {
-                    if meta.status.is_success() {
-                        return Msg::GetGamesList(body);
-                    }
-                }
-                Msg::SaveError
-            }),
-        );
-
-        // Store reference to task
-        self.save_task = Some(task);
-    }
-
     fn save_game(&mut self) {
         // Create JSON representation of game to save
         let json_game = json!{self.game};
 
         // Create POST request to save game
-        let post_request = Request::post("http://localhost:8000/insert-game")
-            .header("Content-Type", "application/json")
+        let mut post_request = Request::post(api::url("/insert-game"))
+            .header("Content-Type", "application/json");
+        // Games played under an account are only accepted from that account
+        if let Some(account) = &self.account {
+            post_request = post_request.header("Authorization", account.bearer());
+        }
+        let post_request = post_request
             .body(Json(&json_game))
             .expect("Failed to build request.");
 
         // Create save task
         let task = FetchService::new().fetch(
             post_request,
-            self.link.callback(|response: Response<Result<String, Error>>| {
+            self.link.callback(|response: Response<Json<Result<Game, Error>>>| {
                 log!("In callback function");
-                if response.status().is_success() {
-                    Msg::GameSaved
-                } else {
-                    Msg::SaveError
+                if let (meta, Json(Ok(saved))) = response.into_parts() {
+                    if meta.status.is_success() {
+                        return Msg::GameSaved(saved);
+                    }
                 }
+                Msg::SaveError
             }),
         );
 
@@ -649,23 +744,88 @@ impl TootOttoHuman {
         self.save_task = Some(task);
     }
 
+    fn open_room(&mut self, message: ClientMessage) {
+        let received = self.link.callback(Msg::RoomMessage);
+        let status = self.link.callback(Msg::RoomStatus);
+        match RoomConnection::open(message, received, status) {
+            Ok(room) => {
+                self.room = Some(room);
+                self.online_status = "Connecting to the game server...".into();
+            },
+            Err(e) => alert(&format!("Cannot reach the game server: {}", e)),
+        }
+    }
+
+    fn room_message(&mut self, message: Result<ServerMessage, Error>) {
+        match message {
+            Ok(ServerMessage::Created { code }) => {
+                self.online_status = format!("Room {} is open, share the code with your opponent", code);
+                self.room_code = code;
+            },
+            Ok(ServerMessage::Started { game, you }) => self.start_online(game, you),
+            Ok(ServerMessage::Moved(mv)) => {
+                if let Some(letter) = mv.letter.and_then(Letter::from_char) {
+                    self.paused = false;
+                    self.action(mv.column as usize, letter);
+                }
+            },
+            Ok(ServerMessage::Rejected { reason }) => {
+                alert(&reason);
+                // A room that could not be opened or joined is no use
+                if !self.game_started {
+                    self.room = None;
+                    self.online_status.clear();
+                }
+            },
+            Ok(ServerMessage::OpponentLeft) => {
+                self.room = None;
+                if !self.won {
+                    alert("Your opponent left the game");
+                    self.reset();
+                }
+            },
+            Err(e) => log!("Unreadable message from the game server: {}", e),
+        }
+    }
+
+    // Both players are in the room, so take its settings and start
+    fn start_online(&mut self, game: Game, you: Player) {
+        self.game = Game {
+            player1_color: self.player1_color.clone(),
+            player2_color: self.player2_color.clone(),
+            ..game
+        };
+        self.online_player = Some(you);
+        self.online_status = match you {
+            Player::One => "Playing online as Player 1, you move first".into(),
+            Player::Two => "Playing online as Player 2".into(),
+        };
+        self.game_started = true;
+        self.draw_board();
+    }
+
     fn reset(&mut self) {
         self.clear();
         self.game = Game {
-            game_number: 0, // placeholder, when game is saved this can be set
+            game_number: 0, // placeholder, the backend numbers the game when it is saved
             game_type: "TOOT-OTTO".into(),
-            player1_name: "".into(),
+            player1_name: session::player_name(&self.account),
             player2_name: "".into(),
             winner_name: "".into(),
-            game_date: 0 // placeholder, when game is saved this can be set
+            game_date: 0, // placeholder, when game is saved this can be set
+            ..Game::default()
         };
-        self.selected_disc = 'T';
+        self.selected_disc = Letter::T;
         self.game_started = false;
-        self.board.tokens = [[0; 6]; 4];
-        self.board.disc_map = [['0'; 6]; 4];
-        self.move_num = 0;
+        self.state = TootOttoState::new();
         self.won = false;
         self.paused = false;
+        self.redo.clear();
+        self.show_notation = false;
+        self.room = None;
+        self.online_player = None;
+        self.room_code.clear();
+        self.online_status.clear();
     }
 
     fn context(&self) -> &CanvasRenderingContext2d {
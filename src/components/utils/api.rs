@@ -0,0 +1,11 @@
+/// Address of the backend, taken from `API_BASE` when the app is built so a
+/// deployed frontend can point at its own server
+pub const API_BASE: &str = match option_env!("API_BASE") {
+    Some(base) => base,
+    None => "http://localhost:8000",
+};
+
+/// Full URL of a backend route, with `path` starting at the `/`
+pub fn url(path: &str) -> String {
+    format!("{}{}", API_BASE.trim_end_matches('/'), path)
+}
@@ -1 +1,4 @@
-pub mod table_builder;
\ No newline at end of file
+pub mod api;
+pub mod table_builder;
+pub mod online;
+pub mod session;
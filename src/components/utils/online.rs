@@ -0,0 +1,43 @@
+use yew::prelude::*;
+use yew::format::Json;
+use yew::services::websocket::{WebSocketService, WebSocketStatus, WebSocketTask};
+use anyhow::Error;
+use crate::models::online::{ClientMessage, ServerMessage};
+
+// Room server started alongside the backend, taken from `ROOMS_URL` when the app is built
+const ROOMS_URL: &str = match option_env!("ROOMS_URL") {
+    Some(url) => url,
+    None => "ws://localhost:8001",
+};
+
+/// Connection to the game room server for online play. The first message is
+/// held back until the socket has opened.
+pub struct RoomConnection {
+    task: WebSocketTask,
+    pending: Option<ClientMessage>
+}
+
+impl RoomConnection {
+    pub fn open(
+        first: ClientMessage,
+        received: Callback<Result<ServerMessage, Error>>,
+        status: Callback<WebSocketStatus>
+    ) -> Result<Self, String> {
+        let received = received.reform(|Json(message)| message);
+        let task = WebSocketService::new()
+            .connect(ROOMS_URL, received, status)
+            .map_err(|e| e.to_string())?;
+        Ok(RoomConnection { task, pending: Some(first) })
+    }
+
+    /// Send the first message, once the socket reports `WebSocketStatus::Opened`
+    pub fn opened(&mut self) {
+        if let Some(message) = self.pending.take() {
+            self.send(&message);
+        }
+    }
+
+    pub fn send(&mut self, message: &ClientMessage) {
+        self.task.send(Json(message));
+    }
+}
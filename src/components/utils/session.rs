@@ -0,0 +1,31 @@
+use yew::format::Json;
+use yew::services::storage::{Area, StorageService};
+use anyhow::Error;
+use crate::models::account::Session;
+
+// Local storage key the logged in account is kept under between visits
+const SESSION_KEY: &str = "connect4.session";
+
+/// The account logged in on this browser, if any
+pub fn current() -> Option<Session> {
+    let storage = StorageService::new(Area::Local).ok()?;
+    let Json(session): Json<Result<Session, Error>> = storage.restore(SESSION_KEY);
+    session.ok()
+}
+
+pub fn store(session: &Session) {
+    if let Ok(mut storage) = StorageService::new(Area::Local) {
+        storage.store(SESSION_KEY, Json(session));
+    }
+}
+
+pub fn clear() {
+    if let Ok(mut storage) = StorageService::new(Area::Local) {
+        storage.remove(SESSION_KEY);
+    }
+}
+
+/// Name games are played under by `account`, or blank for the player to type
+pub fn player_name(account: &Option<Session>) -> String {
+    account.as_ref().map(|account| account.username.clone()).unwrap_or_default()
+}
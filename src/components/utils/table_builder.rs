@@ -1,17 +1,18 @@
 use yew::prelude::*;
 use chrono::NaiveDateTime;
 use crate::models::game::Game;
+use crate::models::rating::Rating;
 use std::collections::HashMap;
 
 /**
  * Game History Table
  */
 
-fn render_gh_row(games: &Vec<Game>, index: usize) -> Html {
+fn render_gh_row(games: &Vec<Game>, index: usize, offset: usize, on_select: &Callback<usize>) -> Html {
     match games.get(index) {
         Some(game) => html!{
-            <tr>
-                <td>{{ index + 1 }}</td>
+            <tr style="cursor:pointer;" onclick=on_select.reform(move |_| index)>
+                <td>{{ offset + index + 1 }}</td>
                 <td>{{ game.game_type.clone() }}</td>
                 <td>{{ game.player1_name.clone() }}</td>
                 <td>{{ game.player2_name.clone() }}</td>
@@ -23,22 +24,24 @@ fn render_gh_row(games: &Vec<Game>, index: usize) -> Html {
     }
 }
 
-fn render_all_gh_rows(games: &Vec<Game>, index: usize) -> Html {
+fn render_all_gh_rows(games: &Vec<Game>, index: usize, offset: usize, on_select: &Callback<usize>) -> Html {
     if index == games.len() - 1 {
-        render_gh_row(games, index)
+        render_gh_row(games, index, offset, on_select)
     } else {
         html! {
             <>
-                {{ render_gh_row(games, index) }}
-                {{ render_all_gh_rows(games, index + 1) }}
+                {{ render_gh_row(games, index, offset, on_select) }}
+                {{ render_all_gh_rows(games, index + 1, offset, on_select) }}
             </>
         }
     }
 }
 
-pub fn render_gh_table(games: &Vec<Game>) -> Html {
+// Clicking a row sends its index to `on_select`. Rows are numbered from
+// `offset` + 1, so later pages carry on from earlier ones.
+pub fn render_gh_table(games: &Vec<Game>, offset: usize, on_select: &Callback<usize>) -> Html {
     if games.len() != 0 {
-        return render_all_gh_rows(games, 0);
+        return render_all_gh_rows(games, 0, offset, on_select);
     }
     return html!{<></>}
 }
@@ -152,6 +155,60 @@ pub fn render_gw_table(games: &Vec<Game>) -> Html {
     return html!{<></>}
 }
 
+// Ratings
+
+// Size of the rating trend drawn in each row
+const SPARKLINE_WIDTH: f64 = 120.0;
+const SPARKLINE_HEIGHT: f64 = 24.0;
+
+fn render_sparkline(history: &[f64]) -> Html {
+    if history.len() < 2 {
+        return html!{<></>}
+    }
+    let low = history.iter().cloned().fold(f64::INFINITY, f64::min);
+    let high = history.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    // A flat history is drawn along the middle
+    let range = if high > low { high - low } else { 1.0 };
+    let offset = if high > low { 0.0 } else { SPARKLINE_HEIGHT / 2.0 };
+    let step = SPARKLINE_WIDTH / (history.len() - 1) as f64;
+    let points: Vec<String> = history.iter().enumerate().map(|(index, rating)| {
+        let y = SPARKLINE_HEIGHT - 2.0 - (rating - low) / range * (SPARKLINE_HEIGHT - 4.0) - offset;
+        format!("{:.1},{:.1}", index as f64 * step, y.max(0.0))
+    }).collect();
+    html!{
+        <svg width=SPARKLINE_WIDTH.to_string() height=SPARKLINE_HEIGHT.to_string()>
+            <polyline points=points.join(" ") fill="none" stroke="red" stroke-width="2"/>
+        </svg>
+    }
+}
+
+fn render_rating_row(index: usize, rating: &Rating) -> Html {
+    let name = if rating.anchored {
+        format!("{} (fixed)", rating.player)
+    } else {
+        rating.player.clone()
+    };
+    html!{
+        <tr>
+            <td>{{ index + 1 }}</td>
+            <td>{name}</td>
+            <td>{{ rating.rating.round() }}</td>
+            <td>{{ rating.wins }}</td>
+            <td>{{ rating.losses }}</td>
+            <td>{{ rating.draws }}</td>
+            <td>{{ render_sparkline(&rating.history) }}</td>
+        </tr>
+    }
+}
+
+pub fn render_ratings_table(ratings: &[Rating]) -> Html {
+    html!{
+        <>
+            { for ratings.iter().enumerate().map(|(index, rating)| render_rating_row(index, rating)) }
+        </>
+    }
+}
+
 // TODO: Fix time conversion to Local timezone
 
 fn convert_timestamp(timestamp: i64) -> String {
@@ -0,0 +1,9 @@
+use wasm_bindgen::prelude::*;
+use yew::agent::Threaded;
+use connect4_with_tootand_otto::agents::ai::AiAgent;
+
+// Runs as soon as the worker has loaded the module
+#[wasm_bindgen(start)]
+pub fn run_worker() {
+    AiAgent::register();
+}
@@ -9,6 +9,11 @@ module.exports = {
   },
   mode: "development",
   plugins: [
-    new CopyWebpackPlugin(['index.html'])
+    new CopyWebpackPlugin([
+      'index.html',
+      // The computer players run in a Web Worker built from the `worker` crate
+      { from: '../worker/pkg/worker.js' },
+      { from: '../worker/pkg/worker_bg.wasm' },
+    ])
   ],
 };